@@ -1,4 +1,4 @@
-use std::sync::Arc;
+use std::{path::PathBuf, sync::Arc};
 
 use nimiq_account::{Accounts, BlockLog};
 use nimiq_block::Block;
@@ -72,6 +72,9 @@ pub struct BlockchainConfig {
     pub max_epochs_stored: u32,
     /// Enables/Disables indices in the history store.
     pub index_history: bool,
+    /// Path of a read-only replica of the database (e.g. a filesystem snapshot) to serve
+    /// history chunks from. If `None`, everything is served from the primary database.
+    pub history_read_replica: Option<PathBuf>,
 }
 
 impl Default for BlockchainConfig {
@@ -80,6 +83,7 @@ impl Default for BlockchainConfig {
             keep_history: true,
             max_epochs_stored: Policy::MIN_EPOCHS_STORED,
             index_history: true,
+            history_read_replica: None,
         }
     }
 }
@@ -136,18 +140,16 @@ impl Blockchain {
             return Err(BlockchainError::InvalidGenesisBlock);
         }
 
-        let history_store = if config.index_history {
-            Arc::new(HistoryStoreProxy::WithIndex(HistoryStoreIndex::new(
-                env.clone(),
-                network_id,
-            )))
+        let mut history_store = if config.index_history {
+            HistoryStoreProxy::WithIndex(HistoryStoreIndex::new(env.clone(), network_id))
         } else {
-            Arc::new(HistoryStoreProxy::WithoutIndex(Box::new(HistoryStore::new(
-                env.clone(),
-                network_id,
-            ))
-                as Box<dyn HistoryInterface + Sync + Send>))
+            HistoryStoreProxy::WithoutIndex(Box::new(HistoryStore::new(env.clone(), network_id))
+                as Box<dyn HistoryInterface + Sync + Send>)
         };
+        if let Some(path) = &config.history_read_replica {
+            history_store = history_store.with_read_replica(path, network_id);
+        }
+        let history_store = Arc::new(history_store);
 
         let chain_store = ChainStore::new(env.clone(), Arc::clone(&history_store));
 
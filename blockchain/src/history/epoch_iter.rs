@@ -0,0 +1,106 @@
+use std::{cmp, collections::VecDeque};
+
+use nimiq_database::{
+    mdbx::MdbxReadTransaction,
+    traits::{DupReadCursor, ReadTransaction},
+};
+use nimiq_transaction::historic_transaction::HistoricTransaction;
+use thiserror::Error;
+
+use super::history_store::HistoricTransactionTable;
+
+/// The number of historic transactions read from the database at once.
+const BATCH_SIZE: u32 = 256;
+
+/// Errors that can occur while iterating over the history of an epoch.
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum HistoryIterError {
+    #[error("Historic transaction {leaf_index} of epoch {epoch_number} is missing")]
+    MissingLeaf { epoch_number: u32, leaf_index: u32 },
+}
+
+/// Iterator over the historic transactions of an epoch in leaf order.
+/// Historic transactions are read lazily in small batches from the database. The iterator holds
+/// a read transaction for its whole lifetime, so it always yields a consistent view of the epoch.
+/// After yielding an error, the iterator ends.
+pub struct EpochHistoryIter<'db> {
+    txn: MdbxReadTransaction<'db>,
+    hist_tx_table: HistoricTransactionTable,
+    epoch_number: u32,
+    /// The leaf index of the next historic transaction to be read from the database.
+    next_leaf_index: u32,
+    /// The number of leaves of the history tree of the epoch.
+    num_leaves: u32,
+    buffer: VecDeque<HistoricTransaction>,
+    error: Option<HistoryIterError>,
+}
+
+impl<'db> EpochHistoryIter<'db> {
+    pub(super) fn new(
+        txn: MdbxReadTransaction<'db>,
+        hist_tx_table: HistoricTransactionTable,
+        epoch_number: u32,
+        num_leaves: u32,
+    ) -> Self {
+        EpochHistoryIter {
+            txn,
+            hist_tx_table,
+            epoch_number,
+            next_leaf_index: 0,
+            num_leaves,
+            buffer: VecDeque::new(),
+            error: None,
+        }
+    }
+
+    /// Reads the next batch of historic transactions into the buffer.
+    fn read_batch(&mut self) {
+        let start = self.next_leaf_index;
+        let end = cmp::min(start + BATCH_SIZE, self.num_leaves);
+
+        let mut cursor = self.txn.dup_cursor(&self.hist_tx_table);
+
+        for leaf_index in start..end {
+            let hist_tx = if leaf_index == start {
+                cursor.set_subkey(&self.epoch_number, &leaf_index)
+            } else {
+                // The entries are consecutive in the database, so we can just move to the next
+                // duplicate without seeking.
+                cursor.next_duplicate().map(|(_, hist_tx)| hist_tx)
+            };
+
+            match hist_tx {
+                Some(hist_tx) if hist_tx.index == leaf_index => {
+                    self.buffer.push_back(hist_tx.value)
+                }
+                _ => {
+                    self.error = Some(HistoryIterError::MissingLeaf {
+                        epoch_number: self.epoch_number,
+                        leaf_index,
+                    });
+                    self.next_leaf_index = self.num_leaves;
+                    return;
+                }
+            }
+        }
+
+        self.next_leaf_index = end;
+    }
+}
+
+impl<'db> Iterator for EpochHistoryIter<'db> {
+    type Item = Result<HistoricTransaction, HistoryIterError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.buffer.is_empty() && self.error.is_none() && self.next_leaf_index < self.num_leaves
+        {
+            self.read_batch();
+        }
+
+        // Historic transactions read before an error occurred are yielded first.
+        if let Some(hist_tx) = self.buffer.pop_front() {
+            return Some(Ok(hist_tx));
+        }
+        self.error.take().map(Err)
+    }
+}
@@ -13,6 +13,7 @@ use nimiq_genesis::NetworkId;
 use nimiq_hash::{Blake2bHash, Hash};
 use nimiq_mmr::{
     error::Error as MMRError,
+    hash::Hash as MMRHash,
     mmr::{
         partial::PartialMerkleMountainRange,
         position::leaf_number_to_index,
@@ -31,9 +32,14 @@ use nimiq_transaction::{
     inherent::Inherent,
     EquivocationLocator,
 };
+use thiserror::Error;
 
 use super::{
-    interface::HistoryInterface, utils::IndexedTransaction, validity_store::ValidityStore,
+    epoch_iter::EpochHistoryIter,
+    integrity::{IntegrityIssue, IntegrityReport},
+    interface::HistoryInterface,
+    utils::IndexedTransaction,
+    validity_store::ValidityStore,
 };
 use crate::history::{mmr_store::MMRStore, HistoryTreeChunk};
 
@@ -43,6 +49,18 @@ declare_table!(HistoryTreeTable, "HistoryTrees", u32 => u32 => Blake2bHash);
 declare_table!(HistoricTransactionTable, "HistoricTransactions", u32 => u32 => HistoricTransaction);
 declare_table!(LastLeafTable, "LastLeafIndexesByBlock", u32 => u32);
 
+/// Errors that can occur while reverting the history of a block.
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum RevertBlockError {
+    #[error("Block #{block_number} is not the last block in the history store (last block #{last_block_number})")]
+    NotLastBlock {
+        block_number: u32,
+        last_block_number: u32,
+    },
+    #[error("Failed to remove the history of block #{0}")]
+    RemoveFailed(u32),
+}
+
 /// A struct that contains databases to store history trees (which are Merkle Mountain Ranges
 /// constructed from the list of historic transactions in an epoch) and historic transactions (which
 /// are representations of transactions).
@@ -86,6 +104,19 @@ impl HistoryStore {
         store
     }
 
+    /// Creates a HistoryStore on a database that was opened in read-only mode.
+    /// No tables are created, so they must already exist in the database.
+    pub fn new_read_only(db: MdbxDatabase, network_id: NetworkId) -> Self {
+        HistoryStore {
+            validity_store: ValidityStore::new_read_only(db.clone()),
+            db,
+            network_id,
+            hist_tree_table: HistoryTreeTable,
+            hist_tx_table: HistoricTransactionTable,
+            last_leaf_table: LastLeafTable,
+        }
+    }
+
     /// Gets an historic transaction by its hash. Note that this hash is the leaf hash (see MMRHash)
     /// of the transaction, not a simple Blake2b hash of the transaction.
     pub(crate) fn get_historic_tx(
@@ -101,6 +132,124 @@ impl HistoryStore {
         Some(value.value)
     }
 
+    /// Returns an iterator over all historic transactions of the given epoch in leaf order.
+    /// The historic transactions are read lazily from the database within a read transaction
+    /// that is held for the lifetime of the iterator.
+    pub fn iter_epoch(&self, epoch_number: u32) -> EpochHistoryIter<'_> {
+        let txn = self.db.read_transaction();
+
+        let num_leaves = MerkleMountainRange::new(MMRStore::with_read_transaction(
+            &self.hist_tree_table,
+            &txn,
+            epoch_number,
+        ))
+        .num_leaves();
+
+        EpochHistoryIter::new(
+            txn,
+            self.hist_tx_table.clone(),
+            epoch_number,
+            num_leaves as u32,
+        )
+    }
+
+    /// Checks the integrity of the history of the given epochs without modifying the database.
+    /// For each epoch, all inner nodes of the history tree are recomputed from their children,
+    /// the stored historic transactions are checked against the leaves of the history tree and
+    /// the last leaf indexes of the blocks are checked against the stored historic transactions.
+    /// All inconsistencies found are returned in the report.
+    pub fn verify_integrity(
+        &self,
+        epochs: Range<u32>,
+        txn_option: Option<&MdbxReadTransaction>,
+    ) -> IntegrityReport {
+        let txn = txn_option.or_new(&self.db);
+        let mut report = IntegrityReport::default();
+
+        for epoch_number in epochs {
+            let tree = MerkleMountainRange::new(MMRStore::with_read_transaction(
+                &self.hist_tree_table,
+                &txn,
+                epoch_number,
+            ));
+            let num_leaves = tree.num_leaves() as u32;
+
+            report.num_epochs += 1;
+            report.num_leaves += num_leaves as usize;
+
+            // Recompute all inner nodes of the history tree.
+            for node_index in tree.find_inconsistent_nodes() {
+                report.issues.push(IntegrityIssue::InconsistentNode {
+                    epoch_number,
+                    node_index,
+                });
+            }
+
+            // Check the stored historic transactions against the leaves.
+            let mut next_leaf_index = 0;
+            let cursor = txn.dup_cursor(&self.hist_tx_table);
+            for (_, hist_tx) in cursor.into_iter_dup_of(&epoch_number) {
+                let leaf_index = hist_tx.index;
+                if leaf_index >= num_leaves {
+                    report.issues.push(IntegrityIssue::UnexpectedLeaf {
+                        epoch_number,
+                        leaf_index,
+                    });
+                    continue;
+                }
+
+                for missing_leaf_index in next_leaf_index..leaf_index {
+                    report.issues.push(IntegrityIssue::MissingLeaf {
+                        epoch_number,
+                        leaf_index: missing_leaf_index,
+                    });
+                }
+                next_leaf_index = leaf_index + 1;
+
+                let leaf_hash = MMRHash::<Blake2bHash>::hash(&hist_tx.value, 1);
+                if tree.get_leaf(leaf_index as usize) != Some(leaf_hash) {
+                    report.issues.push(IntegrityIssue::LeafHashMismatch {
+                        epoch_number,
+                        leaf_index,
+                    });
+                }
+            }
+            for missing_leaf_index in next_leaf_index..num_leaves {
+                report.issues.push(IntegrityIssue::MissingLeaf {
+                    epoch_number,
+                    leaf_index: missing_leaf_index,
+                });
+            }
+
+            // Check the last leaf indexes of the blocks of this epoch.
+            let Some(first_block) = Policy::first_block_of(epoch_number) else {
+                continue;
+            };
+            let mut cursor = txn.cursor(&self.last_leaf_table);
+            let mut entry = cursor.set_lowerbound_key(&first_block);
+            while let Some((block_number, leaf_index)) = entry {
+                if Policy::epoch_at(block_number) != epoch_number {
+                    break;
+                }
+
+                let is_valid = leaf_index < num_leaves
+                    && self
+                        .get_historic_tx(epoch_number, leaf_index, Some(&txn))
+                        .is_some_and(|hist_tx| hist_tx.block_number == block_number);
+                if !is_valid {
+                    report.issues.push(IntegrityIssue::InvalidLastLeafIndex {
+                        block_number,
+                        leaf_index,
+                    });
+                }
+
+                entry = cursor.next();
+            }
+        }
+
+        report
+    }
+
     fn get_historic_txns(
         &self,
         epoch_number: u32,
@@ -226,8 +375,14 @@ impl HistoryStore {
 
         let mut cursor = WriteTransaction::cursor(txn, &self.last_leaf_table);
 
-        let Some(first_block) = Policy::first_block_of(epoch_number) else {
-            return;
+        // The first epoch only consists of the genesis block.
+        let first_block = if epoch_number == 0 {
+            Policy::genesis_block_number()
+        } else {
+            let Some(first_block) = Policy::first_block_of(epoch_number) else {
+                return;
+            };
+            first_block
         };
 
         let Some((mut block_number, _value)) = cursor.set_lowerbound_key(&first_block) else {
@@ -323,6 +478,28 @@ impl HistoryStore {
         (start, end)
     }
 
+    /// Returns the number of historic transactions of the given block, which must be the last
+    /// block in the history store.
+    pub(crate) fn num_block_transactions_to_revert(
+        &self,
+        block_number: u32,
+        txn_option: Option<&MdbxReadTransaction>,
+    ) -> Result<u32, RevertBlockError> {
+        let txn = txn_option.or_new(&self.db);
+
+        if let Some(last_block_number) = self.get_last_leaf_block_number(Some(&txn)) {
+            if last_block_number > block_number {
+                return Err(RevertBlockError::NotLastBlock {
+                    block_number,
+                    last_block_number,
+                });
+            }
+        }
+
+        let (start, end) = self.get_indexes_for_block(block_number, Some(&txn));
+        Ok(end - start)
+    }
+
     /// Calculates the history tree root from a vector of historic transactions. It doesn't use the
     /// database, it is just used to check the correctness of the history root when syncing.
     pub(crate) fn _root_from_hist_txs(hist_txs: &[HistoricTransaction]) -> Option<Blake2bHash> {
@@ -697,6 +874,78 @@ impl HistoryInterface for HistoryStore {
         })
     }
 
+    fn prove_chunk_bounded(
+        &self,
+        epoch_number: u32,
+        verifier_block_number: u32,
+        start_leaf_index: usize,
+        max_bytes: usize,
+        txn_option: Option<&MdbxReadTransaction>,
+    ) -> Option<(HistoryTreeChunk, bool)> {
+        let txn = txn_option.or_new(&self.db);
+
+        // Get history tree for given epoch.
+        let tree = MerkleMountainRange::new(MMRStore::with_read_transaction(
+            &self.hist_tree_table,
+            &txn,
+            epoch_number,
+        ));
+
+        // Calculate number of nodes in the verifier's history tree.
+        let leaf_count = self.length_at(verifier_block_number, Some(&txn))? as usize;
+        let number_of_nodes = leaf_number_to_index(leaf_count);
+
+        if start_leaf_index >= leaf_count {
+            return None;
+        }
+
+        // Collect complete historic transactions as long as they fit into the limit on their own.
+        // The proof is accounted for below.
+        let mut hist_txs = vec![];
+        let mut hist_txs_size = 0;
+        let mut cursor = txn.dup_cursor(&self.hist_tx_table);
+
+        for leaf_index in start_leaf_index as u32..leaf_count as u32 {
+            let hist_tx = if leaf_index == start_leaf_index as u32 {
+                cursor
+                    .set_subkey(&epoch_number, &leaf_index)
+                    .expect("Transaction not found")
+            } else {
+                let (epoch, hist_tx) = cursor.next_duplicate().expect("Transaction not found");
+                assert_eq!(epoch, epoch_number, "Epoch number mismatch");
+                hist_tx
+            };
+            assert_eq!(hist_tx.index, leaf_index, "Index mismatch");
+
+            hist_txs_size += hist_tx.value.serialized_size();
+            if hist_txs_size > max_bytes {
+                break;
+            }
+            hist_txs.push(hist_tx.value);
+        }
+
+        // Drop trailing historic transactions until the chunk including its proof fits.
+        while !hist_txs.is_empty() {
+            let end = start_leaf_index + hist_txs.len();
+            let proof = tree
+                .prove_range(start_leaf_index..end, Some(number_of_nodes), false)
+                .ok()?;
+
+            let mut chunk = HistoryTreeChunk {
+                proof,
+                history: hist_txs,
+            };
+            if chunk.serialized_size() <= max_bytes {
+                return Some((chunk, end < leaf_count));
+            }
+
+            chunk.history.pop();
+            hist_txs = chunk.history;
+        }
+
+        None
+    }
+
     /// Creates a new history tree from chunks and returns the root hash.
     fn tree_from_chunks(
         &self,
@@ -873,6 +1122,23 @@ impl HistoryInterface for HistoryStore {
         Some(total_size)
     }
 
+    fn revert_block(
+        &self,
+        txn: &mut MdbxWriteTransaction,
+        block_number: u32,
+    ) -> Result<u32, RevertBlockError> {
+        let num_hist_txs = self.num_block_transactions_to_revert(block_number, Some(txn))?;
+        if num_hist_txs > 0 {
+            self.remove_partial_history(txn, Policy::epoch_at(block_number), num_hist_txs as usize)
+                .ok_or(RevertBlockError::RemoveFailed(block_number))?;
+        }
+
+        self.validity_store
+            .delete_block_transactions(txn, block_number);
+
+        Ok(num_hist_txs)
+    }
+
     fn history_store_range(&self, txn_option: Option<&MdbxReadTransaction>) -> (u32, u32) {
         let txn = txn_option.or_new(&self.db);
 
@@ -897,6 +1163,7 @@ mod tests {
     };
 
     use super::*;
+    use crate::history::{ChunkVerificationError, HistorySnapshotError};
 
     #[test]
     fn prove_num_leaves_works() {
@@ -1107,6 +1374,399 @@ mod tests {
         assert_eq!(real_root_1, calc_root_1);
     }
 
+    #[test]
+    fn prove_chunk_bounded_works() {
+        let genesis_block_number = Policy::genesis_block_number();
+        let epoch_number = Policy::epoch_at(genesis_block_number + 1);
+        // Initialize History Store.
+        let env = MdbxDatabase::new_volatile(Default::default()).unwrap();
+        let history_store = HistoryStore::new(env.clone(), NetworkId::UnitAlbatross);
+
+        // Create historic transactions.
+        let hist_txs = gen_hist_txs();
+
+        // Add historic transactions to History Store.
+        let mut txn = env.write_transaction();
+        history_store.add_to_history(&mut txn, genesis_block_number + 0, &hist_txs[..3]);
+        history_store.add_to_history(&mut txn, genesis_block_number + 2, &hist_txs[3..]);
+        let root = history_store
+            .get_history_tree_root(genesis_block_number + 2, Some(&txn))
+            .unwrap();
+
+        // A large enough limit returns all remaining historic transactions.
+        let (chunk, more) = history_store
+            .prove_chunk_bounded(
+                epoch_number,
+                genesis_block_number + 2,
+                2,
+                usize::MAX,
+                Some(&txn),
+            )
+            .unwrap();
+        assert_eq!(chunk.history, hist_txs[5..]);
+        assert!(!more);
+        assert_eq!(chunk.verify(&root, 2), Some(true));
+
+        // A limit that is too small for a single historic transaction returns nothing.
+        assert!(history_store
+            .prove_chunk_bounded(
+                epoch_number,
+                genesis_block_number + 2,
+                0,
+                hist_txs[3].serialized_size(),
+                Some(&txn),
+            )
+            .is_none());
+
+        // The size is computed without serializing, but matches the serialization.
+        let full_size = chunk.serialized_size();
+        assert_eq!(full_size, chunk.serialize_to_vec().len());
+
+        // Otherwise as many complete historic transactions as fit are returned.
+        let (chunk, more) = history_store
+            .prove_chunk_bounded(
+                epoch_number,
+                genesis_block_number + 2,
+                2,
+                full_size - 1,
+                Some(&txn),
+            )
+            .unwrap();
+        assert!(chunk.serialized_size() < full_size);
+        assert!(!chunk.history.is_empty());
+        assert_eq!(chunk.history, hist_txs[5..5 + chunk.history.len()]);
+        assert!(more);
+        assert_eq!(chunk.verify(&root, 2), Some(true));
+
+        // Starting beyond the last leaf returns nothing.
+        assert!(history_store
+            .prove_chunk_bounded(
+                epoch_number,
+                genesis_block_number + 2,
+                hist_txs.len() - 3,
+                usize::MAX,
+                Some(&txn),
+            )
+            .is_none());
+    }
+
+    #[test]
+    fn verify_chunk_batch_works() {
+        let genesis_block_number = Policy::genesis_block_number();
+        // Initialize History Store.
+        let env = MdbxDatabase::new_volatile(Default::default()).unwrap();
+        let history_store = HistoryStore::new(env.clone(), NetworkId::UnitAlbatross);
+
+        // Create historic transactions.
+        let hist_txs = gen_hist_txs();
+
+        // Add historic transactions to History Store.
+        let mut txn = env.write_transaction();
+        history_store.add_to_history(&mut txn, genesis_block_number + 2, &hist_txs[3..]);
+        let root = history_store
+            .get_history_tree_root(genesis_block_number + 2, Some(&txn))
+            .unwrap();
+
+        let chunk = |chunk_index| {
+            history_store
+                .prove_chunk(1, genesis_block_number + 2, 3, chunk_index, Some(&txn))
+                .unwrap()
+        };
+
+        // Verify method works.
+        let chunks = vec![chunk(0), chunk(1), chunk(2)];
+        assert_eq!(HistoryTreeChunk::verify_batch(&chunks, &root, 0), Ok(()));
+        assert_eq!(
+            HistoryTreeChunk::verify_batch(&chunks[1..], &root, 3),
+            Ok(())
+        );
+
+        // Gaps and wrong starting points are detected.
+        let chunks = vec![chunk(0), chunk(2)];
+        assert_eq!(
+            HistoryTreeChunk::verify_batch(&chunks, &root, 0),
+            Err(ChunkVerificationError::InvalidProof(1))
+        );
+        assert_eq!(
+            HistoryTreeChunk::verify_batch(&chunks, &root, 3),
+            Err(ChunkVerificationError::InvalidProof(0))
+        );
+
+        // Empty chunks are rejected.
+        let mut empty_chunk = chunk(2);
+        empty_chunk.history.clear();
+        let chunks = vec![chunk(2), empty_chunk];
+        assert_eq!(
+            HistoryTreeChunk::verify_batch(&chunks, &root, 6),
+            Err(ChunkVerificationError::EmptyChunk(1))
+        );
+
+        // Chunks proven against a different tree are detected.
+        let chunks = vec![
+            chunk(0),
+            history_store
+                .prove_chunk(1, genesis_block_number + 1, 1, 1, Some(&txn))
+                .unwrap(),
+        ];
+        assert_eq!(
+            HistoryTreeChunk::verify_batch(&chunks, &root, 0),
+            Err(ChunkVerificationError::TreeSizeMismatch(1))
+        );
+    }
+
+    #[test]
+    fn iter_epoch_works() {
+        let genesis_block_number = Policy::genesis_block_number();
+        let epoch_number = Policy::epoch_at(genesis_block_number + 1);
+        // Initialize History Store.
+        let env = MdbxDatabase::new_volatile(Default::default()).unwrap();
+        let history_store = HistoryStore::new(env.clone(), NetworkId::UnitAlbatross);
+
+        // Create more historic transactions than are read in a single batch.
+        let hist_txs: Vec<_> = (0..600)
+            .map(|i| create_transaction(genesis_block_number + 1, i))
+            .collect();
+
+        // Add historic transactions to History Store.
+        let mut txn = env.write_transaction();
+        history_store.add_to_history(&mut txn, genesis_block_number + 1, &hist_txs);
+        txn.commit();
+
+        // Verify method works.
+        let iterated = history_store
+            .iter_epoch(epoch_number)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(iterated, hist_txs);
+
+        assert_eq!(history_store.iter_epoch(epoch_number + 1).count(), 0);
+    }
+
+    #[test]
+    fn revert_block_works() {
+        let genesis_block_number = Policy::genesis_block_number();
+        // Initialize History Store.
+        let env = MdbxDatabase::new_volatile(Default::default()).unwrap();
+        let history_store = HistoryStore::new(env.clone(), NetworkId::UnitAlbatross);
+
+        // Add historic transactions to History Store.
+        let hist_txs = gen_hist_txs();
+        let mut txn = env.write_transaction();
+        let (root_before, _) = history_store
+            .add_to_history(&mut txn, genesis_block_number + 1, &hist_txs[3..5])
+            .unwrap();
+        history_store.add_to_history(&mut txn, genesis_block_number + 2, &hist_txs[5..]);
+
+        // Only the last block can be reverted.
+        assert_eq!(
+            history_store.revert_block(&mut txn, genesis_block_number + 1),
+            Err(RevertBlockError::NotLastBlock {
+                block_number: genesis_block_number + 1,
+                last_block_number: genesis_block_number + 2,
+            })
+        );
+
+        // Verify method works.
+        assert_eq!(
+            history_store.revert_block(&mut txn, genesis_block_number + 2),
+            Ok(6)
+        );
+        assert_eq!(
+            history_store.get_history_tree_root(genesis_block_number + 1, Some(&txn)),
+            Some(root_before)
+        );
+        assert!(history_store
+            .get_block_transactions(genesis_block_number + 2, Some(&txn))
+            .is_empty());
+        assert_eq!(
+            history_store.get_last_leaf_block_number(Some(&txn)),
+            Some(genesis_block_number + 1)
+        );
+        assert!(!history_store.tx_in_validity_window(&hist_txs[5].tx_hash(), Some(&txn)));
+
+        // Reverting a block without historic transactions doesn't remove anything.
+        assert_eq!(
+            history_store.revert_block(&mut txn, genesis_block_number + 2),
+            Ok(0)
+        );
+        assert_eq!(
+            history_store
+                .num_epoch_transactions(Policy::epoch_at(genesis_block_number + 1), Some(&txn)),
+            2
+        );
+    }
+
+    #[test]
+    fn export_and_import_epoch_works() {
+        let genesis_block_number = Policy::genesis_block_number();
+        let epoch_number = Policy::epoch_at(genesis_block_number + 1);
+        // Initialize History Stores.
+        let env = MdbxDatabase::new_volatile(Default::default()).unwrap();
+        let history_store = HistoryStore::new(env.clone(), NetworkId::UnitAlbatross);
+        let other_env = MdbxDatabase::new_volatile(Default::default()).unwrap();
+        let other_history_store = HistoryStore::new(other_env.clone(), NetworkId::UnitAlbatross);
+
+        // Add historic transactions to History Store.
+        let hist_txs = gen_hist_txs();
+        let mut txn = env.write_transaction();
+        let (root, _) = history_store
+            .add_to_history(&mut txn, genesis_block_number + 2, &hist_txs[3..])
+            .unwrap();
+
+        let mut snapshot = vec![];
+        history_store
+            .export_epoch(epoch_number, &mut snapshot, Some(&txn))
+            .unwrap();
+
+        // The snapshot must match the expected root.
+        let mut other_txn = other_env.write_transaction();
+        assert!(matches!(
+            other_history_store.import_epoch(
+                &mut other_txn,
+                &mut &snapshot[..],
+                &Blake2bHash::default()
+            ),
+            Err(HistorySnapshotError::RootMismatch)
+        ));
+        assert!(matches!(
+            other_history_store.import_epoch(&mut other_txn, &mut &b"garbage"[..], &root),
+            Err(HistorySnapshotError::InvalidMagic)
+        ));
+        assert_eq!(
+            other_history_store.num_epoch_transactions(epoch_number, Some(&other_txn)),
+            0
+        );
+
+        // Verify method works.
+        assert_eq!(
+            other_history_store
+                .import_epoch(&mut other_txn, &mut &snapshot[..], &root)
+                .unwrap(),
+            epoch_number
+        );
+        assert_eq!(
+            other_history_store.get_history_tree_root(genesis_block_number + 1, Some(&other_txn)),
+            Some(root.clone())
+        );
+        assert_eq!(
+            other_history_store.get_epoch_transactions(epoch_number, Some(&other_txn)),
+            hist_txs[3..]
+        );
+        assert_eq!(
+            other_history_store.get_block_transactions(genesis_block_number + 1, Some(&other_txn)),
+            hist_txs[3..5]
+        );
+
+        // An epoch can only be imported once.
+        assert!(matches!(
+            other_history_store.import_epoch(&mut other_txn, &mut &snapshot[..], &root),
+            Err(HistorySnapshotError::EpochNotEmpty(_))
+        ));
+    }
+
+    #[test]
+    fn history_root_is_updated_incrementally() {
+        let genesis_block_number = Policy::genesis_block_number();
+        // Initialize History Store.
+        let env = MdbxDatabase::new_volatile(Default::default()).unwrap();
+        let history_store = HistoryStore::new(env.clone(), NetworkId::UnitAlbatross);
+
+        // Create historic transactions, one per block.
+        let hist_txs: Vec<_> = (1..=20)
+            .map(|i| create_transaction(genesis_block_number + i, i as u64))
+            .collect();
+
+        // Every appended block updates the root to the one of the rebuilt tree.
+        let mut txn = env.write_transaction();
+        for (i, hist_tx) in hist_txs.iter().enumerate() {
+            let (root, _) = history_store
+                .add_to_history(&mut txn, hist_tx.block_number, &[hist_tx.clone()])
+                .unwrap();
+            assert_eq!(
+                Some(root),
+                HistoryStore::_root_from_hist_txs(&hist_txs[..=i])
+            );
+        }
+
+        // Reverting blocks restores the previous roots.
+        history_store
+            .remove_partial_history(&mut txn, 1, 5)
+            .unwrap();
+        assert_eq!(
+            history_store.get_history_tree_root(genesis_block_number + 15, Some(&txn)),
+            HistoryStore::_root_from_hist_txs(&hist_txs[..15])
+        );
+
+        // Appending after a revert continues from the reverted state.
+        let hist_tx = create_transaction(genesis_block_number + 16, 100);
+        let (root, _) = history_store
+            .add_to_history(&mut txn, genesis_block_number + 16, &[hist_tx.clone()])
+            .unwrap();
+        let mut expected_hist_txs = hist_txs[..15].to_vec();
+        expected_hist_txs.push(hist_tx);
+        assert_eq!(
+            Some(root),
+            HistoryStore::_root_from_hist_txs(&expected_hist_txs)
+        );
+    }
+
+    #[test]
+    fn verify_integrity_works() {
+        let genesis_block_number = Policy::genesis_block_number();
+        // Initialize History Store.
+        let env = MdbxDatabase::new_volatile(Default::default()).unwrap();
+        let history_store = HistoryStore::new(env.clone(), NetworkId::UnitAlbatross);
+
+        // Create historic transactions.
+        let hist_txs = gen_hist_txs();
+
+        // Add historic transactions to History Store.
+        let mut txn = env.write_transaction();
+        history_store.add_to_history(&mut txn, genesis_block_number + 0, &hist_txs[..3]);
+        history_store.add_to_history(&mut txn, genesis_block_number + 2, &hist_txs[3..]);
+
+        // Verify method works.
+        let report = history_store.verify_integrity(0..3, Some(&txn));
+        assert!(report.is_ok());
+        assert_eq!(report.num_epochs, 3);
+        assert_eq!(report.num_leaves, hist_txs.len());
+
+        // Remove a historic transaction behind the history store's back.
+        txn.remove_item(
+            &history_store.hist_tx_table,
+            &1,
+            &IndexedTransaction {
+                index: 1,
+                value: hist_txs[4].clone(),
+            },
+        );
+        // Point a block to a leaf of another block.
+        txn.put(
+            &history_store.last_leaf_table,
+            &(genesis_block_number + 2),
+            &0,
+        );
+
+        let report = history_store.verify_integrity(0..2, Some(&txn));
+        assert_eq!(
+            report.issues,
+            vec![
+                IntegrityIssue::MissingLeaf {
+                    epoch_number: 1,
+                    leaf_index: 1,
+                },
+                IntegrityIssue::InvalidLastLeafIndex {
+                    block_number: genesis_block_number + 1,
+                    leaf_index: 1,
+                },
+                IntegrityIssue::InvalidLastLeafIndex {
+                    block_number: genesis_block_number + 2,
+                    leaf_index: 0,
+                },
+            ]
+        );
+    }
+
     #[test]
     fn get_block_transactions_works() {
         let genesis_block_number = Policy::genesis_block_number();
@@ -1391,6 +2051,8 @@ mod tests {
 
         assert_eq!(history_store.num_epoch_transactions(1, Some(&txn)), 8);
 
+        assert_eq!(history_store.num_transactions_total(Some(&txn)), 11);
+
         // Remove historic transactions to History Store.
         history_store.remove_partial_history(&mut txn, 1, 3);
 
@@ -1398,6 +2060,11 @@ mod tests {
         assert_eq!(history_store.num_epoch_transactions(0, Some(&txn)), 3);
 
         assert_eq!(history_store.num_epoch_transactions(1, Some(&txn)), 5);
+
+        assert_eq!(history_store.num_transactions_total(Some(&txn)), 8);
+
+        history_store.remove_history(&mut txn, 0);
+        assert_eq!(history_store.num_transactions_total(Some(&txn)), 5);
     }
 
     #[test]
@@ -25,7 +25,11 @@ use super::{
     interface::HistoryInterface,
     utils::{EpochBasedIndex, OrderedHash},
 };
-use crate::{history::HistoryTreeChunk, interface::HistoryIndexInterface, HistoryStore};
+use crate::{
+    history::{HistoryTreeChunk, RevertBlockError},
+    interface::HistoryIndexInterface,
+    HistoryStore,
+};
 
 // `RawTransactonHash` -> `EpochBasedIndex` (`epoch number || leaf_index`)
 declare_table!(TxHashTable, "LeafIndexByTxHash", RawTransactionHash => EpochBasedIndex);
@@ -303,6 +307,27 @@ impl HistoryInterface for HistoryStoreIndex {
         Some(total_size)
     }
 
+    fn revert_block(
+        &self,
+        txn: &mut MdbxWriteTransaction,
+        block_number: u32,
+    ) -> Result<u32, RevertBlockError> {
+        let num_hist_txs = self
+            .history_store
+            .num_block_transactions_to_revert(block_number, Some(txn))?;
+        if num_hist_txs > 0 {
+            // This also removes the historic transactions from the indices.
+            self.remove_partial_history(txn, Policy::epoch_at(block_number), num_hist_txs as usize)
+                .ok_or(RevertBlockError::RemoveFailed(block_number))?;
+        }
+
+        self.history_store
+            .validity_store
+            .delete_block_transactions(txn, block_number);
+
+        Ok(num_hist_txs)
+    }
+
     fn get_history_tree_root(
         &self,
         block_number: u32,
@@ -410,6 +435,23 @@ impl HistoryInterface for HistoryStoreIndex {
         )
     }
 
+    fn prove_chunk_bounded(
+        &self,
+        epoch_number: u32,
+        verifier_block_number: u32,
+        start_leaf_index: usize,
+        max_bytes: usize,
+        txn_option: Option<&MdbxReadTransaction>,
+    ) -> Option<(HistoryTreeChunk, bool)> {
+        self.history_store.prove_chunk_bounded(
+            epoch_number,
+            verifier_block_number,
+            start_leaf_index,
+            max_bytes,
+            txn_option,
+        )
+    }
+
     fn tree_from_chunks(
         &self,
         epoch_number: u32,
@@ -548,6 +590,18 @@ impl HistoryIndexInterface for HistoryStoreIndex {
             .get_historic_tx(leaf.epoch_number, leaf.index, Some(&txn))
     }
 
+    /// Returns the number of the block that contains the transaction with the given hash.
+    /// The transaction hash index points to the leaf of the transaction, thus this is a point
+    /// lookup. Its entries are removed when the history is reverted or pruned.
+    fn block_number_of(
+        &self,
+        raw_tx_hash: &Blake2bHash,
+        txn_option: Option<&MdbxReadTransaction>,
+    ) -> Option<u32> {
+        self.get_hist_tx_by_hash(raw_tx_hash, txn_option)
+            .map(|hist_tx| hist_tx.block_number)
+    }
+
     /// Returns a vector containing all transaction (and reward inherents) hashes corresponding to the given
     /// address. It fetches the transactions from most recent to least recent up to the maximum
     /// number given.
@@ -587,6 +641,51 @@ impl HistoryIndexInterface for HistoryStoreIndex {
         tx_hashes
     }
 
+    /// Returns the historic transactions (and reward inherents) corresponding to the given
+    /// address in chronological order, up to the maximum number given. Each historic transaction
+    /// is returned together with its position in the history.
+    /// If `start_at` is given, only historic transactions at or after that position are
+    /// returned. To fetch the next page, pass the position following the last one returned.
+    fn get_hist_txs_by_address(
+        &self,
+        address: &Address,
+        max: u16,
+        start_at: Option<EpochBasedIndex>,
+        txn_option: Option<&MdbxReadTransaction>,
+    ) -> Vec<(EpochBasedIndex, HistoricTransaction)> {
+        if max == 0 {
+            return vec![];
+        }
+
+        let txn = txn_option.or_new(&self.db);
+
+        // Seek to the first position at the given address, the positions are sorted in
+        // chronological order.
+        let mut cursor = txn.dup_cursor(&self.address_table);
+        let mut ordered_hash = match start_at {
+            Some(start_at) => cursor.set_lowerbound_subkey(address, &start_at),
+            None => cursor.set_key(address),
+        };
+
+        let mut positions = vec![];
+        while let Some(OrderedHash { index, .. }) = ordered_hash {
+            positions.push(index);
+            if positions.len() >= max as usize {
+                break;
+            }
+            ordered_hash = cursor.next_duplicate().map(|(_, v)| v);
+        }
+
+        positions
+            .into_iter()
+            .filter_map(|position| {
+                self.history_store
+                    .get_historic_tx(position.epoch_number, position.index, Some(&txn))
+                    .map(|hist_tx| (position, hist_tx))
+            })
+            .collect()
+    }
+
     /// Returns a proof for transactions with the given hashes. The proof also includes the extended
     /// transactions.
     /// The verifier state is used for those cases where the verifier might have an incomplete MMR,
@@ -614,6 +713,23 @@ impl HistoryIndexInterface for HistoryStoreIndex {
         self.history_store
             .prove_with_position(epoch_number, positions, verifier_state, txn_option)
     }
+
+    fn prove_transaction(
+        &self,
+        raw_tx_hash: &Blake2bHash,
+        txn_option: Option<&MdbxReadTransaction>,
+    ) -> Option<HistoryTreeProof> {
+        let txn = txn_option.or_new(&self.db);
+
+        let leaf = self.get_leaf_indices_by_tx_hash(raw_tx_hash, Some(&txn))?;
+
+        self.history_store.prove_with_position(
+            leaf.epoch_number,
+            vec![leaf.index as usize],
+            None,
+            Some(&txn),
+        )
+    }
 }
 
 #[cfg(test)]
@@ -884,6 +1000,47 @@ mod tests {
         );
     }
 
+    #[test]
+    fn block_number_of_works() {
+        // Initialize History Store.
+        let env = MdbxDatabase::new_volatile(Default::default()).unwrap();
+        let history_store = HistoryStoreIndex::new(env.clone(), NetworkId::UnitAlbatross);
+
+        // Create historic transactions.
+        let hist_txs = gen_hist_txs();
+        let hashes: Vec<_> = hist_txs.iter().map(|hist_tx| hist_tx.tx_hash()).collect();
+
+        // Add historic transactions to History Store.
+        let mut txn = env.write_transaction();
+        history_store.add_to_history(&mut txn, Policy::genesis_block_number() + 0, &hist_txs[..3]);
+        history_store.add_to_history(&mut txn, Policy::genesis_block_number() + 2, &hist_txs[3..]);
+
+        // Verify method works.
+        assert_eq!(
+            history_store.block_number_of(&hashes[0], Some(&txn)),
+            Some(Policy::genesis_block_number())
+        );
+        assert_eq!(
+            history_store.block_number_of(&hashes[5], Some(&txn)),
+            Some(Policy::genesis_block_number() + 2)
+        );
+        assert_eq!(
+            history_store.block_number_of(&Blake2bHash::default(), Some(&txn)),
+            None
+        );
+
+        // Reverted transactions can't be found anymore.
+        assert_eq!(
+            history_store.revert_block(&mut txn, Policy::genesis_block_number() + 2),
+            Ok(6)
+        );
+        assert_eq!(history_store.block_number_of(&hashes[5], Some(&txn)), None);
+        assert_eq!(
+            history_store.block_number_of(&hashes[0], Some(&txn)),
+            Some(Policy::genesis_block_number())
+        );
+    }
+
     #[test]
     fn get_block_transactions_works() {
         let genesis_block_number = Policy::genesis_block_number();
@@ -1237,6 +1394,74 @@ mod tests {
         assert_eq!(query_4.len(), 0);
     }
 
+    #[test]
+    fn get_hist_txs_by_address_works() {
+        // Initialize History Store.
+        let env = MdbxDatabase::new_volatile(Default::default()).unwrap();
+        let history_store = HistoryStoreIndex::new(env.clone(), NetworkId::UnitAlbatross);
+
+        // Create historic transactions.
+        let hist_txs = gen_hist_txs();
+
+        // Add historic transactions to History Store.
+        let mut txn = env.write_transaction();
+        history_store.add_to_history(&mut txn, Policy::genesis_block_number() + 0, &hist_txs[..3]);
+        history_store.add_to_history(&mut txn, Policy::genesis_block_number() + 2, &hist_txs[3..]);
+
+        let address =
+            Address::from_user_friendly_address("NQ09 VF5Y 1PKV MRM4 5LE1 55KV P6R2 GXYJ XYQF")
+                .unwrap();
+        // The first three historic transactions are in epoch 0, the others in epoch 1.
+        let positions = |page: &[(EpochBasedIndex, HistoricTransaction)]| {
+            page.iter()
+                .map(|(position, hist_tx)| {
+                    let offset = if position.epoch_number == 0 { 0 } else { 3 };
+                    assert_eq!(*hist_tx, hist_txs[offset + position.index as usize]);
+                    (position.epoch_number, position.index)
+                })
+                .collect::<Vec<_>>()
+        };
+
+        // Verify method works.
+        let all = history_store.get_hist_txs_by_address(&address, 99, None, Some(&txn));
+        assert_eq!(
+            positions(&all),
+            vec![(0, 0), (0, 1), (1, 0), (1, 2), (1, 3)]
+        );
+
+        // Fetch the same historic transactions in pages.
+        let page_1 = history_store.get_hist_txs_by_address(&address, 2, None, Some(&txn));
+        assert_eq!(positions(&page_1), vec![(0, 0), (0, 1)]);
+
+        let page_2 = history_store.get_hist_txs_by_address(
+            &address,
+            2,
+            Some(EpochBasedIndex::new(0, 2)),
+            Some(&txn),
+        );
+        assert_eq!(positions(&page_2), vec![(1, 0), (1, 2)]);
+
+        let page_3 = history_store.get_hist_txs_by_address(
+            &address,
+            2,
+            Some(EpochBasedIndex::new(1, 3)),
+            Some(&txn),
+        );
+        assert_eq!(positions(&page_3), vec![(1, 3)]);
+
+        let page_4 = history_store.get_hist_txs_by_address(
+            &address,
+            2,
+            Some(EpochBasedIndex::new(1, 4)),
+            Some(&txn),
+        );
+        assert!(page_4.is_empty());
+
+        let none =
+            history_store.get_hist_txs_by_address(&Address::burn_address(), 0, None, Some(&txn));
+        assert!(none.is_empty());
+    }
+
     #[test]
     fn prove_works() {
         // Initialize History Store.
@@ -1309,6 +1534,88 @@ mod tests {
         assert!(proof.verify(root).unwrap());
     }
 
+    #[test]
+    fn prune_below_works() {
+        // Initialize History Store.
+        let env = MdbxDatabase::new_volatile(Default::default()).unwrap();
+        let history_store = HistoryStoreIndex::new(env.clone(), NetworkId::UnitAlbatross);
+
+        // Create historic transactions.
+        let hist_txs = gen_hist_txs();
+
+        // Add historic transactions to History Store.
+        let mut txn = env.write_transaction();
+        history_store.add_to_history(&mut txn, Policy::genesis_block_number() + 0, &hist_txs[..3]);
+        history_store.add_to_history(&mut txn, Policy::genesis_block_number() + 2, &hist_txs[3..]);
+
+        let hashes: Vec<_> = hist_txs.iter().map(|hist_tx| hist_tx.tx_hash()).collect();
+
+        // Pruning below the oldest epoch does nothing.
+        assert_eq!(history_store.prune_below(&mut txn, 0), 0);
+
+        // Prune the first epoch.
+        assert_eq!(history_store.prune_below(&mut txn, 1), 3);
+        assert_eq!(history_store.num_epoch_transactions(0, Some(&txn)), 0);
+        assert!(history_store
+            .get_hist_tx_by_hash(&hashes[0], Some(&txn))
+            .is_none());
+        assert!(history_store
+            .get_block_transactions(Policy::genesis_block_number(), Some(&txn))
+            .is_empty());
+
+        // The retained epoch is untouched.
+        assert_eq!(history_store.num_epoch_transactions(1, Some(&txn)), 8);
+        assert!(history_store
+            .get_hist_tx_by_hash(&hashes[3], Some(&txn))
+            .is_some());
+
+        let root = history_store
+            .get_history_tree_root(Policy::genesis_block_number() + 1, Some(&txn))
+            .unwrap();
+        let proof = history_store
+            .prove(1, vec![&hashes[3], &hashes[6]], None, Some(&txn))
+            .unwrap();
+        assert!(proof.verify(root).unwrap());
+
+        // Pruning again does not remove anything else.
+        assert_eq!(history_store.prune_below(&mut txn, 1), 0);
+    }
+
+    #[test]
+    fn prove_transaction_works() {
+        // Initialize History Store.
+        let env = MdbxDatabase::new_volatile(Default::default()).unwrap();
+        let history_store = HistoryStoreIndex::new(env.clone(), NetworkId::UnitAlbatross);
+
+        // Create historic transactions.
+        let hist_txs = gen_hist_txs();
+
+        // Add historic transactions to History Store.
+        let mut txn = env.write_transaction();
+        history_store.add_to_history(&mut txn, Policy::genesis_block_number() + 0, &hist_txs[..3]);
+        history_store.add_to_history(&mut txn, Policy::genesis_block_number() + 2, &hist_txs[3..]);
+
+        let hashes: Vec<_> = hist_txs.iter().map(|hist_tx| hist_tx.tx_hash()).collect();
+
+        // Verify method works.
+        let root = history_store
+            .get_history_tree_root(Policy::genesis_block_number() + 1, Some(&txn))
+            .unwrap();
+
+        let proof = history_store
+            .prove_transaction(&hashes[6], Some(&txn))
+            .unwrap();
+
+        assert_eq!(proof.positions, vec![3]);
+        assert_eq!(proof.history, vec![hist_txs[6].clone()]);
+        assert!(proof.verify(root).unwrap());
+
+        // Unknown transactions can't be proven.
+        assert!(history_store
+            .prove_transaction(&Blake2bHash::default(), Some(&txn))
+            .is_none());
+    }
+
     #[test]
     fn prove_empty_tree_works() {
         // Initialize History Store.
@@ -1,5 +1,8 @@
+use std::path::Path;
+
 use nimiq_block::{Block, MicroBlock};
-use nimiq_database::mdbx::{MdbxReadTransaction, MdbxWriteTransaction};
+use nimiq_database::mdbx::{MdbxDatabase, MdbxReadTransaction, MdbxWriteTransaction};
+use nimiq_genesis::NetworkId;
 use nimiq_hash::Blake2bHash;
 use nimiq_mmr::{
     error::Error as MMRError,
@@ -12,12 +15,21 @@ use nimiq_transaction::{
 };
 
 use super::history_store_index::HistoryStoreIndex;
-use crate::{interface::HistoryInterface, HistoryTreeChunk};
+use crate::{interface::HistoryInterface, HistoryStore, HistoryTreeChunk, RevertBlockError};
 
 #[derive(Debug)]
 pub enum HistoryStoreProxy {
     WithIndex(HistoryStoreIndex),
     WithoutIndex(Box<dyn HistoryInterface + Send + Sync>),
+    /// Serves history chunks from a read-only replica of the history store, e.g. opened
+    /// on a filesystem snapshot, while all other operations go to the primary.
+    /// Only requests without a given database transaction are routed to the replica, and the
+    /// primary is used whenever the replica lags behind the verifier block or can't serve the
+    /// request.
+    WithReplica {
+        primary: Box<HistoryStoreProxy>,
+        replica: HistoryStore,
+    },
 }
 
 impl HistoryStoreProxy {
@@ -25,6 +37,7 @@ impl HistoryStoreProxy {
         match self {
             HistoryStoreProxy::WithIndex(_) => true,
             HistoryStoreProxy::WithoutIndex(_) => false,
+            HistoryStoreProxy::WithReplica { primary, .. } => primary.supports_index(),
         }
     }
 
@@ -32,10 +45,34 @@ impl HistoryStoreProxy {
         match self {
             HistoryStoreProxy::WithIndex(index) => Some(index),
             HistoryStoreProxy::WithoutIndex(_) => None,
+            HistoryStoreProxy::WithReplica { primary, .. } => primary.history_index(),
+        }
+    }
+
+    /// Adds a read-only replica of the history store located at the given path.
+    /// If the replica can't be opened, the proxy is returned unchanged.
+    pub fn with_read_replica<P: AsRef<Path>>(self, path: P, network_id: NetworkId) -> Self {
+        match MdbxDatabase::new_read_only(path, Default::default()) {
+            Ok(db) => HistoryStoreProxy::WithReplica {
+                primary: Box::new(self),
+                replica: HistoryStore::new_read_only(db, network_id),
+            },
+            Err(error) => {
+                log::warn!(%error, "Could not open history store replica, using the primary only");
+                self
+            }
         }
     }
 }
 
+/// Checks whether the replica contains the history up to the given verifier block.
+/// A replica that lags behind the primary would otherwise prove the history of an earlier block.
+fn replica_covers(replica: &HistoryStore, verifier_block_number: u32) -> bool {
+    replica
+        .get_last_leaf_block_number(None)
+        .is_some_and(|block_number| block_number >= verifier_block_number)
+}
+
 impl HistoryInterface for HistoryStoreProxy {
     // Adds all the transactions included in a given block into the history store.
     fn add_block(
@@ -47,6 +84,9 @@ impl HistoryInterface for HistoryStoreProxy {
         match self {
             HistoryStoreProxy::WithIndex(index) => index.add_block(txn, block, inherents),
             HistoryStoreProxy::WithoutIndex(store) => store.add_block(txn, block, inherents),
+            HistoryStoreProxy::WithReplica { primary, .. } => {
+                primary.add_block(txn, block, inherents)
+            }
         }
     }
 
@@ -60,6 +100,24 @@ impl HistoryInterface for HistoryStoreProxy {
         match self {
             HistoryStoreProxy::WithIndex(index) => index.remove_block(txn, block, inherents),
             HistoryStoreProxy::WithoutIndex(store) => store.remove_block(txn, block, inherents),
+            HistoryStoreProxy::WithReplica { primary, .. } => {
+                primary.remove_block(txn, block, inherents)
+            }
+        }
+    }
+
+    /// Reverts the history of the given block, which must be the last block in the history store.
+    fn revert_block(
+        &self,
+        txn: &mut MdbxWriteTransaction,
+        block_number: u32,
+    ) -> Result<u32, RevertBlockError> {
+        match self {
+            HistoryStoreProxy::WithIndex(index) => index.revert_block(txn, block_number),
+            HistoryStoreProxy::WithoutIndex(store) => store.revert_block(txn, block_number),
+            HistoryStoreProxy::WithReplica { primary, .. } => {
+                primary.revert_block(txn, block_number)
+            }
         }
     }
 
@@ -68,6 +126,9 @@ impl HistoryInterface for HistoryStoreProxy {
         match self {
             HistoryStoreProxy::WithIndex(index) => index.remove_history(txn, epoch_number),
             HistoryStoreProxy::WithoutIndex(store) => store.remove_history(txn, epoch_number),
+            HistoryStoreProxy::WithReplica { primary, .. } => {
+                primary.remove_history(txn, epoch_number)
+            }
         }
     }
 
@@ -84,6 +145,9 @@ impl HistoryInterface for HistoryStoreProxy {
             HistoryStoreProxy::WithoutIndex(store) => {
                 store.get_history_tree_root(block_number, txn_option)
             }
+            HistoryStoreProxy::WithReplica { primary, .. } => {
+                primary.get_history_tree_root(block_number, txn_option)
+            }
         }
     }
 
@@ -92,6 +156,7 @@ impl HistoryInterface for HistoryStoreProxy {
         match self {
             HistoryStoreProxy::WithIndex(index) => index.clear(txn),
             HistoryStoreProxy::WithoutIndex(store) => store.clear(txn),
+            HistoryStoreProxy::WithReplica { primary, .. } => primary.clear(txn),
         }
     }
 
@@ -106,6 +171,9 @@ impl HistoryInterface for HistoryStoreProxy {
         match self {
             HistoryStoreProxy::WithIndex(index) => index.length_at(block_number, txn_option),
             HistoryStoreProxy::WithoutIndex(store) => store.length_at(block_number, txn_option),
+            HistoryStoreProxy::WithReplica { primary, .. } => {
+                primary.length_at(block_number, txn_option)
+            }
         }
     }
 
@@ -124,6 +192,9 @@ impl HistoryInterface for HistoryStoreProxy {
             HistoryStoreProxy::WithoutIndex(store) => {
                 store.total_len_at_epoch(epoch_number, txn_option)
             }
+            HistoryStoreProxy::WithReplica { primary, .. } => {
+                primary.total_len_at_epoch(epoch_number, txn_option)
+            }
         }
     }
 
@@ -132,6 +203,9 @@ impl HistoryInterface for HistoryStoreProxy {
         match self {
             HistoryStoreProxy::WithIndex(index) => index.history_store_range(txn_option),
             HistoryStoreProxy::WithoutIndex(store) => store.history_store_range(txn_option),
+            HistoryStoreProxy::WithReplica { primary, .. } => {
+                primary.history_store_range(txn_option)
+            }
         }
     }
 
@@ -150,6 +224,9 @@ impl HistoryInterface for HistoryStoreProxy {
             HistoryStoreProxy::WithoutIndex(store) => {
                 store.add_to_history(txn, block_number, hist_txs)
             }
+            HistoryStoreProxy::WithReplica { primary, .. } => {
+                primary.add_to_history(txn, block_number, hist_txs)
+            }
         }
     }
 
@@ -173,6 +250,9 @@ impl HistoryInterface for HistoryStoreProxy {
             HistoryStoreProxy::WithoutIndex(store) => {
                 store.add_to_history_for_epoch(txn, epoch_number, block_number, hist_txs)
             }
+            HistoryStoreProxy::WithReplica { primary, .. } => {
+                primary.add_to_history_for_epoch(txn, epoch_number, block_number, hist_txs)
+            }
         }
     }
 
@@ -191,6 +271,9 @@ impl HistoryInterface for HistoryStoreProxy {
             HistoryStoreProxy::WithoutIndex(store) => {
                 store.remove_partial_history(txn, epoch_number, num_hist_txs)
             }
+            HistoryStoreProxy::WithReplica { primary, .. } => {
+                primary.remove_partial_history(txn, epoch_number, num_hist_txs)
+            }
         }
     }
 
@@ -206,6 +289,9 @@ impl HistoryInterface for HistoryStoreProxy {
             HistoryStoreProxy::WithoutIndex(store) => {
                 store.tx_in_validity_window(raw_tx_hash, txn_opt)
             }
+            HistoryStoreProxy::WithReplica { primary, .. } => {
+                primary.tx_in_validity_window(raw_tx_hash, txn_opt)
+            }
         }
     }
 
@@ -223,6 +309,9 @@ impl HistoryInterface for HistoryStoreProxy {
             HistoryStoreProxy::WithoutIndex(store) => {
                 store.get_block_transactions(block_number, txn_option)
             }
+            HistoryStoreProxy::WithReplica { primary, .. } => {
+                primary.get_block_transactions(block_number, txn_option)
+            }
         }
     }
 
@@ -239,6 +328,9 @@ impl HistoryInterface for HistoryStoreProxy {
             HistoryStoreProxy::WithoutIndex(store) => {
                 store.get_epoch_transactions(epoch_number, txn_option)
             }
+            HistoryStoreProxy::WithReplica { primary, .. } => {
+                primary.get_epoch_transactions(epoch_number, txn_option)
+            }
         }
     }
 
@@ -255,6 +347,9 @@ impl HistoryInterface for HistoryStoreProxy {
             HistoryStoreProxy::WithoutIndex(store) => {
                 store.num_epoch_transactions(epoch_number, txn_option)
             }
+            HistoryStoreProxy::WithReplica { primary, .. } => {
+                primary.num_epoch_transactions(epoch_number, txn_option)
+            }
         }
     }
 
@@ -272,6 +367,9 @@ impl HistoryInterface for HistoryStoreProxy {
             HistoryStoreProxy::WithoutIndex(store) => {
                 store.num_epoch_transactions_before(block_number, txn_option)
             }
+            HistoryStoreProxy::WithReplica { primary, .. } => {
+                primary.num_epoch_transactions_before(block_number, txn_option)
+            }
         }
     }
 
@@ -289,6 +387,9 @@ impl HistoryInterface for HistoryStoreProxy {
             HistoryStoreProxy::WithoutIndex(store) => {
                 store.get_epoch_transactions_after(block_number, txn_option)
             }
+            HistoryStoreProxy::WithReplica { primary, .. } => {
+                primary.get_epoch_transactions_after(block_number, txn_option)
+            }
         }
     }
 
@@ -321,6 +422,76 @@ impl HistoryInterface for HistoryStoreProxy {
                 chunk_index,
                 txn_option,
             ),
+            HistoryStoreProxy::WithReplica { primary, replica } => txn_option
+                .is_none()
+                .then_some(replica)
+                .filter(|replica| replica_covers(replica, verifier_block_number))
+                .and_then(|replica| {
+                    replica.prove_chunk(
+                        epoch_number,
+                        verifier_block_number,
+                        chunk_size,
+                        chunk_index,
+                        None,
+                    )
+                })
+                .or_else(|| {
+                    primary.prove_chunk(
+                        epoch_number,
+                        verifier_block_number,
+                        chunk_size,
+                        chunk_index,
+                        txn_option,
+                    )
+                }),
+        }
+    }
+
+    fn prove_chunk_bounded(
+        &self,
+        epoch_number: u32,
+        verifier_block_number: u32,
+        start_leaf_index: usize,
+        max_bytes: usize,
+        txn_option: Option<&MdbxReadTransaction>,
+    ) -> Option<(HistoryTreeChunk, bool)> {
+        match self {
+            HistoryStoreProxy::WithIndex(index) => index.prove_chunk_bounded(
+                epoch_number,
+                verifier_block_number,
+                start_leaf_index,
+                max_bytes,
+                txn_option,
+            ),
+            HistoryStoreProxy::WithoutIndex(store) => store.prove_chunk_bounded(
+                epoch_number,
+                verifier_block_number,
+                start_leaf_index,
+                max_bytes,
+                txn_option,
+            ),
+            HistoryStoreProxy::WithReplica { primary, replica } => txn_option
+                .is_none()
+                .then_some(replica)
+                .filter(|replica| replica_covers(replica, verifier_block_number))
+                .and_then(|replica| {
+                    replica.prove_chunk_bounded(
+                        epoch_number,
+                        verifier_block_number,
+                        start_leaf_index,
+                        max_bytes,
+                        None,
+                    )
+                })
+                .or_else(|| {
+                    primary.prove_chunk_bounded(
+                        epoch_number,
+                        verifier_block_number,
+                        start_leaf_index,
+                        max_bytes,
+                        txn_option,
+                    )
+                }),
         }
     }
 
@@ -338,6 +509,9 @@ impl HistoryInterface for HistoryStoreProxy {
             HistoryStoreProxy::WithoutIndex(store) => {
                 store.tree_from_chunks(epoch_number, chunks, txn)
             }
+            HistoryStoreProxy::WithReplica { primary, .. } => {
+                primary.tree_from_chunks(epoch_number, chunks, txn)
+            }
         }
     }
 
@@ -346,6 +520,9 @@ impl HistoryInterface for HistoryStoreProxy {
         match self {
             HistoryStoreProxy::WithIndex(index) => index.get_last_leaf_block_number(txn_option),
             HistoryStoreProxy::WithoutIndex(store) => store.get_last_leaf_block_number(txn_option),
+            HistoryStoreProxy::WithReplica { primary, .. } => {
+                primary.get_last_leaf_block_number(txn_option)
+            }
         }
     }
 
@@ -363,6 +540,9 @@ impl HistoryInterface for HistoryStoreProxy {
             HistoryStoreProxy::WithoutIndex(store) => {
                 store.has_equivocation_proof(locator, txn_option)
             }
+            HistoryStoreProxy::WithReplica { primary, .. } => {
+                primary.has_equivocation_proof(locator, txn_option)
+            }
         }
     }
 
@@ -377,6 +557,116 @@ impl HistoryInterface for HistoryStoreProxy {
             HistoryStoreProxy::WithoutIndex(store) => {
                 store.prove_num_leaves(block_number, txn_option)
             }
+            HistoryStoreProxy::WithReplica { primary, .. } => {
+                primary.prove_num_leaves(block_number, txn_option)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use nimiq_database::traits::{Database, WriteTransaction};
+    use nimiq_keys::Address;
+    use nimiq_primitives::{coin::Coin, networks::NetworkId, policy::Policy};
+    use nimiq_test_log::test;
+    use nimiq_transaction::{
+        historic_transaction::HistoricTransactionData, ExecutedTransaction, Transaction,
+    };
+
+    use super::*;
+
+    fn create_transaction(block: u32, value: u64) -> HistoricTransaction {
+        HistoricTransaction {
+            network_id: NetworkId::UnitAlbatross,
+            block_number: block,
+            block_time: 0,
+            data: HistoricTransactionData::Basic(ExecutedTransaction::Ok(Transaction::new_basic(
+                Address::from_user_friendly_address("NQ09 VF5Y 1PKV MRM4 5LE1 55KV P6R2 GXYJ XYQF")
+                    .unwrap(),
+                Address::burn_address(),
+                Coin::from_u64_unchecked(value),
+                Coin::from_u64_unchecked(0),
+                0,
+                NetworkId::UnitAlbatross,
+            ))),
         }
     }
+
+    /// Creates a history store with the given historic transactions, each added in its own block.
+    fn history_store(hist_txs: &[HistoricTransaction]) -> HistoryStore {
+        let env = MdbxDatabase::new_volatile(Default::default()).unwrap();
+        let history_store = HistoryStore::new(env.clone(), NetworkId::UnitAlbatross);
+
+        let mut txn = env.write_transaction();
+        for hist_tx in hist_txs {
+            history_store.add_to_history(&mut txn, hist_tx.block_number, &[hist_tx.clone()]);
+        }
+        txn.commit();
+
+        history_store
+    }
+
+    #[test]
+    fn prove_chunk_uses_up_to_date_replica() {
+        let genesis_block_number = Policy::genesis_block_number();
+        let epoch_number = Policy::epoch_at(genesis_block_number + 1);
+
+        // The replica has the same blocks as the primary, but different values, so we can tell
+        // which store served the chunk.
+        let primary_txs = vec![
+            create_transaction(genesis_block_number + 1, 1),
+            create_transaction(genesis_block_number + 2, 2),
+        ];
+        let replica_txs = vec![
+            create_transaction(genesis_block_number + 1, 11),
+            create_transaction(genesis_block_number + 2, 12),
+        ];
+        let proxy = HistoryStoreProxy::WithReplica {
+            primary: Box::new(HistoryStoreProxy::WithoutIndex(Box::new(history_store(
+                &primary_txs,
+            )))),
+            replica: history_store(&replica_txs),
+        };
+
+        let chunk = proxy
+            .prove_chunk(epoch_number, genesis_block_number + 2, 10, 0, None)
+            .unwrap();
+        assert_eq!(chunk.history, replica_txs);
+
+        let (chunk, more) = proxy
+            .prove_chunk_bounded(epoch_number, genesis_block_number + 2, 0, usize::MAX, None)
+            .unwrap();
+        assert_eq!(chunk.history, replica_txs);
+        assert!(!more);
+    }
+
+    #[test]
+    fn prove_chunk_skips_stale_replica() {
+        let genesis_block_number = Policy::genesis_block_number();
+        let epoch_number = Policy::epoch_at(genesis_block_number + 1);
+
+        // The replica is missing the last block of the primary.
+        let primary_txs = vec![
+            create_transaction(genesis_block_number + 1, 1),
+            create_transaction(genesis_block_number + 2, 2),
+        ];
+        let proxy = HistoryStoreProxy::WithReplica {
+            primary: Box::new(HistoryStoreProxy::WithoutIndex(Box::new(history_store(
+                &primary_txs,
+            )))),
+            replica: history_store(&primary_txs[..1]),
+        };
+
+        let chunk = proxy
+            .prove_chunk(epoch_number, genesis_block_number + 2, 10, 0, None)
+            .unwrap();
+        assert_eq!(chunk.history, primary_txs);
+
+        let (chunk, more) = proxy
+            .prove_chunk_bounded(epoch_number, genesis_block_number + 2, 0, usize::MAX, None)
+            .unwrap();
+        assert_eq!(chunk.history, primary_txs);
+        assert!(!more);
+    }
 }
@@ -4,11 +4,24 @@ use nimiq_hash::Blake2bHash;
 use nimiq_mmr::mmr::proof::RangeProof;
 use nimiq_transaction::historic_transaction::HistoricTransaction;
 use serde::{Deserialize, Serialize};
+use thiserror::Error;
 
 /// The chunk size used in our protocol.
 /// TODO: Update number.
 pub const CHUNK_SIZE: usize = 1024;
 
+/// Errors that can occur while verifying a run of history tree chunks.
+/// The contained index refers to the first chunk that broke the chain.
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum ChunkVerificationError {
+    #[error("Chunk #{0} does not contain any historic transactions")]
+    EmptyChunk(usize),
+    #[error("Chunk #{0} was proven against a different history tree")]
+    TreeSizeMismatch(usize),
+    #[error("Chunk #{0} does not match the expected history root")]
+    InvalidProof(usize),
+}
+
 #[derive(Serialize, Deserialize)]
 pub struct HistoryTreeChunk {
     pub(crate) proof: RangeProof<Blake2bHash>,
@@ -37,4 +50,32 @@ impl HistoryTreeChunk {
             .verify_with_start(expected_root, leaf_index, &self.history)
             .ok()
     }
+
+    /// Verifies a contiguous run of chunks, the first of which starts at `leaf_index`.
+    /// Every chunk is expected to start right after the last leaf of the previous chunk, so the
+    /// chunks must be given in order and without gaps or overlaps. All chunks must have been
+    /// proven against the same history tree, whose root is `expected_root`.
+    pub fn verify_batch(
+        chunks: &[HistoryTreeChunk],
+        expected_root: &Blake2bHash,
+        leaf_index: usize,
+    ) -> Result<(), ChunkVerificationError> {
+        let mmr_size = chunks.first().map(|chunk| chunk.proof.proof.mmr_size);
+        let mut leaf_index = leaf_index;
+
+        for (i, chunk) in chunks.iter().enumerate() {
+            if chunk.history.is_empty() {
+                return Err(ChunkVerificationError::EmptyChunk(i));
+            }
+            if Some(chunk.proof.proof.mmr_size) != mmr_size {
+                return Err(ChunkVerificationError::TreeSizeMismatch(i));
+            }
+            if chunk.verify(expected_root, leaf_index) != Some(true) {
+                return Err(ChunkVerificationError::InvalidProof(i));
+            }
+            leaf_index += chunk.history.len();
+        }
+
+        Ok(())
+    }
 }
@@ -0,0 +1,35 @@
+/// An inconsistency found while checking the integrity of the history store.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum IntegrityIssue {
+    /// An inner node of the history tree is missing or doesn't match the hash of its children.
+    InconsistentNode {
+        epoch_number: u32,
+        node_index: usize,
+    },
+    /// The history tree has a leaf for which no historic transaction is stored.
+    MissingLeaf { epoch_number: u32, leaf_index: u32 },
+    /// A historic transaction is stored for which the history tree has no leaf.
+    UnexpectedLeaf { epoch_number: u32, leaf_index: u32 },
+    /// A stored historic transaction doesn't match the respective leaf of the history tree.
+    LeafHashMismatch { epoch_number: u32, leaf_index: u32 },
+    /// The last leaf index stored for a block doesn't refer to a historic transaction of that block.
+    InvalidLastLeafIndex { block_number: u32, leaf_index: u32 },
+}
+
+/// The result of checking the integrity of the history store.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct IntegrityReport {
+    /// The number of epochs that were checked.
+    pub num_epochs: usize,
+    /// The total number of leaves that were checked.
+    pub num_leaves: usize,
+    /// All inconsistencies that were found.
+    pub issues: Vec<IntegrityIssue>,
+}
+
+impl IntegrityReport {
+    /// Returns true if no inconsistencies were found.
+    pub fn is_ok(&self) -> bool {
+        self.issues.is_empty()
+    }
+}
@@ -1,3 +1,5 @@
+use std::io::{Read, Write};
+
 use nimiq_block::{Block, MicroBlock};
 use nimiq_database::mdbx::{MdbxReadTransaction, MdbxWriteTransaction};
 use nimiq_hash::Blake2bHash;
@@ -6,6 +8,7 @@ use nimiq_mmr::{
     error::Error as MMRError,
     mmr::proof::{RangeProof, SizeProof},
 };
+use nimiq_primitives::policy::Policy;
 use nimiq_transaction::{
     historic_transaction::{HistoricTransaction, RawTransactionHash},
     history_proof::HistoryTreeProof,
@@ -13,7 +16,10 @@ use nimiq_transaction::{
     EquivocationLocator,
 };
 
-use crate::HistoryTreeChunk;
+use crate::{
+    history::snapshot::{EpochSnapshot, HistorySnapshotError},
+    EpochBasedIndex, HistoryTreeChunk, RevertBlockError,
+};
 
 /// Defines several methods to interact with a history store.
 pub trait HistoryInterface: std::fmt::Debug {
@@ -33,9 +39,76 @@ pub trait HistoryInterface: std::fmt::Debug {
         inherents: Vec<Inherent>,
     ) -> Option<u64>;
 
+    /// Reverts the history of the given block, which must be the last block in the history
+    /// store. All historic transactions of the block are removed from the history tree, the
+    /// indices and the validity store. Returns the number of historic transactions removed.
+    fn revert_block(
+        &self,
+        txn: &mut MdbxWriteTransaction,
+        block_number: u32,
+    ) -> Result<u32, RevertBlockError>;
+
     /// Removes the full history associated with a given epoch.
     fn remove_history(&self, txn: &mut MdbxWriteTransaction, epoch_number: u32) -> Option<()>;
 
+    /// Removes the full history of all epochs strictly below the given epoch number, including
+    /// their index entries. Returns the number of historic transactions removed.
+    fn prune_below(&self, txn: &mut MdbxWriteTransaction, epoch_number: u32) -> usize {
+        let (first_block, _) = self.history_store_range(Some(txn));
+
+        let mut num_removed = 0;
+        for epoch in Policy::epoch_at(first_block)..epoch_number {
+            let num_hist_txs = self.num_epoch_transactions(epoch, Some(txn));
+            if num_hist_txs > 0 && self.remove_history(txn, epoch).is_some() {
+                num_removed += num_hist_txs;
+            }
+        }
+
+        num_removed
+    }
+
+    /// Writes a snapshot of the full history of the given epoch to the writer. The snapshot can
+    /// be distributed out-of-band and imported by other nodes using `import_epoch`.
+    fn export_epoch(
+        &self,
+        epoch_number: u32,
+        writer: &mut dyn Write,
+        txn_option: Option<&MdbxReadTransaction>,
+    ) -> Result<(), HistorySnapshotError> {
+        let history = self.get_epoch_transactions(epoch_number, txn_option);
+        EpochSnapshot::new(epoch_number, history).write(writer)
+    }
+
+    /// Reads a snapshot written by `export_epoch` and adds its history to the store. The snapshot
+    /// is only added if it matches the expected history root and the history of its epoch is
+    /// still empty. Returns the epoch number of the snapshot.
+    fn import_epoch(
+        &self,
+        txn: &mut MdbxWriteTransaction,
+        reader: &mut dyn Read,
+        expected_root: &Blake2bHash,
+    ) -> Result<u32, HistorySnapshotError> {
+        let snapshot = EpochSnapshot::read(reader, expected_root)?;
+        let epoch_number = snapshot.epoch_number;
+
+        if self.num_epoch_transactions(epoch_number, Some(txn)) > 0 {
+            return Err(HistorySnapshotError::EpochNotEmpty(epoch_number));
+        }
+
+        // Like the history sync, we add the whole epoch at once.
+        if let Some(last_hist_tx) = snapshot.history.last() {
+            self.add_to_history_for_epoch(
+                txn,
+                epoch_number,
+                last_hist_tx.block_number,
+                &snapshot.history,
+            )
+            .ok_or(HistorySnapshotError::StoreFailed(epoch_number))?;
+        }
+
+        Ok(epoch_number)
+    }
+
     /// Obtains the current history root at the given block.
     fn get_history_tree_root(
         &self,
@@ -126,6 +199,17 @@ pub trait HistoryInterface: std::fmt::Debug {
         txn_option: Option<&MdbxReadTransaction>,
     ) -> usize;
 
+    /// Returns the total number of historic transactions of all epochs in the history store.
+    /// The number of historic transactions of each epoch is given by the size of its history
+    /// tree, thus no historic transactions are read.
+    fn num_transactions_total(&self, txn_option: Option<&MdbxReadTransaction>) -> u64 {
+        let (first_block, last_block) = self.history_store_range(txn_option);
+
+        (Policy::epoch_at(first_block)..=Policy::epoch_at(last_block))
+            .map(|epoch_number| self.num_epoch_transactions(epoch_number, txn_option) as u64)
+            .sum()
+    }
+
     /// Returns the number of historic transactions within the given block's epoch that occurred
     /// before the given block (inclusive).
     fn num_epoch_transactions_before(
@@ -157,6 +241,23 @@ pub trait HistoryInterface: std::fmt::Debug {
         txn_option: Option<&MdbxReadTransaction>,
     ) -> Option<HistoryTreeChunk>;
 
+    /// Returns a chunk of the history of the given epoch starting at leaf `start_leaf_index`
+    /// whose serialized size, including the proof, does not exceed `max_bytes`.
+    /// The chunk contains as many complete historic transactions as fit, it never contains a
+    /// partial transaction. The returned flag indicates whether more historic transactions are
+    /// available after the chunk.
+    /// The `verifier_block_number` has the same meaning as for `prove_chunk`.
+    /// Returns `None` if there is no historic transaction at `start_leaf_index` or if not even a
+    /// single historic transaction fits into `max_bytes`.
+    fn prove_chunk_bounded(
+        &self,
+        epoch_number: u32,
+        verifier_block_number: u32,
+        start_leaf_index: usize,
+        max_bytes: usize,
+        txn_option: Option<&MdbxReadTransaction>,
+    ) -> Option<(HistoryTreeChunk, bool)>;
+
     /// Creates a new history tree from chunks and returns the root hash.
     fn tree_from_chunks(
         &self,
@@ -193,6 +294,13 @@ pub trait HistoryIndexInterface {
         txn_option: Option<&MdbxReadTransaction>,
     ) -> Option<HistoricTransaction>;
 
+    /// Returns the number of the block that contains the transaction with the given hash.
+    fn block_number_of(
+        &self,
+        raw_tx_hash: &Blake2bHash,
+        txn_option: Option<&MdbxReadTransaction>,
+    ) -> Option<u32>;
+
     /// Returns a vector containing all transaction (and reward inherents) hashes corresponding to the given
     /// address. It fetches the transactions from most recent to least recent up to the maximum
     /// number given.
@@ -203,6 +311,19 @@ pub trait HistoryIndexInterface {
         txn_option: Option<&MdbxReadTransaction>,
     ) -> Vec<Blake2bHash>;
 
+    /// Returns the historic transactions (and reward inherents) corresponding to the given
+    /// address in chronological order, up to the maximum number given. Each historic transaction
+    /// is returned together with its position in the history.
+    /// If `start_at` is given, only historic transactions at or after that position are
+    /// returned. To fetch the next page, pass the position following the last one returned.
+    fn get_hist_txs_by_address(
+        &self,
+        address: &Address,
+        max: u16,
+        start_at: Option<EpochBasedIndex>,
+        txn_option: Option<&MdbxReadTransaction>,
+    ) -> Vec<(EpochBasedIndex, HistoricTransaction)>;
+
     /// Returns a proof for transactions with the given hashes. The proof also includes the extended
     /// transactions.
     /// The verifier state is used for those cases where the verifier might have an incomplete MMR,
@@ -214,4 +335,15 @@ pub trait HistoryIndexInterface {
         verifier_state: Option<usize>,
         txn_option: Option<&MdbxReadTransaction>,
     ) -> Option<HistoryTreeProof>;
+
+    /// Returns an inclusion proof for the transaction with the given hash, located via the index.
+    /// The proof includes the historic transaction and its leaf position and is created for the
+    /// current state of the history tree of the transaction's epoch.
+    /// The validity window ensures that a transaction hash only ever occurs once, thus the proof
+    /// covers all occurrences of the transaction.
+    fn prove_transaction(
+        &self,
+        raw_tx_hash: &Blake2bHash,
+        txn_option: Option<&MdbxReadTransaction>,
+    ) -> Option<HistoryTreeProof>;
 }
@@ -1,12 +1,19 @@
-pub use history_store::HistoryStore;
+pub use epoch_iter::{EpochHistoryIter, HistoryIterError};
+pub use history_store::{HistoryStore, RevertBlockError};
 pub use history_store_index::HistoryStoreIndex;
-pub use history_tree_chunk::{HistoryTreeChunk, CHUNK_SIZE};
+pub use history_tree_chunk::{ChunkVerificationError, HistoryTreeChunk, CHUNK_SIZE};
+pub use integrity::{IntegrityIssue, IntegrityReport};
+pub use snapshot::HistorySnapshotError;
+pub use utils::EpochBasedIndex;
 
+mod epoch_iter;
 mod history_store;
 mod history_store_index;
 pub mod history_store_proxy;
 mod history_tree_chunk;
+mod integrity;
 pub mod interface;
 mod mmr_store;
+mod snapshot;
 mod utils;
 mod validity_store;
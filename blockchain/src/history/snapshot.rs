@@ -0,0 +1,111 @@
+use std::io::{self, Read, Write};
+
+use nimiq_hash::Blake2bHash;
+use nimiq_primitives::policy::Policy;
+use nimiq_serde::{Deserialize as _, DeserializeError, Serialize as _};
+use nimiq_transaction::historic_transaction::HistoricTransaction;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::HistoryStore;
+
+/// Errors that can occur while exporting or importing a history snapshot.
+#[derive(Debug, Error)]
+pub enum HistorySnapshotError {
+    #[error("I/O error: {0}")]
+    Io(#[from] io::Error),
+    #[error("Malformed snapshot: {0}")]
+    Deserialize(#[from] DeserializeError),
+    #[error("Not a history snapshot")]
+    InvalidMagic,
+    #[error("Unsupported snapshot version {0}")]
+    UnsupportedVersion(u8),
+    #[error(
+        "Historic transaction of block #{block_number} doesn't belong to epoch {epoch_number}"
+    )]
+    InvalidBlockNumber {
+        epoch_number: u32,
+        block_number: u32,
+    },
+    #[error("Snapshot doesn't match the expected history root")]
+    RootMismatch,
+    #[error("History of epoch {0} is not empty")]
+    EpochNotEmpty(u32),
+    #[error("Failed to add the history of epoch {0}")]
+    StoreFailed(u32),
+}
+
+/// A portable snapshot of the history of a single epoch.
+///
+/// The snapshot contains all historic transactions of the epoch in leaf order. The history
+/// tree is rebuilt from them when the snapshot is imported, which verifies it against the
+/// history root at the same time.
+#[derive(Serialize, Deserialize)]
+pub(crate) struct EpochSnapshot {
+    magic: [u8; 4],
+    version: u8,
+    pub(crate) epoch_number: u32,
+    pub(crate) history: Vec<HistoricTransaction>,
+}
+
+impl EpochSnapshot {
+    /// Identifies the file as a history snapshot.
+    const MAGIC: [u8; 4] = *b"NQHS";
+    /// The current version of the snapshot format.
+    const VERSION: u8 = 1;
+
+    pub(crate) fn new(epoch_number: u32, history: Vec<HistoricTransaction>) -> Self {
+        Self {
+            magic: Self::MAGIC,
+            version: Self::VERSION,
+            epoch_number,
+            history,
+        }
+    }
+
+    pub(crate) fn write(&self, mut writer: &mut dyn Write) -> Result<(), HistorySnapshotError> {
+        self.serialize_to_writer(&mut writer)?;
+        writer.flush()?;
+        Ok(())
+    }
+
+    /// Reads a snapshot and checks that its historic transactions belong to its epoch, in
+    /// increasing block number order, and that they form a history tree with the expected root.
+    pub(crate) fn read(
+        reader: &mut dyn Read,
+        expected_root: &Blake2bHash,
+    ) -> Result<Self, HistorySnapshotError> {
+        let mut bytes = vec![];
+        reader.read_to_end(&mut bytes)?;
+
+        // Check the header first, such that other files don't produce confusing errors.
+        let (magic, version) = <([u8; 4], u8)>::deserialize_take(&bytes)?.0;
+        if magic != Self::MAGIC {
+            return Err(HistorySnapshotError::InvalidMagic);
+        }
+        if version != Self::VERSION {
+            return Err(HistorySnapshotError::UnsupportedVersion(version));
+        }
+
+        let snapshot = Self::deserialize_all(&bytes)?;
+
+        let mut previous_block_number = 0;
+        for hist_tx in &snapshot.history {
+            if Policy::epoch_at(hist_tx.block_number) != snapshot.epoch_number
+                || hist_tx.block_number < previous_block_number
+            {
+                return Err(HistorySnapshotError::InvalidBlockNumber {
+                    epoch_number: snapshot.epoch_number,
+                    block_number: hist_tx.block_number,
+                });
+            }
+            previous_block_number = hist_tx.block_number;
+        }
+
+        if HistoryStore::_root_from_hist_txs(&snapshot.history).as_ref() != Some(expected_root) {
+            return Err(HistorySnapshotError::RootMismatch);
+        }
+
+        Ok(snapshot)
+    }
+}
@@ -13,7 +13,9 @@ declare_table!(BlockTxnsTable, "ValidityBlockTxnHashes", u32 => dup(RawTransacti
 
 /// The validity store is used by full/history nodes to keep track of which
 /// transactions have occurred within the validity window.
-/// The validity store keeps at least `validity_window_blocks + blocks_per_batch` blocks
+/// The validity store keeps at least `validity_window_blocks + blocks_per_batch` blocks.
+/// It only indexes transaction hashes and does not maintain any history root. The history root of
+/// each block is maintained incrementally by the history tree of its epoch in the history store.
 #[derive(Debug)]
 pub struct ValidityStore {
     // Database handle.
@@ -28,11 +30,7 @@ pub struct ValidityStore {
 impl ValidityStore {
     /// Creates a new validity store initializing database tables
     pub(crate) fn new(db: MdbxDatabase) -> Self {
-        let store = Self {
-            db,
-            txn_hashes: TxnHashesTable,
-            block_txns: BlockTxnsTable,
-        };
+        let store = Self::new_read_only(db);
 
         store.db.create_regular_table(&store.txn_hashes);
         store.db.create_dup_table(&store.block_txns);
@@ -40,6 +38,15 @@ impl ValidityStore {
         store
     }
 
+    /// Creates a validity store on a database opened in read-only mode without creating tables
+    pub(crate) fn new_read_only(db: MdbxDatabase) -> Self {
+        Self {
+            db,
+            txn_hashes: TxnHashesTable,
+            block_txns: BlockTxnsTable,
+        }
+    }
+
     /// Returns true if the validity store has the given transaction hash.
     pub(crate) fn has_transaction(
         &self,
@@ -29,7 +29,9 @@ use tokio::sync::{
 };
 use tokio_stream::wrappers::BroadcastStream;
 
-use super::{ConsensusRequest, ResolveBlockError, ResolveBlockRequest};
+use super::{
+    sync_progress::SyncProgressEvent, ConsensusRequest, ResolveBlockError, ResolveBlockRequest,
+};
 use crate::{
     consensus::remote_data_store::RemoteDataStore,
     messages::{
@@ -46,6 +48,7 @@ pub struct ConsensusProxy<N: Network> {
     pub(crate) established_flag: Arc<AtomicBool>,
     pub(crate) synced_validity_window_flag: Arc<AtomicBool>,
     pub(crate) events: BroadcastSender<ConsensusEvent>,
+    pub(crate) sync_progress_events: BroadcastSender<SyncProgressEvent>,
     pub(crate) request: MpscSender<ConsensusRequest<N>>,
 }
 
@@ -57,6 +60,7 @@ impl<N: Network> Clone for ConsensusProxy<N> {
             established_flag: Arc::clone(&self.established_flag),
             synced_validity_window_flag: Arc::clone(&self.synced_validity_window_flag),
             events: self.events.clone(),
+            sync_progress_events: self.sync_progress_events.clone(),
             request: self.request.clone(),
         }
     }
@@ -88,6 +92,12 @@ impl<N: Network> ConsensusProxy<N> {
         BroadcastStream::new(self.events.subscribe())
     }
 
+    /// Subscribes to the periodic sync progress events, which are emitted while consensus is
+    /// not established.
+    pub fn subscribe_sync_progress(&self) -> BroadcastStream<SyncProgressEvent> {
+        BroadcastStream::new(self.sync_progress_events.subscribe())
+    }
+
     /// Subscribe to remote address notification events
     pub async fn subscribe_address_notifications(
         &self,
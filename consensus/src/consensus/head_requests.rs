@@ -1,5 +1,5 @@
 use std::{
-    collections::HashSet,
+    collections::{HashMap, HashSet},
     future::Future,
     mem,
     pin::Pin,
@@ -18,7 +18,8 @@ use nimiq_utils::stream::FuturesUnordered;
 use crate::messages::{BlockError, RequestBlock, RequestHead, ResponseHead};
 
 /// Requests the head blocks for a set of peers.
-/// Calculates the number of known/unknown blocks and a vector of unknown blocks.
+/// Calculates the number of known/unknown blocks, a vector of unknown blocks and the block
+/// numbers of the heads of the peers.
 pub struct HeadRequests<TNetwork: Network + 'static> {
     peers: Vec<TNetwork::PeerId>,
     head_hashes: FuturesUnordered<BoxFuture<'static, (usize, Result<ResponseHead, RequestError>)>>,
@@ -32,6 +33,10 @@ pub struct HeadRequests<TNetwork: Network + 'static> {
         >,
     >,
     requested_hashes: HashSet<Blake2bHash>,
+    /// The head hashes reported by the peers, one per successful head request.
+    reported_heads: Vec<Blake2bHash>,
+    /// The block numbers of the reported heads that are known or were received.
+    head_block_numbers: HashMap<Blake2bHash, u32>,
     blockchain: BlockchainProxy,
     network: Arc<TNetwork>,
     num_known_blocks: usize,
@@ -44,6 +49,9 @@ pub struct HeadRequestsResult<TNetwork: Network + 'static> {
     pub num_known_blocks: usize,
     pub num_unknown_blocks: usize,
     pub unknown_blocks: Vec<(Block, TNetwork::PeerId)>,
+    /// The block numbers of the heads reported by the peers. Heads that are unknown and
+    /// couldn't be retrieved are omitted.
+    pub head_block_numbers: Vec<u32>,
 }
 
 impl<TNetwork: Network + 'static> HeadRequests<TNetwork> {
@@ -72,6 +80,8 @@ impl<TNetwork: Network + 'static> HeadRequests<TNetwork> {
             head_hashes,
             head_blocks: Default::default(),
             requested_hashes: Default::default(),
+            reported_heads: Default::default(),
+            head_block_numbers: Default::default(),
             blockchain,
             network,
             num_known_blocks: 0,
@@ -116,8 +126,11 @@ impl<TNetwork: Network + 'static> Future for HeadRequests<TNetwork> {
             match result {
                 Ok(head) => {
                     let hash = head.micro;
-                    if self.blockchain.read().get_block(&hash, false).is_ok() {
+                    self.reported_heads.push(hash.clone());
+                    let known_block = self.blockchain.read().get_block(&hash, false);
+                    if let Ok(block) = known_block {
                         self.num_known_blocks += 1;
+                        self.head_block_numbers.insert(hash, block.block_number());
                     } else {
                         // Request unknown blocks from peer that gave it to us.
                         self.num_unknown_blocks += 1;
@@ -149,6 +162,8 @@ impl<TNetwork: Network + 'static> Future for HeadRequests<TNetwork> {
         while let Poll::Ready(Some(result)) = self.head_blocks.poll_next_unpin(cx) {
             match result {
                 (Ok(Ok(block)), peer_id) => {
+                    self.head_block_numbers
+                        .insert(block.hash(), block.block_number());
                     self.unknown_blocks.push((block, peer_id));
                 }
                 // We don't do anything with failed requests.
@@ -163,10 +178,16 @@ impl<TNetwork: Network + 'static> Future for HeadRequests<TNetwork> {
 
         // We're done if both queues are empty.
         if self.is_finished() {
+            let head_block_numbers = self
+                .reported_heads
+                .iter()
+                .filter_map(|hash| self.head_block_numbers.get(hash).copied())
+                .collect();
             return Poll::Ready(HeadRequestsResult {
                 num_known_blocks: self.num_known_blocks,
                 num_unknown_blocks: self.num_unknown_blocks,
                 unknown_blocks: mem::take(&mut self.unknown_blocks),
+                head_block_numbers,
             });
         }
 
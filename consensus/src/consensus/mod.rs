@@ -1,5 +1,6 @@
 use std::{
     future::Future,
+    mem,
     pin::Pin,
     sync::{
         atomic::{AtomicBool, Ordering},
@@ -18,6 +19,7 @@ use nimiq_blockchain_proxy::BlockchainProxy;
 use nimiq_blockchain_proxy::BlockchainReadProxy;
 use nimiq_hash::Blake2bHash;
 use nimiq_network_interface::{network::Network, request::request_handler};
+use nimiq_primitives::policy::Policy;
 use nimiq_time::{interval, Interval};
 use nimiq_utils::{spawn, WakerExt};
 use nimiq_zkp_component::zkp_component::ZKPComponentProxy;
@@ -30,9 +32,12 @@ use tokio::sync::{
 };
 use tokio_stream::wrappers::BroadcastStream;
 
-use self::consensus_proxy::ConsensusProxy;
 #[cfg(feature = "full")]
 use self::remote_event_dispatcher::RemoteEventDispatcher;
+use self::{
+    consensus_proxy::ConsensusProxy,
+    sync_progress::{SyncProgressEvent, SyncProgressTracker, SyncState},
+};
 use crate::{
     consensus::head_requests::{HeadRequests, HeadRequestsResult},
     messages::{RequestBlock, RequestHead, RequestMacroChain, RequestMissingBlocks},
@@ -52,6 +57,7 @@ mod head_requests;
 mod remote_data_store;
 #[cfg(feature = "full")]
 mod remote_event_dispatcher;
+pub mod sync_progress;
 
 /// Events that are generated by the consensus component to convey the two possible states of consensus:
 /// Established consensus (by satisfying some specific consensus criteria), or we lost it
@@ -61,7 +67,8 @@ pub enum ConsensusEvent {
     /// Also includes a flag that indicates if we are ready for transaction verification.
     /// The established event can be triggered multiple times with different values for the flag.
     Established { synced_validity_window: bool },
-    /// Consensus was lost
+    /// Consensus was lost, either because we dropped below the minimum number of peers or because
+    /// we fell behind the tip of the chain.
     Lost,
 }
 
@@ -140,8 +147,23 @@ pub struct Consensus<N: Network> {
     head_requests_time: Option<Instant>,
     head_requests_interval: Interval,
 
+    sync_progress_events: BroadcastSender<SyncProgressEvent>,
+    sync_progress: SyncProgressTracker,
+    sync_progress_interval: Interval,
+    stall_timeout: Duration,
+
     min_peers: usize,
 
+    /// The block number of the tip of the chain, i.e. the highest head block number reported
+    /// by at least 2/3 of our peers in the last head request.
+    ///
+    /// Buffered blocks aren't used for this since they are unverified and a single peer could
+    /// announce a forged block far ahead of the chain.
+    tip_block_number: Option<u32>,
+
+    /// Number of blocks our head is behind `tip_block_number`. Updated on every poll.
+    blocks_behind: usize,
+
     /// Sender and Receiver of a consensus request channel used to relay requests from any source
     /// to the Consensus instance. Currently the only source is a ConsensusProxy instance, but
     /// the Consensus is not limited to it.
@@ -173,6 +195,37 @@ impl<N: Network> Consensus<N> {
     /// established state and to advance the chain.
     const HEAD_REQUESTS_TIMEOUT: Duration = Duration::from_secs(5);
 
+    /// Default time without progress after which the sync is considered stalled.
+    const DEFAULT_STALL_TIMEOUT: Duration = Duration::from_secs(60);
+
+    /// Maximum number of blocks we may be behind the known tip of the chain while consensus is
+    /// established.
+    fn max_blocks_behind() -> usize {
+        Policy::blocks_per_batch() as usize
+    }
+
+    /// Returns how many blocks our head at `head_block_number` is behind the highest known block.
+    fn blocks_behind(head_block_number: u32, highest_known_block_number: Option<u32>) -> usize {
+        highest_known_block_number.map_or(0, |block_number| {
+            block_number.saturating_sub(head_block_number) as usize
+        })
+    }
+
+    /// Returns the highest block number that at least 2/3 of the given head block numbers reach,
+    /// or `None` if there are none.
+    fn quorum_block_number(mut head_block_numbers: Vec<u32>) -> Option<u32> {
+        head_block_numbers.sort_unstable_by(|a, b| b.cmp(a));
+        let quorum = (2 * head_block_numbers.len()).div_ceil(3);
+        quorum
+            .checked_sub(1)
+            .and_then(|index| head_block_numbers.get(index).copied())
+    }
+
+    /// Returns whether we are too far behind the tip of the chain to have consensus established.
+    fn is_behind(blocks_behind: usize) -> bool {
+        blocks_behind > Self::max_blocks_behind()
+    }
+
     pub fn from_network(
         blockchain: BlockchainProxy,
         network: Arc<N>,
@@ -196,6 +249,7 @@ impl<N: Network> Consensus<N> {
         zkp_proxy: ZKPComponentProxy<N>,
     ) -> Self {
         let (tx, _rx) = broadcast(256);
+        let (sync_progress_tx, _rx) = broadcast(16);
 
         Self::init_network_request_receivers(&network, &blockchain);
 
@@ -224,7 +278,13 @@ impl<N: Network> Consensus<N> {
             head_requests: None,
             head_requests_time: None,
             head_requests_interval: interval(Self::HEAD_REQUESTS_TIMEOUT),
+            sync_progress_events: sync_progress_tx,
+            sync_progress: SyncProgressTracker::new(),
+            sync_progress_interval: interval(SyncProgressTracker::INTERVAL),
+            stall_timeout: Self::DEFAULT_STALL_TIMEOUT,
             min_peers,
+            tip_block_number: None,
+            blocks_behind: 0,
             // Choose a small buffer as having a lot of items buffered here indicates a bigger problem.
             requests: mpsc_channel(10),
             zkp_proxy,
@@ -302,10 +362,35 @@ impl<N: Network> Consensus<N> {
         BroadcastStream::new(self.events.subscribe())
     }
 
+    /// Subscribes to the periodic sync progress events, which are emitted while consensus is
+    /// not established.
+    pub fn subscribe_sync_progress(&self) -> BroadcastStream<SyncProgressEvent> {
+        BroadcastStream::new(self.sync_progress_events.subscribe())
+    }
+
     pub fn is_established(&self) -> bool {
         self.established_flag.load(Ordering::Acquire)
     }
 
+    /// Returns the current sync state. While consensus isn't established, the sync is reported
+    /// as stalled if our head didn't advance for the stall timeout despite knowing of a higher
+    /// block.
+    pub fn sync_state(&self) -> SyncState {
+        let current_block = self.blockchain.read().block_number();
+        self.sync_progress.sync_state(
+            current_block,
+            self.is_established(),
+            Instant::now(),
+            self.stall_timeout,
+        )
+    }
+
+    /// Sets the time without progress after which the sync is considered stalled.
+    /// Defaults to one minute.
+    pub fn set_stall_timeout(&mut self, stall_timeout: Duration) {
+        self.stall_timeout = stall_timeout;
+    }
+
     pub fn num_agents(&self) -> usize {
         self.sync.num_peers()
     }
@@ -317,6 +402,7 @@ impl<N: Network> Consensus<N> {
             established_flag: Arc::clone(&self.established_flag),
             synced_validity_window_flag: Arc::clone(&self.synced_validity_window_flag),
             events: self.events.clone(),
+            sync_progress_events: self.sync_progress_events.clone(),
             request: self.requests.0.clone(),
         }
     }
@@ -376,9 +462,11 @@ impl<N: Network> Consensus<N> {
     }
 
     /// Calculates and sets established state, returns a ConsensusEvent if the state changed.
-    /// Once consensus is established, we can only lose it if we lose all our peers.
-    /// To reach consensus established state, we need at least `minPeers` peers and
-    /// one of the following conditions must be true:
+    /// Once consensus is established, we lose it if we drop below `minPeers` peers or fall
+    /// more than `max_blocks_behind` blocks behind the tip of the chain, as reported by at least
+    /// 2/3 of our peers in the last head request.
+    /// To reach consensus established state, we need at least `minPeers` peers, must not be
+    /// behind the tip of the chain by more than that and one of the following conditions must be true:
     /// - we accepted at least `MIN_BLOCKS_ESTABLISHED` block announcements
     /// - we know at least 2/3 of the head blocks of our peers
     ///
@@ -391,25 +479,37 @@ impl<N: Network> Consensus<N> {
         &mut self,
         finished_head_request: Option<HeadRequestsResult<N>>,
     ) -> Option<ConsensusEvent> {
-        // We can only lose established state right now if we drop below our minimum peer threshold.
+        // We lose established state if we drop below our minimum peer threshold or fall behind.
         if self.is_established() {
             if self.num_agents() < self.min_peers {
                 warn!("Lost consensus!");
                 self.established_flag.swap(false, Ordering::Release);
                 return Some(ConsensusEvent::Lost);
             }
+            if Self::is_behind(self.blocks_behind) {
+                warn!(
+                    blocks_behind = self.blocks_behind,
+                    "Lost consensus, fell behind the tip of the chain!"
+                );
+                self.established_flag.swap(false, Ordering::Release);
+                return Some(ConsensusEvent::Lost);
+            }
             // Check if validity window availability changed.
             if let (_, Some(event)) = self.check_validity_window() {
                 return Some(event);
             }
         } else {
-            // We have three conditions on whether we move to the established state.
+            // We have four conditions on whether we move to the established state.
             // First, we always need a minimum number of peers connected.
             // Second, the state must always be complete.
+            // Third, we must not be too far behind the tip of the chain.
             // Then, we check that we either:
             // - accepted a minimum number of block announcements, or
             // - know the head state of a majority of our peers
-            if self.num_agents() >= self.min_peers && self.sync.state_complete() {
+            if self.num_agents() >= self.min_peers
+                && self.sync.state_complete()
+                && !Self::is_behind(self.blocks_behind)
+            {
                 if self.sync.accepted_block_announcements() >= Self::MIN_BLOCKS_ESTABLISHED {
                     info!("Consensus established, number of accepted announcements satisfied.");
                     self.established_flag.swap(true, Ordering::Release);
@@ -525,6 +625,8 @@ impl<N: Network> Future for Consensus<N> {
                             "Catching up to tip of the chain (now at #{}, {} blocks remaining)",
                             block_number, remaining_in_buffer
                         );
+                        self.sync_progress
+                            .update_target(block_number + remaining_in_buffer as u32);
 
                         if remaining_in_buffer == 0 {
                             self.head_requests_time = None;
@@ -538,13 +640,17 @@ impl<N: Network> Future for Consensus<N> {
                         self.head_requests_time = None;
                     }
                 }
-                LiveSyncPushEvent::RejectedBlock(hash) => {
-                    warn!("Rejected block {}", hash);
+                LiveSyncPushEvent::RejectedBlock(error) => {
+                    warn!(%error, "Rejected block");
                 }
                 LiveSyncPushEvent::AcceptedChunks(_) => {}
             }
         }
 
+        // Derive how far we are behind the tip of the chain reported by our peers.
+        let head_block_number = self.blockchain.read().block_number();
+        self.blocks_behind = Self::blocks_behind(head_block_number, self.tip_block_number);
+
         // Check consensus established state on changes.
         if let Some(event) = self.check_established(None) {
             self.events.send(event).ok();
@@ -558,9 +664,19 @@ impl<N: Network> Future for Consensus<N> {
 
                 // Push unknown blocks to the block queue, trying to sync.
                 for (block, peer_id) in result.unknown_blocks.drain(..) {
+                    self.sync_progress.update_target(block.block_number());
                     self.sync.push_block(block, BlockSource::requested(peer_id));
                 }
 
+                // Update the tip of the chain if enough peers reported their heads.
+                if let Some(block_number) =
+                    Self::quorum_block_number(mem::take(&mut result.head_block_numbers))
+                {
+                    self.tip_block_number = Some(block_number);
+                    let head_block_number = self.blockchain.read().block_number();
+                    self.blocks_behind = Self::blocks_behind(head_block_number, Some(block_number));
+                }
+
                 // Update established state using the result.
                 if let Some(event) = self.check_established(Some(result)) {
                     self.events.send(event).ok();
@@ -581,7 +697,78 @@ impl<N: Network> Future for Consensus<N> {
         // Advance consensus and catch-up through head requests.
         self.request_heads();
 
+        // Emit the sync progress on a regular basis.
+        while self.sync_progress_interval.poll_next_unpin(cx).is_ready() {
+            let current_block = self.blockchain.read().block_number();
+            let established = self.is_established();
+            if let Some(event) =
+                self.sync_progress
+                    .on_tick(current_block, established, Instant::now())
+            {
+                self.sync_progress_events.send(event).ok();
+            }
+        }
+
         self.waker.store_waker(cx);
         Poll::Pending
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use nimiq_network_mock::MockNetwork;
+    use nimiq_primitives::policy::Policy;
+    use nimiq_test_log::test;
+
+    type Consensus = super::Consensus<MockNetwork>;
+
+    #[test]
+    fn it_loses_and_reestablishes_consensus_when_falling_behind() {
+        let batch = Policy::blocks_per_batch();
+
+        // An unknown tip means we aren't behind.
+        assert_eq!(Consensus::blocks_behind(100, None), 0);
+        assert!(!Consensus::is_behind(Consensus::blocks_behind(100, None)));
+
+        // A tip up to one batch ahead keeps consensus established.
+        let blocks_behind = Consensus::blocks_behind(100, Some(100 + batch));
+        assert_eq!(blocks_behind, batch as usize);
+        assert!(!Consensus::is_behind(blocks_behind));
+
+        // Falling further behind loses consensus.
+        let blocks_behind = Consensus::blocks_behind(100, Some(101 + batch));
+        assert_eq!(blocks_behind, batch as usize + 1);
+        assert!(Consensus::is_behind(blocks_behind));
+
+        // Once our head catches up, consensus can be established again.
+        assert!(!Consensus::is_behind(Consensus::blocks_behind(
+            101,
+            Some(101 + batch)
+        )));
+
+        // A stale tip below our head doesn't count.
+        assert_eq!(Consensus::blocks_behind(200, Some(150)), 0);
+    }
+
+    #[test]
+    fn it_takes_the_tip_reported_by_two_thirds_of_the_peers() {
+        assert_eq!(Consensus::quorum_block_number(vec![]), None);
+        assert_eq!(Consensus::quorum_block_number(vec![42]), Some(42));
+
+        // A single peer reporting a far ahead head doesn't move the tip.
+        assert_eq!(
+            Consensus::quorum_block_number(vec![100, 1_000_000, 100]),
+            Some(100)
+        );
+        assert_eq!(
+            Consensus::quorum_block_number(vec![1_000_000, 100]),
+            Some(100)
+        );
+
+        // The highest block number reached by 2/3 of the peers is the tip.
+        assert_eq!(
+            Consensus::quorum_block_number(vec![90, 110, 100, 120, 80, 130]),
+            Some(100)
+        );
+    }
+}
@@ -0,0 +1,233 @@
+use std::{collections::VecDeque, time::Duration};
+
+use instant::Instant;
+
+/// Progress of the node while catching up to the tip of the chain.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SyncProgress {
+    /// The block number of our current head.
+    pub current_block: u32,
+    /// The highest block number we know of that we are syncing to.
+    pub target_block: u32,
+    /// The number of blocks per second we adopted, averaged over the last few seconds.
+    pub blocks_per_second: f64,
+}
+
+/// Events that are emitted periodically by the consensus component while it is syncing.
+#[derive(Clone, Debug, PartialEq)]
+pub enum SyncProgressEvent {
+    /// The sync made progress.
+    Progress(SyncProgress),
+    /// The sync completed and consensus was established.
+    SyncComplete,
+}
+
+/// The current state of the sync, see `Consensus::sync_state`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SyncState {
+    /// We are catching up to the highest block number we know of.
+    Syncing { current: u32, target: u32 },
+    /// Consensus is established.
+    Established,
+    /// We know of a higher block number, but our head hasn't advanced for a while.
+    Stalled,
+}
+
+/// Keeps track of the sync progress and estimates the sync rate.
+pub(crate) struct SyncProgressTracker {
+    /// Recent (time, block number) samples used to compute the rolling average of the sync rate.
+    samples: VecDeque<(Instant, u32)>,
+    target_block: u32,
+    complete: bool,
+    /// The time our head last advanced and the block number it advanced to.
+    last_progress: (Instant, u32),
+}
+
+impl SyncProgressTracker {
+    /// Interval in which the sync progress is emitted.
+    pub(crate) const INTERVAL: Duration = Duration::from_secs(1);
+
+    /// Time window over which the sync rate is averaged.
+    const RATE_WINDOW: Duration = Duration::from_secs(5);
+
+    pub(crate) fn new() -> Self {
+        Self {
+            samples: VecDeque::new(),
+            target_block: 0,
+            complete: false,
+            last_progress: (Instant::now(), 0),
+        }
+    }
+
+    /// Raises the target block if the given block number is higher than the current target.
+    pub(crate) fn update_target(&mut self, block_number: u32) {
+        self.target_block = self.target_block.max(block_number);
+    }
+
+    /// Records the current block number and returns the event to be emitted, if any.
+    /// While syncing, a progress event is returned on every call. Once consensus is established,
+    /// a single `SyncComplete` event is returned and the tracking starts over.
+    pub(crate) fn on_tick(
+        &mut self,
+        current_block: u32,
+        established: bool,
+        now: Instant,
+    ) -> Option<SyncProgressEvent> {
+        if self.complete && !established {
+            // We start syncing again, so the time without progress is counted from now on.
+            self.last_progress = (now, current_block);
+        } else if current_block > self.last_progress.1 {
+            self.last_progress = (now, current_block);
+        }
+
+        if established {
+            if self.complete {
+                return None;
+            }
+            self.complete = true;
+            self.samples.clear();
+            return Some(SyncProgressEvent::SyncComplete);
+        }
+        self.complete = false;
+
+        self.samples.push_back((now, current_block));
+        while let Some((time, _)) = self.samples.front() {
+            if now.duration_since(*time) <= Self::RATE_WINDOW {
+                break;
+            }
+            self.samples.pop_front();
+        }
+
+        Some(SyncProgressEvent::Progress(SyncProgress {
+            current_block,
+            target_block: self.target_block.max(current_block),
+            blocks_per_second: self.blocks_per_second(),
+        }))
+    }
+
+    /// Returns the current sync state. The sync is considered stalled if we know of a higher
+    /// block number but our head didn't advance for at least `stall_timeout`.
+    pub(crate) fn sync_state(
+        &self,
+        current_block: u32,
+        established: bool,
+        now: Instant,
+        stall_timeout: Duration,
+    ) -> SyncState {
+        if established {
+            return SyncState::Established;
+        }
+
+        let target_block = self.target_block.max(current_block);
+        let (last_progress_time, last_progress_block) = self.last_progress;
+        if target_block > current_block
+            && current_block <= last_progress_block
+            && now.saturating_duration_since(last_progress_time) >= stall_timeout
+        {
+            return SyncState::Stalled;
+        }
+
+        SyncState::Syncing {
+            current: current_block,
+            target: target_block,
+        }
+    }
+
+    fn blocks_per_second(&self) -> f64 {
+        let (Some((first_time, first_block)), Some((last_time, last_block))) =
+            (self.samples.front(), self.samples.back())
+        else {
+            return 0.0;
+        };
+
+        let elapsed = last_time.duration_since(*first_time).as_secs_f64();
+        if elapsed == 0.0 {
+            return 0.0;
+        }
+        last_block.saturating_sub(*first_block) as f64 / elapsed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use nimiq_test_log::test;
+
+    use super::*;
+
+    #[test]
+    fn it_computes_a_rolling_sync_rate() {
+        let mut tracker = SyncProgressTracker::new();
+        tracker.update_target(1000);
+
+        let start = Instant::now();
+        let progress = |event| match event {
+            Some(SyncProgressEvent::Progress(progress)) => progress,
+            event => panic!("Unexpected event: {event:?}"),
+        };
+
+        let first = progress(tracker.on_tick(100, false, start));
+        assert_eq!(first.current_block, 100);
+        assert_eq!(first.target_block, 1000);
+        assert_eq!(first.blocks_per_second, 0.0);
+
+        let second = progress(tracker.on_tick(120, false, start + Duration::from_secs(2)));
+        assert_eq!(second.blocks_per_second, 10.0);
+
+        // The first sample falls out of the rate window.
+        let third = progress(tracker.on_tick(130, false, start + Duration::from_secs(6)));
+        assert_eq!(third.blocks_per_second, 2.5);
+
+        assert_eq!(
+            tracker.on_tick(1000, true, start + Duration::from_secs(7)),
+            Some(SyncProgressEvent::SyncComplete)
+        );
+        assert_eq!(
+            tracker.on_tick(1000, true, start + Duration::from_secs(8)),
+            None
+        );
+    }
+
+    #[test]
+    fn it_detects_a_stalled_sync() {
+        let mut tracker = SyncProgressTracker::new();
+        tracker.update_target(1000);
+
+        let start = Instant::now();
+        let timeout = Duration::from_secs(10);
+
+        tracker.on_tick(100, false, start);
+        assert_eq!(
+            tracker.sync_state(100, false, start + Duration::from_secs(5), timeout),
+            SyncState::Syncing {
+                current: 100,
+                target: 1000
+            }
+        );
+        assert_eq!(
+            tracker.sync_state(100, false, start + Duration::from_secs(10), timeout),
+            SyncState::Stalled
+        );
+
+        // Progress that wasn't recorded by a tick yet isn't considered stalled.
+        assert_eq!(
+            tracker.sync_state(110, false, start + Duration::from_secs(10), timeout),
+            SyncState::Syncing {
+                current: 110,
+                target: 1000
+            }
+        );
+
+        tracker.on_tick(110, false, start + Duration::from_secs(11));
+        assert_eq!(
+            tracker.sync_state(110, false, start + Duration::from_secs(20), timeout),
+            SyncState::Syncing {
+                current: 110,
+                target: 1000
+            }
+        );
+        assert_eq!(
+            tracker.sync_state(110, true, start + Duration::from_secs(30), timeout),
+            SyncState::Established
+        );
+    }
+}
@@ -1,4 +1,5 @@
-use nimiq_blockchain_interface::BlockchainError;
+use nimiq_blockchain_interface::{BlockchainError, PushError};
+use nimiq_hash::Blake2bHash;
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
@@ -6,14 +7,84 @@ use thiserror::Error;
 pub enum Error {
     #[error("Blockchain error: {0}")]
     BlockchainError(#[from] BlockchainError),
+    /// The block with the given hash was rejected by the blockchain
+    #[error("Block {hash} rejected: {reason}")]
+    BlockRejected {
+        hash: Blake2bHash,
+        reason: PushError,
+    },
 }
 
+impl Error {
+    /// Returns the hash of the block that caused the error, if any.
+    pub fn block_hash(&self) -> Option<&Blake2bHash> {
+        match self {
+            Error::BlockRejected { hash, .. } => Some(hash),
+            Error::BlockchainError(_) => None,
+        }
+    }
+
+    /// Returns whether the error may go away when retrying later, e.g. because a missing
+    /// predecessor or missing data has arrived by then. Errors caused by invalid data are
+    /// permanent, the peer that sent the data should not be asked again.
+    pub fn is_transient(&self) -> bool {
+        match self {
+            Error::BlockchainError(error) => is_transient_blockchain_error(error),
+            Error::BlockRejected { reason, .. } => match reason {
+                PushError::Orphan
+                | PushError::MissingAccountsTrieDiff
+                | PushError::IncompleteAccountsTrie => true,
+                PushError::BlockchainError(error) => is_transient_blockchain_error(error),
+                PushError::InvalidZKP
+                | PushError::InvalidBlock(_)
+                | PushError::InvalidSuccessor
+                | PushError::InvalidPredecessor
+                | PushError::DuplicateTransaction
+                | PushError::InvalidEquivocationProof(_)
+                | PushError::AccountsError(_)
+                | PushError::InvalidFork
+                | PushError::EquivocationAlreadyIncluded(_) => false,
+            },
+        }
+    }
+}
+
+/// Blockchain errors caused by data we don't have (yet) are transient, errors caused by invalid
+/// data or a broken local state are not.
+fn is_transient_blockchain_error(error: &BlockchainError) -> bool {
+    match error {
+        BlockchainError::BlockNotFound
+        | BlockchainError::BlockBodyNotFound
+        | BlockchainError::NoValidatorsFound
+        | BlockchainError::AccountsDiffNotFound => true,
+        BlockchainError::InvalidGenesisBlock
+        | BlockchainError::FailedLoadingMainChain
+        | BlockchainError::InconsistentState
+        | BlockchainError::NoNetwork(_)
+        | BlockchainError::BlockIsNotMacro
+        | BlockchainError::InvalidEpoch => false,
+    }
+}
+
+/// Errors that can occur while syncing.
 #[derive(Debug, Error)]
-pub enum SyncError {
-    #[error("Other")]
-    Other,
+pub enum SyncError<TPeerId> {
+    /// No peer offers a valid target to sync to
     #[error("No valid sync target found")]
     NoValidSyncTarget,
+    /// The given peer sent an invalid chunk
+    #[error("Invalid chunk received from peer {peer:?}")]
+    InvalidChunkReceived { peer: TPeerId },
+}
+
+impl<TPeerId> SyncError<TPeerId> {
+    /// Returns the peer that caused the error, if any.
+    pub fn peer(&self) -> Option<&TPeerId> {
+        match self {
+            SyncError::InvalidChunkReceived { peer } => Some(peer),
+            SyncError::NoValidSyncTarget => None,
+        }
+    }
 }
 
 /// Different errors that can be obtained when subscribing to transaction addresses.
@@ -34,3 +105,31 @@ pub enum SubscribeToAddressesError {
     #[serde(other)]
     Other = 4,
 }
+
+#[cfg(test)]
+mod tests {
+    use nimiq_block::BlockError;
+    use nimiq_test_log::test;
+
+    use super::*;
+
+    #[test]
+    fn it_classifies_transient_errors() {
+        let rejected = |reason| Error::BlockRejected {
+            hash: Blake2bHash::default(),
+            reason,
+        };
+
+        assert!(rejected(PushError::Orphan).is_transient());
+        assert!(
+            rejected(PushError::BlockchainError(BlockchainError::BlockNotFound)).is_transient()
+        );
+        assert!(Error::BlockchainError(BlockchainError::AccountsDiffNotFound).is_transient());
+
+        assert!(
+            !rejected(PushError::InvalidBlock(BlockError::InvalidJustification)).is_transient()
+        );
+        assert!(!rejected(PushError::InvalidZKP).is_transient());
+        assert!(!Error::BlockchainError(BlockchainError::InconsistentState).is_transient());
+    }
+}
@@ -1,8 +1,12 @@
 #[macro_use]
 extern crate log;
 
-pub use consensus::{consensus_proxy::ConsensusProxy, Consensus, ConsensusEvent, RemoteEvent};
-pub use error::{Error, SubscribeToAddressesError};
+pub use consensus::{
+    consensus_proxy::ConsensusProxy,
+    sync_progress::{SyncProgress, SyncProgressEvent, SyncState},
+    Consensus, ConsensusEvent, RemoteEvent,
+};
+pub use error::{Error, SubscribeToAddressesError, SyncError};
 
 pub mod consensus;
 pub mod error;
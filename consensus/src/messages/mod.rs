@@ -14,6 +14,8 @@ use nimiq_hash::{Blake2bHash, Blake2sHash, Hash, HashOutput, SerializeContent};
 use nimiq_keys::Address;
 use nimiq_macros::test_max_req_size;
 use nimiq_mmr::mmr::proof::SizeProof;
+#[cfg(feature = "full")]
+use nimiq_network_interface::request::RequestPriority;
 use nimiq_network_interface::{
     network::Topic,
     request::{RequestCommon, RequestMarker},
@@ -335,6 +337,7 @@ impl RequestCommon for RequestHistoryChunk {
     const TYPE_ID: u16 = 204;
     type Response = Result<HistoryChunk, HistoryChunkError>;
     const MAX_REQUESTS: u32 = MAX_REQUEST_RESPONSE_HISTORY_CHUNK;
+    const PRIORITY: RequestPriority = RequestPriority::Low;
 }
 
 #[cfg(feature = "full")]
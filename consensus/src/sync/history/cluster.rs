@@ -14,13 +14,18 @@ use nimiq_block::{Block, MacroBlock};
 use nimiq_blockchain::{interface::HistoryInterface, Blockchain, HistoryTreeChunk, CHUNK_SIZE};
 use nimiq_blockchain_interface::{AbstractBlockchain, PushError, PushResult};
 use nimiq_hash::Blake2bHash;
-use nimiq_network_interface::{network::Network, request::RequestError};
+use nimiq_network_interface::{
+    network::{CloseReason, Network},
+    request::RequestError,
+    Multiaddr,
+};
 use nimiq_primitives::{networks::NetworkId, policy::Policy, slots_allocation::Validators};
 use nimiq_transaction::historic_transaction::HistoricTransaction;
 use parking_lot::RwLock;
 use thiserror::Error;
 
 use crate::{
+    error::SyncError,
     messages::{
         BatchSetError, BatchSetInfo, HistoryChunkError, RequestBatchSet, RequestHistoryChunk,
     },
@@ -154,11 +159,13 @@ pub struct SyncCluster<TNetwork: Network> {
 
     blockchain: Arc<RwLock<Blockchain>>,
     network: Arc<TNetwork>,
+
+    /// Maximum number of history chunks that are requested concurrently.
+    max_pending_chunks: usize,
 }
 
 impl<TNetwork: Network + 'static> SyncCluster<TNetwork> {
     const NUM_PENDING_BATCH_SETS: usize = 5;
-    const NUM_PENDING_CHUNKS: usize = 12;
 
     pub(crate) fn for_epoch(
         blockchain: Arc<RwLock<Blockchain>>,
@@ -166,6 +173,7 @@ impl<TNetwork: Network + 'static> SyncCluster<TNetwork> {
         peers: PeerList<TNetwork>,
         epoch_ids: Vec<Blake2bHash>,
         first_epoch_number: usize,
+        max_pending_chunks: usize,
     ) -> Self {
         Self::new(
             blockchain,
@@ -175,6 +183,7 @@ impl<TNetwork: Network + 'static> SyncCluster<TNetwork> {
             first_epoch_number,
             (first_epoch_number * Policy::blocks_per_epoch() as usize)
                 + Policy::genesis_block_number() as usize,
+            max_pending_chunks,
         )
     }
 
@@ -185,6 +194,7 @@ impl<TNetwork: Network + 'static> SyncCluster<TNetwork> {
         checkpoint_id: Blake2bHash,
         epoch_number: usize,
         block_number: usize,
+        max_pending_chunks: usize,
     ) -> Self {
         Self::new(
             blockchain,
@@ -193,6 +203,7 @@ impl<TNetwork: Network + 'static> SyncCluster<TNetwork> {
             vec![checkpoint_id],
             epoch_number,
             block_number,
+            max_pending_chunks,
         )
     }
 
@@ -203,6 +214,7 @@ impl<TNetwork: Network + 'static> SyncCluster<TNetwork> {
         epoch_ids: Vec<Blake2bHash>,
         first_epoch_number: usize,
         first_block_number: usize,
+        max_pending_chunks: usize,
     ) -> Self {
         let id = SYNC_CLUSTER_ID.fetch_add(1, Ordering::SeqCst);
 
@@ -260,7 +272,7 @@ impl<TNetwork: Network + 'static> SyncCluster<TNetwork> {
             Arc::clone(&network),
             Vec::<(HistoryChunkRequest, Option<_>)>::new(),
             peers,
-            Self::NUM_PENDING_CHUNKS,
+            max_pending_chunks,
             move |request, network, peer_id| {
                 async move {
                     Self::request_history_chunk(network, peer_id, request.clone())
@@ -281,6 +293,7 @@ impl<TNetwork: Network + 'static> SyncCluster<TNetwork> {
             num_epochs_finished: 0,
             blockchain,
             network,
+            max_pending_chunks,
         }
     }
 
@@ -496,10 +509,20 @@ impl<TNetwork: Network + 'static> SyncCluster<TNetwork> {
     }
 
     /// Returns the shared list of peers of both queues (history and batch set).
-    pub(crate) fn peers(&self) -> Vec<<TNetwork as Network>::PeerId> {
+    pub fn peers(&self) -> Vec<<TNetwork as Network>::PeerId> {
         self.batch_set_queue.peers.read().peers().to_vec()
     }
 
+    /// Returns the addresses of the peers of this cluster. Peers whose info isn't available
+    /// anymore are skipped.
+    pub fn peer_addresses(&self) -> Vec<Multiaddr> {
+        self.peers()
+            .into_iter()
+            .filter_map(|peer_id| self.network.get_peer_info(peer_id))
+            .map(|peer_info| peer_info.get_address())
+            .collect()
+    }
+
     pub(crate) fn split_off(&mut self, at: usize) -> Self {
         assert!(
             self.num_epochs_finished() <= at,
@@ -521,6 +544,7 @@ impl<TNetwork: Network + 'static> SyncCluster<TNetwork> {
             self.batch_set_queue.peers.read().clone(), // makes sure we have a hard copy
             ids,
             first_epoch_number,
+            self.max_pending_chunks,
         )
     }
 
@@ -529,7 +553,7 @@ impl<TNetwork: Network + 'static> SyncCluster<TNetwork> {
         *self = self.split_off(usize::min(num_items, self.len()));
     }
 
-    pub(crate) fn compare(&self, other: &Self, current_epoch: usize) -> std::cmp::Ordering {
+    pub fn compare(&self, other: &Self, current_epoch: usize) -> std::cmp::Ordering {
         let this_epoch_number = self.first_epoch_number.max(current_epoch);
         let other_epoch_number = other.first_epoch_number.max(current_epoch);
 
@@ -617,25 +641,43 @@ impl<TNetwork: Network + 'static> SyncCluster<TNetwork> {
         };
         let chunk = network.request(req, peer_id).await??.chunk;
 
-        // Verify that the chunk is valid.
-        let leaf_index = request.chunk_index as usize * CHUNK_SIZE;
-        if !chunk
-            .verify(&request.history_root, leaf_index)
-            .unwrap_or(false)
-        {
+        // Verify the chunk as soon as it arrives. A peer that sends an invalid chunk is banned
+        // right away, the chunk is then re-requested from another peer.
+        if let Err(error) = Self::verify_history_chunk(&request, peer_id, &chunk) {
             log::warn!(
                 epoch_number = request.epoch_number,
                 block_number = request.block_number,
                 chunk_index = request.chunk_index,
-                peer = %peer_id,
-                "HistoryChunk failed to verify",
+                %error,
+                "Banning peer because history chunk failed to verify",
             );
+            network
+                .disconnect_peer(peer_id, CloseReason::MaliciousPeer)
+                .await;
             return Err(HistoryRequestError::InvalidHistoryChunk);
         }
 
         Ok(chunk)
     }
 
+    /// Verifies that the given chunk, received from the given peer, is the requested part of
+    /// the history whose root is committed to in the macro block of the request.
+    pub fn verify_history_chunk(
+        request: &HistoryChunkRequest,
+        peer_id: TNetwork::PeerId,
+        chunk: &HistoryTreeChunk,
+    ) -> Result<(), SyncError<TNetwork::PeerId>> {
+        let leaf_index = request.chunk_index as usize * CHUNK_SIZE;
+        if chunk
+            .verify(&request.history_root, leaf_index)
+            .unwrap_or(false)
+        {
+            Ok(())
+        } else {
+            Err(SyncError::InvalidChunkReceived { peer: peer_id })
+        }
+    }
+
     fn pop_complete_epoch(&mut self) -> Option<PendingBatchSet> {
         if !self.pending_batch_sets.is_empty() && self.pending_batch_sets[0].is_complete() {
             self.num_epochs_finished += 1;
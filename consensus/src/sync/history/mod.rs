@@ -2,5 +2,9 @@ pub mod cluster;
 mod sync;
 mod sync_clustering;
 mod sync_stream;
+mod target_selector;
 
-pub use sync::HistoryMacroSync;
+pub use sync::{HistoryMacroSync, DEFAULT_MAX_PENDING_CHUNKS};
+pub use target_selector::{
+    BestClusterSelector, DiverseClusterSelector, DiversityPolicy, SyncTargetSelector,
+};
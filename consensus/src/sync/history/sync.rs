@@ -14,7 +14,12 @@ use parking_lot::RwLock;
 use crate::{
     messages::Checkpoint,
     sync::{
-        history::cluster::{SyncCluster, SyncClusterResult},
+        history::{
+            cluster::{SyncCluster, SyncClusterResult},
+            target_selector::{
+                BestClusterSelector, DiverseClusterSelector, DiversityPolicy, SyncTargetSelector,
+            },
+        },
         syncer::MacroSync,
     },
 };
@@ -40,11 +45,20 @@ impl<T> EpochIds<T> {
     }
 }
 
+/// Default maximum number of history chunks that are requested concurrently during the sync.
+pub const DEFAULT_MAX_PENDING_CHUNKS: usize = 12;
+
 pub(crate) enum Job<TNetwork: Network> {
     PushBatchSet(usize, Blake2bHash, BoxFuture<'static, SyncClusterResult>),
     FinishCluster(SyncCluster<TNetwork>, SyncClusterResult),
 }
 
+/// Macro sync for history nodes. It requests the epoch ids from its peers, groups them into
+/// clusters and downloads and pushes the batch sets and history of the best cluster.
+///
+/// Every pushed batch set is committed to the database, and the epoch ids are always requested
+/// starting at our latest election and checkpoint blocks. Thus, an interrupted sync resumes from
+/// the last adopted macro block when the node restarts.
 pub struct HistoryMacroSync<TNetwork: Network> {
     pub(crate) blockchain: Arc<RwLock<Blockchain>>,
     pub(crate) network: Arc<TNetwork>,
@@ -57,6 +71,8 @@ pub struct HistoryMacroSync<TNetwork: Network> {
     pub(crate) active_cluster: Option<SyncCluster<TNetwork>>,
     pub(crate) job_queue: VecDeque<Job<TNetwork>>,
     pub(crate) waker: Option<Waker>,
+    pub(crate) target_selector: Box<dyn SyncTargetSelector<TNetwork>>,
+    pub(crate) max_pending_chunks: usize,
 }
 
 impl<TNetwork: Network> HistoryMacroSync<TNetwork> {
@@ -79,9 +95,34 @@ impl<TNetwork: Network> HistoryMacroSync<TNetwork> {
             active_cluster: None,
             job_queue: VecDeque::new(),
             waker: None,
+            target_selector: Box::new(BestClusterSelector),
+            max_pending_chunks: DEFAULT_MAX_PENDING_CHUNKS,
         }
     }
 
+    /// Sets the maximum number of history chunks that are requested concurrently. The chunks
+    /// are requested from the peers of the cluster in turn and applied in order.
+    pub fn with_max_pending_chunks(mut self, max_pending_chunks: usize) -> Self {
+        self.max_pending_chunks = max_pending_chunks.max(1);
+        self
+    }
+
+    /// Replaces the strategy used to choose the cluster to sync next.
+    pub fn with_target_selector(
+        mut self,
+        target_selector: Box<dyn SyncTargetSelector<TNetwork>>,
+    ) -> Self {
+        self.target_selector = target_selector;
+        self
+    }
+
+    /// Makes the sync prefer clusters whose peers are spread over multiple subnets, according to
+    /// the given policy. This replaces the current target selector. The subnets of the peers
+    /// aren't considered when requesting data from the chosen cluster.
+    pub fn with_diversity_policy(self, policy: DiversityPolicy) -> Self {
+        self.with_target_selector(Box::new(DiverseClusterSelector::new(policy)))
+    }
+
     pub fn remove_peer(&mut self, peer_id: TNetwork::PeerId) {
         for cluster in self.epoch_clusters.iter_mut() {
             cluster.remove_peer(&peer_id);
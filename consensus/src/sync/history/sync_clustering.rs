@@ -12,11 +12,13 @@ use nimiq_utils::WakerExt as _;
 use parking_lot::RwLock;
 
 use crate::{
+    error::SyncError,
     messages::{MacroChain, MacroChainError, RequestMacroChain},
     sync::{
         history::{
             cluster::{SyncCluster, SyncClusterResult},
             sync::{EpochIds, Job},
+            target_selector::SyncTargetSelector,
             HistoryMacroSync,
         },
         peer_list::PeerList,
@@ -371,6 +373,7 @@ impl<TNetwork: Network> HistoryMacroSync<TNetwork> {
                 peers,
                 Vec::from(&epoch_ids.ids[id_index..]),
                 epoch_ids.first_epoch_number + id_index,
+                self.max_pending_chunks,
             ));
             // Don't increment the num_clusters here, as this is done in the loop later on.
         }
@@ -415,6 +418,7 @@ impl<TNetwork: Network> HistoryMacroSync<TNetwork> {
                     checkpoint.hash,
                     checkpoint_epoch,
                     checkpoint.block_number as usize,
+                    self.max_pending_chunks,
                 );
                 self.checkpoint_clusters.push_back(cluster);
                 num_clusters += 1;
@@ -442,29 +446,31 @@ impl<TNetwork: Network> HistoryMacroSync<TNetwork> {
     }
 
     pub(crate) fn pop_next_cluster(&mut self) -> Option<SyncCluster<TNetwork>> {
-        let cluster = HistoryMacroSync::<TNetwork>::find_best_cluster(
+        // If we made space in epoch_clusters, wake the task.
+        if let Ok(cluster) = HistoryMacroSync::<TNetwork>::find_best_cluster(
             &mut self.epoch_clusters,
             &self.blockchain,
-        );
-
-        // If we made space in epoch_clusters, wake the task.
-        if cluster.is_some() {
+            self.target_selector.as_mut(),
+        ) {
             self.waker.wake();
-            return cluster;
+            return Some(cluster);
         }
 
         HistoryMacroSync::<TNetwork>::find_best_cluster(
             &mut self.checkpoint_clusters,
             &self.blockchain,
+            self.target_selector.as_mut(),
         )
+        .ok()
     }
 
     fn find_best_cluster(
         clusters: &mut VecDeque<SyncCluster<TNetwork>>,
         blockchain: &Arc<RwLock<Blockchain>>,
-    ) -> Option<SyncCluster<TNetwork>> {
+        target_selector: &mut dyn SyncTargetSelector<TNetwork>,
+    ) -> Result<SyncCluster<TNetwork>, SyncError<TNetwork::PeerId>> {
         if clusters.is_empty() {
-            return None;
+            return Err(SyncError::NoValidSyncTarget);
         }
 
         let (current_block, last_finalized_epoch) = {
@@ -475,17 +481,16 @@ impl<TNetwork: Network> HistoryMacroSync<TNetwork> {
             )
         };
 
-        let (best_idx, _) = clusters
-            .iter()
-            .enumerate()
-            .reduce(|accum, item| {
-                if accum.1.compare(item.1, last_finalized_epoch).is_le() {
-                    accum
-                } else {
-                    item
-                }
-            })
-            .expect("clusters not empty");
+        let best_idx = match target_selector.select(clusters, last_finalized_epoch) {
+            Some(best_idx) if best_idx < clusters.len() => best_idx,
+            _ => {
+                debug!(
+                    num_clusters = clusters.len(),
+                    "Sync target selector didn't choose any cluster"
+                );
+                return Err(SyncError::NoValidSyncTarget);
+            }
+        };
 
         let mut best_cluster = clusters
             .swap_remove_front(best_idx)
@@ -514,7 +519,7 @@ impl<TNetwork: Network> HistoryMacroSync<TNetwork> {
             clusters.len() + 1
         );
 
-        Some(best_cluster)
+        Ok(best_cluster)
     }
 
     /// Reduces the number of clusters for each peer present in the given cluster by 1.
@@ -256,7 +256,14 @@ impl<TNetwork: Network> Stream for HistoryMacroSync<TNetwork> {
 
 #[cfg(test)]
 mod tests {
-    use std::{sync::Arc, task::Poll};
+    use std::{
+        collections::VecDeque,
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc,
+        },
+        task::Poll,
+    };
 
     use futures::{Stream, StreamExt};
     use nimiq_blockchain::{BlockProducer, Blockchain, BlockchainConfig};
@@ -273,7 +280,12 @@ mod tests {
 
     use crate::{
         messages::{RequestBatchSet, RequestHistoryChunk, RequestMacroChain},
-        sync::{history::HistoryMacroSync, syncer::MacroSyncReturn},
+        sync::{
+            history::{
+                cluster::SyncCluster, BestClusterSelector, HistoryMacroSync, SyncTargetSelector,
+            },
+            syncer::MacroSyncReturn,
+        },
     };
 
     fn blockchain() -> Arc<RwLock<Blockchain>> {
@@ -413,6 +425,57 @@ mod tests {
         }
     }
 
+    #[test(tokio::test)]
+    async fn it_uses_the_given_target_selector() {
+        struct CountingSelector(Arc<AtomicUsize>);
+
+        impl<TNetwork: Network> SyncTargetSelector<TNetwork> for CountingSelector {
+            fn select(
+                &mut self,
+                clusters: &VecDeque<SyncCluster<TNetwork>>,
+                last_finalized_epoch: usize,
+            ) -> Option<usize> {
+                self.0.fetch_add(1, Ordering::SeqCst);
+                BestClusterSelector.select(clusters, last_finalized_epoch)
+            }
+        }
+
+        let mut hub = MockHub::default();
+        let net1 = Arc::new(hub.new_network());
+        let net2 = Arc::new(hub.new_network());
+
+        let chain1 = blockchain();
+        let chain2 = blockchain();
+
+        let producer = BlockProducer::new(signing_key(), voting_key());
+        produce_macro_blocks_with_txns(
+            &producer,
+            &chain2,
+            Policy::batches_per_epoch() as usize,
+            1,
+            0,
+        );
+
+        let num_selections = Arc::new(AtomicUsize::new(0));
+        let mut sync = HistoryMacroSync::<MockNetwork>::new(
+            Arc::clone(&chain1),
+            Arc::clone(&net1),
+            net1.subscribe_events(),
+        )
+        .with_target_selector(Box::new(CountingSelector(Arc::clone(&num_selections))));
+
+        spawn_request_handlers(&net2, &chain2);
+        net1.dial_mock(&net2);
+
+        match sync.next().await {
+            Some(MacroSyncReturn::Good(_)) => {
+                assert_eq!(chain1.read().head(), chain2.read().head());
+            }
+            res => panic!("Unexpected HistorySyncReturn: {res:?}"),
+        }
+        assert!(num_selections.load(Ordering::SeqCst) > 0);
+    }
+
     #[test(tokio::test)]
     async fn it_can_sync_multiple_finalized_epochs() {
         let mut hub = MockHub::default();
@@ -561,6 +624,74 @@ mod tests {
         }
     }
 
+    #[test(tokio::test)]
+    async fn it_resumes_an_interrupted_sync_after_restart() {
+        let mut hub = MockHub::default();
+        let net1 = Arc::new(hub.new_network());
+        let net2 = Arc::new(hub.new_network());
+
+        let env = MdbxDatabase::new_volatile(Default::default()).unwrap();
+        let time = Arc::new(OffsetTime::new());
+        let open_chain = || {
+            Arc::new(RwLock::new(
+                Blockchain::new(
+                    env.clone(),
+                    BlockchainConfig::default(),
+                    NetworkId::UnitAlbatross,
+                    Arc::clone(&time),
+                )
+                .unwrap(),
+            ))
+        };
+
+        let chain2 = blockchain();
+        let producer = BlockProducer::new(signing_key(), voting_key());
+        produce_macro_blocks_with_txns(
+            &producer,
+            &chain2,
+            Policy::batches_per_epoch() as usize,
+            1,
+            0,
+        );
+
+        // Sync the first epoch only, then drop the blockchain to simulate a restart. The first
+        // epoch is copied before the second one is produced, since its micro blocks are pruned
+        // once the second epoch is finalized.
+        let chain1 = open_chain();
+        copy_chain(&chain2, &chain1);
+        drop(chain1);
+
+        produce_macro_blocks_with_txns(
+            &producer,
+            &chain2,
+            Policy::batches_per_epoch() as usize,
+            1,
+            0,
+        );
+
+        let chain1 = open_chain();
+        assert_eq!(
+            chain1.read().block_number(),
+            Policy::blocks_per_epoch() + Policy::genesis_block_number()
+        );
+
+        let mut sync = HistoryMacroSync::<MockNetwork>::new(
+            Arc::clone(&chain1),
+            Arc::clone(&net1),
+            net1.subscribe_events(),
+        );
+
+        spawn_request_handlers(&net2, &chain2);
+        net1.dial_mock(&net2);
+
+        match sync.next().await {
+            Some(MacroSyncReturn::Good(_)) => {
+                assert_eq!(chain1.read().head(), chain2.read().head());
+            }
+            res => panic!("Unexpected HistorySyncReturn: {res:?}"),
+        }
+    }
+
     #[test(tokio::test)]
     async fn it_can_sync_consecutive_batches_from_different_peers() {
         let mut hub = MockHub::default();
@@ -0,0 +1,233 @@
+use std::{
+    collections::{HashSet, VecDeque},
+    net::{IpAddr, Ipv4Addr, Ipv6Addr},
+};
+
+use nimiq_network_interface::{network::Network, Multiaddr, Protocol};
+
+use crate::sync::history::cluster::SyncCluster;
+
+/// Strategy used by the history macro sync to choose the cluster to sync next.
+///
+/// Each cluster represents a sync target, i.e. a chain of epoch ids offered by a set of peers.
+pub trait SyncTargetSelector<TNetwork: Network>: Send {
+    /// Returns the index of the cluster to sync next, or `None` if none of the given clusters
+    /// is a valid sync target. `clusters` is never empty.
+    fn select(
+        &mut self,
+        clusters: &VecDeque<SyncCluster<TNetwork>>,
+        last_finalized_epoch: usize,
+    ) -> Option<usize>;
+}
+
+/// The default sync target selector. It prefers clusters starting at lower epochs, then
+/// (for checkpoints) higher block numbers, then clusters with more peers and more epoch ids.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct BestClusterSelector;
+
+impl<TNetwork: Network> SyncTargetSelector<TNetwork> for BestClusterSelector {
+    fn select(
+        &mut self,
+        clusters: &VecDeque<SyncCluster<TNetwork>>,
+        last_finalized_epoch: usize,
+    ) -> Option<usize> {
+        best_cluster(clusters.iter().enumerate(), last_finalized_epoch)
+    }
+}
+
+/// How diverse the peers backing a sync target need to be. Peers are grouped by their /24
+/// (IPv4) or /48 (IPv6) subnet, such that a single operator needs addresses in many networks
+/// to be the only source of our sync. Peers without an IP address don't add to the diversity.
+///
+/// The policy only covers choosing the sync target. Within the chosen cluster, the epochs and
+/// history chunks are still requested from its peers in turn, regardless of their subnets. A
+/// subnet with many peers in that cluster can thus still serve most of the history.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum DiversityPolicy {
+    /// The addresses of the peers aren't considered.
+    #[default]
+    Disabled,
+    /// Clusters whose peers are in fewer than the given number of distinct subnets are only
+    /// chosen if no cluster satisfies this.
+    MinSubnets(usize),
+}
+
+/// A sync target selector that chooses the best cluster among those satisfying the
+/// [`DiversityPolicy`]. If none does, the policy is relaxed and the best cluster overall is
+/// chosen, as with the [`BestClusterSelector`].
+#[derive(Clone, Copy, Debug, Default)]
+pub struct DiverseClusterSelector {
+    policy: DiversityPolicy,
+}
+
+impl DiverseClusterSelector {
+    pub fn new(policy: DiversityPolicy) -> Self {
+        Self { policy }
+    }
+
+    /// Selects the cluster to sync next, using `cluster_subnets` to determine the number of
+    /// distinct subnets the peers of a cluster are in.
+    fn select_by<TNetwork: Network>(
+        &self,
+        clusters: &VecDeque<SyncCluster<TNetwork>>,
+        last_finalized_epoch: usize,
+        cluster_subnets: impl Fn(&SyncCluster<TNetwork>) -> usize,
+    ) -> Option<usize> {
+        let DiversityPolicy::MinSubnets(min_subnets) = self.policy else {
+            return best_cluster(clusters.iter().enumerate(), last_finalized_epoch);
+        };
+
+        let diverse_clusters = clusters
+            .iter()
+            .enumerate()
+            .filter(|(_, cluster)| cluster_subnets(cluster) >= min_subnets);
+        best_cluster(diverse_clusters, last_finalized_epoch).or_else(|| {
+            debug!(
+                min_subnets,
+                "No sync target satisfies the diversity policy, relaxing it"
+            );
+            best_cluster(clusters.iter().enumerate(), last_finalized_epoch)
+        })
+    }
+}
+
+impl<TNetwork: Network> SyncTargetSelector<TNetwork> for DiverseClusterSelector {
+    fn select(
+        &mut self,
+        clusters: &VecDeque<SyncCluster<TNetwork>>,
+        last_finalized_epoch: usize,
+    ) -> Option<usize> {
+        self.select_by(clusters, last_finalized_epoch, |cluster| {
+            num_subnets(&cluster.peer_addresses())
+        })
+    }
+}
+
+/// Returns the index of the best of the given clusters.
+fn best_cluster<'a, TNetwork: Network>(
+    clusters: impl Iterator<Item = (usize, &'a SyncCluster<TNetwork>)>,
+    last_finalized_epoch: usize,
+) -> Option<usize> {
+    clusters
+        .reduce(|accum, item| {
+            if accum.1.compare(item.1, last_finalized_epoch).is_le() {
+                accum
+            } else {
+                item
+            }
+        })
+        .map(|(idx, _)| idx)
+}
+
+/// Returns the number of distinct subnets the given addresses are in.
+fn num_subnets(addresses: &[Multiaddr]) -> usize {
+    addresses
+        .iter()
+        .filter_map(subnet)
+        .collect::<HashSet<_>>()
+        .len()
+}
+
+/// Returns the /24 (IPv4) or /48 (IPv6) subnet of the given address, if it contains an IP.
+fn subnet(address: &Multiaddr) -> Option<IpAddr> {
+    address.iter().find_map(|protocol| match protocol {
+        Protocol::Ip4(ip) => Some(IpAddr::V4(Ipv4Addr::from(u32::from(ip) & 0xffff_ff00))),
+        Protocol::Ip6(ip) => Some(IpAddr::V6(Ipv6Addr::from(
+            u128::from(ip) & !((1u128 << 80) - 1),
+        ))),
+        _ => None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use nimiq_blockchain::{Blockchain, BlockchainConfig};
+    use nimiq_database::mdbx::MdbxDatabase;
+    use nimiq_hash::Blake2bHash;
+    use nimiq_network_interface::multiaddr;
+    use nimiq_network_mock::{MockHub, MockNetwork};
+    use nimiq_primitives::networks::NetworkId;
+    use nimiq_test_log::test;
+    use nimiq_utils::time::OffsetTime;
+    use parking_lot::RwLock;
+
+    use super::*;
+    use crate::sync::peer_list::PeerList;
+
+    fn clusters(num_epoch_ids: &[usize]) -> VecDeque<SyncCluster<MockNetwork>> {
+        let blockchain = Arc::new(RwLock::new(
+            Blockchain::new(
+                MdbxDatabase::new_volatile(Default::default()).unwrap(),
+                BlockchainConfig::default(),
+                NetworkId::UnitAlbatross,
+                Arc::new(OffsetTime::new()),
+            )
+            .unwrap(),
+        ));
+        let mut hub = MockHub::default();
+        let network = Arc::new(hub.new_network());
+
+        num_epoch_ids
+            .iter()
+            .map(|&num_epoch_ids| {
+                let mut peers = PeerList::default();
+                peers.add_peer(hub.new_network().peer_id());
+                SyncCluster::for_epoch(
+                    Arc::clone(&blockchain),
+                    Arc::clone(&network),
+                    peers,
+                    (0..num_epoch_ids as u8)
+                        .map(|i| Blake2bHash::from([i; 32]))
+                        .collect(),
+                    1,
+                    1,
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn it_prefers_diverse_clusters() {
+        // The first cluster is the best one, but its peers are in a single subnet.
+        let clusters = clusters(&[2, 1]);
+        let subnets = |cluster: &SyncCluster<MockNetwork>| {
+            if cluster.id == clusters[0].id {
+                1
+            } else {
+                2
+            }
+        };
+
+        let selector = DiverseClusterSelector::new(DiversityPolicy::Disabled);
+        assert_eq!(selector.select_by(&clusters, 0, subnets), Some(0));
+
+        let selector = DiverseClusterSelector::new(DiversityPolicy::MinSubnets(2));
+        assert_eq!(selector.select_by(&clusters, 0, subnets), Some(1));
+
+        // If no cluster is diverse enough, the policy is relaxed.
+        let selector = DiverseClusterSelector::new(DiversityPolicy::MinSubnets(3));
+        assert_eq!(selector.select_by(&clusters, 0, subnets), Some(0));
+
+        // Without IP addresses, the peers don't add to the diversity.
+        let mut selector = DiverseClusterSelector::new(DiversityPolicy::MinSubnets(1));
+        assert_eq!(selector.select(&clusters, 0), Some(0));
+    }
+
+    #[test]
+    fn it_counts_distinct_subnets() {
+        let addresses = [
+            multiaddr!(Ip4([10, 0, 0, 1]), Tcp(8443u16)),
+            multiaddr!(Ip4([10, 0, 0, 200]), Tcp(8443u16)),
+            multiaddr!(Ip4([10, 0, 1, 1]), Tcp(8443u16)),
+            multiaddr!(Ip6([0x2001, 0xdb8, 1, 1, 0, 0, 0, 1]), Tcp(8443u16)),
+            multiaddr!(Ip6([0x2001, 0xdb8, 1, 2, 0, 0, 0, 1]), Tcp(8443u16)),
+            multiaddr!(Dns4("example.com"), Tcp(8443u16)),
+            multiaddr!(Memory(1u64)),
+        ];
+        assert_eq!(num_subnets(&addresses), 3);
+        assert_eq!(num_subnets(&addresses[..2]), 1);
+        assert_eq!(num_subnets(&addresses[5..]), 0);
+    }
+}
@@ -141,6 +141,10 @@ const PENDING_SIZE: usize = 5;
 ///   2. Request epoch IDs from the peer
 ///   3. Request the last (if any) election or checkpoint blocks
 ///
+/// The ZKP received in step 1 is verified when it is pushed to the blockchain, before any state
+/// derived from it is trusted. If the verification fails, the peer is banned and not used as a
+/// sync target.
+///
 /// If during the process, a peer is deemed as outdated, then it is emitted
 pub struct LightMacroSync<TNetwork: Network> {
     /// The blockchain
@@ -18,12 +18,15 @@ use nimiq_utils::WakerExt;
 use parking_lot::Mutex;
 
 use super::{BlockAndSource, QueuedBlock};
-use crate::sync::{
-    live::{
-        block_queue::queue::BlockQueue,
-        queue::{self, LiveSyncQueue},
+use crate::{
+    sync::{
+        live::{
+            block_queue::queue::BlockQueue,
+            queue::{self, LiveSyncQueue},
+        },
+        syncer::{LiveSyncEvent, LiveSyncPeerEvent, LiveSyncPushEvent},
     },
-    syncer::{LiveSyncEvent, LiveSyncPeerEvent, LiveSyncPushEvent},
+    Error,
 };
 
 pub enum PushOpResult<N: Network> {
@@ -135,10 +138,14 @@ impl<N: Network> LiveSyncQueue<N> for BlockQueue<N> {
             }
             PushOpResult::Head(Err(result), hash) | PushOpResult::Buffered(Err(result), hash) => {
                 // If there was a blockchain push error, we remove the block from the pending blocks
-                log::trace!("Head push operation failed because of {}", result);
                 self.on_block_processed(&hash);
-                return Some(LiveSyncEvent::PushEvent(LiveSyncPushEvent::RejectedBlock(
+                let error = Error::BlockRejected {
                     hash,
+                    reason: result,
+                };
+                log::debug!(%error, "Head push operation failed");
+                return Some(LiveSyncEvent::PushEvent(LiveSyncPushEvent::RejectedBlock(
+                    Arc::new(error),
                 )));
             }
             PushOpResult::PeerEvent(event) => return Some(LiveSyncEvent::PeerEvent(event)),
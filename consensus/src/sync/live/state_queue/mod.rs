@@ -16,7 +16,7 @@ use nimiq_blockchain_interface::{AbstractBlockchain, BlockchainEvent};
 use nimiq_hash::Blake2bHash;
 use nimiq_network_interface::{
     network::Network,
-    request::{RequestCommon, RequestMarker},
+    request::{RequestCommon, RequestMarker, RequestPriority},
 };
 use nimiq_primitives::{
     key_nibbles::KeyNibbles,
@@ -87,6 +87,7 @@ impl RequestCommon for RequestChunk {
     type Response = ResponseChunk;
 
     const MAX_REQUESTS: u32 = MAX_REQUEST_RESPONSE_CHUNKS;
+    const PRIORITY: RequestPriority = RequestPriority::Low;
 }
 
 pub enum QueuedStateChunks<N: Network> {
@@ -18,6 +18,7 @@ use nimiq_utils::stream::FuturesUnordered;
 
 use crate::{
     consensus::ResolveBlockRequest, messages::RequestHead, sync::live::block_queue::BlockSource,
+    Error,
 };
 
 /// Trait that defines how a node synchronizes macro blocks
@@ -86,9 +87,9 @@ pub enum LiveSyncPushEvent {
     AcceptedBufferedBlock(Blake2bHash, usize),
     /// Missing blocks were received. The vec of all adopted blocks hashes is given here.
     ReceivedMissingBlocks(Vec<Blake2bHash>),
-    /// Block was rejected
+    /// Block was rejected, the error contains its hash and the reason
     /// (this is only returned in *some* cases blocks were rejected)
-    RejectedBlock(Blake2bHash),
+    RejectedBlock(Arc<Error>),
     /// Chunks have been accepted for the head block
     /// (note that other accepted chunks won't be announced)
     AcceptedChunks(Blake2bHash),
@@ -19,7 +19,7 @@ use pin_project::pin_project;
 
 #[cfg(feature = "full")]
 use crate::sync::{
-    history::HistoryMacroSync,
+    history::{DiversityPolicy, HistoryMacroSync},
     live::{diff_queue::DiffQueue, state_queue::StateQueue, StateLiveSync},
 };
 use crate::{
@@ -68,6 +68,8 @@ impl<N: Network> SyncerProxy<N> {
         network: Arc<N>,
         bls_cache: Arc<Mutex<PublicKeyCache>>,
         network_event_rx: SubscribeEvents<N::PeerId>,
+        max_pending_history_chunks: usize,
+        diversity_policy: DiversityPolicy,
     ) -> Self {
         assert!(
             matches!(blockchain_proxy, BlockchainProxy::Full(_)),
@@ -93,7 +95,9 @@ impl<N: Network> SyncerProxy<N> {
             bls_cache,
         );
 
-        let macro_sync = HistoryMacroSync::new(blockchain, Arc::clone(&network), network_event_rx);
+        let macro_sync = HistoryMacroSync::new(blockchain, Arc::clone(&network), network_event_rx)
+            .with_max_pending_chunks(max_pending_history_chunks)
+            .with_diversity_policy(diversity_policy);
 
         Self::History(Syncer::new(
             blockchain_proxy,
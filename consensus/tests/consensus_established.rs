@@ -0,0 +1,123 @@
+use std::{sync::Arc, time::Duration};
+
+use futures::{FutureExt, StreamExt};
+use nimiq_block::Block;
+use nimiq_blockchain::{BlockProducer, Blockchain, BlockchainConfig};
+use nimiq_blockchain_interface::AbstractBlockchain;
+use nimiq_blockchain_proxy::BlockchainProxy;
+use nimiq_bls::cache::PublicKeyCache;
+use nimiq_consensus::{
+    messages::{BlockBodyTopic, BlockHeaderMessage, BlockHeaderTopic},
+    sync::{
+        history::{DiversityPolicy, DEFAULT_MAX_PENDING_CHUNKS},
+        syncer_proxy::SyncerProxy,
+    },
+    Consensus, ConsensusEvent,
+};
+use nimiq_database::mdbx::MdbxDatabase;
+use nimiq_hash::Blake2bHash;
+use nimiq_network_interface::network::Network;
+use nimiq_network_mock::{MockHub, MockNetwork};
+use nimiq_primitives::{networks::NetworkId, policy::Policy};
+use nimiq_test_log::test;
+use nimiq_test_utils::{
+    blockchain::{next_micro_block, signing_key, voting_key},
+    node::TESTING_BLS_CACHE_MAX_CAPACITY,
+};
+use nimiq_time::{sleep, timeout};
+use nimiq_utils::{spawn, time::OffsetTime};
+use nimiq_zkp_component::ZKPComponent;
+use parking_lot::{Mutex, RwLock};
+
+fn blockchain() -> Arc<RwLock<Blockchain>> {
+    Arc::new(RwLock::new(
+        Blockchain::new(
+            MdbxDatabase::new_volatile(Default::default()).unwrap(),
+            BlockchainConfig::default(),
+            NetworkId::UnitAlbatross,
+            Arc::new(OffsetTime::new()),
+        )
+        .unwrap(),
+    ))
+}
+
+async fn consensus(
+    blockchain: &Arc<RwLock<Blockchain>>,
+    network: &Arc<MockNetwork>,
+    min_peers: usize,
+) -> Consensus<MockNetwork> {
+    let blockchain_proxy = BlockchainProxy::from(blockchain);
+    let zkp_proxy = ZKPComponent::new(blockchain_proxy.clone(), Arc::clone(network), None)
+        .await
+        .proxy();
+    let syncer = SyncerProxy::new_history(
+        blockchain_proxy.clone(),
+        Arc::clone(network),
+        Arc::new(Mutex::new(PublicKeyCache::new(
+            TESTING_BLS_CACHE_MAX_CAPACITY,
+        ))),
+        network.subscribe_events(),
+        DEFAULT_MAX_PENDING_CHUNKS,
+        DiversityPolicy::Disabled,
+    )
+    .await;
+    Consensus::new(
+        blockchain_proxy,
+        Arc::clone(network),
+        syncer,
+        min_peers,
+        zkp_proxy,
+    )
+}
+
+#[test(tokio::test)]
+async fn consensus_is_kept_when_a_single_peer_announces_a_far_ahead_block() {
+    let mut hub = MockHub::default();
+
+    // An honest node and a malicious one, both at the same head as ours.
+    let blockchain1 = blockchain();
+    let net1 = Arc::new(hub.new_network());
+    let _consensus1 = consensus(&blockchain1, &net1, 1).await;
+
+    let blockchain3 = blockchain();
+    let net3 = Arc::new(hub.new_network());
+    let _consensus3 = consensus(&blockchain3, &net3, 1).await;
+
+    // Our node, which establishes consensus once both peers are synced.
+    let blockchain2 = blockchain();
+    let net2 = Arc::new(hub.new_network());
+    let consensus2 = consensus(&blockchain2, &net2, 2).await;
+    let consensus2_proxy = consensus2.proxy();
+    let mut consensus_events = consensus2_proxy.subscribe_events();
+    spawn(consensus2);
+
+    net1.dial_mock(&net2);
+    net3.dial_mock(&net2);
+    let event = timeout(Duration::from_secs(10), consensus_events.next())
+        .await
+        .expect("Consensus should be established");
+    assert!(matches!(
+        event,
+        Some(Ok(ConsensusEvent::Established { .. }))
+    ));
+
+    // The malicious peer announces a forged block more than a batch ahead of the chain.
+    let producer = BlockProducer::new(signing_key(), voting_key());
+    let block_number = blockchain3.read().block_number() + Policy::blocks_per_batch() + 1;
+    let block = match next_micro_block(&producer, &blockchain3) {
+        Block::Micro(mut block) => {
+            block.header.block_number = block_number;
+            block.header.parent_hash = Blake2bHash::default();
+            Block::Micro(block)
+        }
+        Block::Macro(_) => unreachable!(),
+    };
+    let (header, body) = BlockHeaderMessage::split_block(block);
+    net3.publish::<BlockHeaderTopic>(header).await.unwrap();
+    net3.publish::<BlockBodyTopic>(body).await.unwrap();
+
+    // The forged block must not make us lose consensus.
+    sleep(Duration::from_secs(2)).await;
+    assert!(consensus2_proxy.is_established());
+    assert!(consensus_events.next().now_or_never().is_none());
+}
@@ -3,7 +3,13 @@ use std::{str::FromStr, sync::Arc};
 use nimiq_blockchain::{BlockProducer, Blockchain, BlockchainConfig};
 use nimiq_blockchain_proxy::BlockchainProxy;
 use nimiq_bls::cache::PublicKeyCache;
-use nimiq_consensus::{sync::syncer_proxy::SyncerProxy, Consensus};
+use nimiq_consensus::{
+    sync::{
+        history::{DiversityPolicy, DEFAULT_MAX_PENDING_CHUNKS},
+        syncer_proxy::SyncerProxy,
+    },
+    Consensus,
+};
 use nimiq_database::mdbx::MdbxDatabase;
 use nimiq_keys::{Address, KeyPair, PrivateKey};
 use nimiq_network_interface::network::Network;
@@ -58,6 +64,8 @@ async fn test_request_transactions_by_address() {
             TESTING_BLS_CACHE_MAX_CAPACITY,
         ))),
         net1.subscribe_events(),
+        DEFAULT_MAX_PENDING_CHUNKS,
+        DiversityPolicy::Disabled,
     )
     .await;
 
@@ -77,6 +85,8 @@ async fn test_request_transactions_by_address() {
             TESTING_BLS_CACHE_MAX_CAPACITY,
         ))),
         net2.subscribe_events(),
+        DEFAULT_MAX_PENDING_CHUNKS,
+        DiversityPolicy::Disabled,
     )
     .await;
     let consensus2 = Consensus::from_network(
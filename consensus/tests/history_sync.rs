@@ -10,10 +10,11 @@ use nimiq_consensus::{
     sync::{
         history::{
             cluster::{HistoryChunkRequest, SyncCluster},
-            HistoryMacroSync,
+            DiversityPolicy, HistoryMacroSync, DEFAULT_MAX_PENDING_CHUNKS,
         },
         syncer_proxy::SyncerProxy,
     },
+    SyncError,
 };
 use nimiq_database::mdbx::MdbxDatabase;
 use nimiq_genesis::NetworkId;
@@ -209,6 +210,8 @@ async fn sync_ingredients() {
             TESTING_BLS_CACHE_MAX_CAPACITY,
         ))),
         net1.subscribe_events(),
+        DEFAULT_MAX_PENDING_CHUNKS,
+        DiversityPolicy::Disabled,
     )
     .await;
     // The consensus itself is unused, but in from_network the request handlers are
@@ -248,6 +251,8 @@ async fn sync_ingredients() {
             TESTING_BLS_CACHE_MAX_CAPACITY,
         ))),
         net2.subscribe_events(),
+        DEFAULT_MAX_PENDING_CHUNKS,
+        DiversityPolicy::Disabled,
     )
     .await;
     // The consensus itself is unused, but in from_network the request handlers are
@@ -358,6 +363,16 @@ async fn sync_ingredients() {
         Some(true)
     );
 
+    // The chunk of epoch 2 must be rejected as part of the history of epoch 1.
+    assert!(matches!(
+        SyncCluster::<Network>::verify_history_chunk(
+            &HistoryChunkRequest::from_block(block1, 0),
+            peer_id,
+            &chunk,
+        ),
+        Err(SyncError::InvalidChunkReceived { peer }) if peer == peer_id
+    ));
+
     // Re-request Macro chain one final time. This time it must return neither epochs, nor a checkpoint.
     let macro_chain = HistoryMacroSync::request_macro_chain(
         Arc::clone(&net2),
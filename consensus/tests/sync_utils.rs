@@ -8,7 +8,11 @@ use nimiq_bls::cache::PublicKeyCache;
 use nimiq_consensus::{
     consensus::Consensus,
     messages::{BlockBodyTopic, BlockHeaderMessage, BlockHeaderTopic},
-    sync::{syncer::MacroSyncReturn, syncer_proxy::SyncerProxy},
+    sync::{
+        history::{DiversityPolicy, DEFAULT_MAX_PENDING_CHUNKS},
+        syncer::MacroSyncReturn,
+        syncer_proxy::SyncerProxy,
+    },
 };
 use nimiq_database::mdbx::MdbxDatabase;
 use nimiq_genesis::NetworkId;
@@ -49,6 +53,8 @@ async fn syncer(
                     TESTING_BLS_CACHE_MAX_CAPACITY,
                 ))),
                 network.subscribe_events(),
+                DEFAULT_MAX_PENDING_CHUNKS,
+                DiversityPolicy::Disabled,
             )
             .await
         }
@@ -120,6 +126,8 @@ pub async fn sync_two_peers(
             TESTING_BLS_CACHE_MAX_CAPACITY,
         ))),
         net1.subscribe_events(),
+        DEFAULT_MAX_PENDING_CHUNKS,
+        DiversityPolicy::Disabled,
     )
     .await;
     let zkp_prover1 =
@@ -13,6 +13,34 @@ use crate::{
 const GIGABYTE: usize = 1024 * 1024 * 1024;
 const TERABYTE: usize = GIGABYTE * 1024;
 
+/// How durably writes are persisted. Less durable modes increase the write throughput, but can
+/// lose the most recent transactions (or even corrupt the database) on a system crash.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum SyncMode {
+    /// Every commit is flushed to disk.
+    #[default]
+    Durable,
+    /// The meta page is not flushed on commit, so the last transactions may be rolled back on a
+    /// system crash.
+    NoMetaSync,
+    /// Flushing is left to the operating system. The database can't be corrupted by a system
+    /// crash, but recent transactions may be lost.
+    SafeNoSync,
+    /// Flushing is left to the operating system. A system crash may corrupt the database.
+    UtterlyNoSync,
+}
+
+impl From<SyncMode> for libmdbx::SyncMode {
+    fn from(value: SyncMode) -> Self {
+        match value {
+            SyncMode::Durable => libmdbx::SyncMode::Durable,
+            SyncMode::NoMetaSync => libmdbx::SyncMode::NoMetaSync,
+            SyncMode::SafeNoSync => libmdbx::SyncMode::SafeNoSync,
+            SyncMode::UtterlyNoSync => libmdbx::SyncMode::UtterlyNoSync,
+        }
+    }
+}
+
 /// Database config options.
 pub struct DatabaseConfig {
     /// The maximum number of tables that can be opened.
@@ -29,6 +57,8 @@ pub struct DatabaseConfig {
     pub growth_step: Option<isize>,
     /// The threshold of unused space, after which the database file will be shrunk.
     pub shrink_threshold: Option<isize>,
+    /// How durably writes are persisted.
+    pub sync_mode: SyncMode,
 }
 
 impl Default for DatabaseConfig {
@@ -43,6 +73,7 @@ impl Default for DatabaseConfig {
             // Default growth step: 4GB
             growth_step: Some(4 * GIGABYTE as isize),
             shrink_threshold: None,
+            sync_mode: SyncMode::Durable,
         }
     }
 }
@@ -54,7 +85,7 @@ impl From<DatabaseConfig> for libmdbx::DatabaseOptions {
             max_readers: value.max_readers,
             no_rdahead: value.no_rdahead,
             mode: libmdbx::Mode::ReadWrite(libmdbx::ReadWriteOptions {
-                sync_mode: libmdbx::SyncMode::Durable,
+                sync_mode: value.sync_mode.into(),
                 min_size: value.size.as_ref().map(|r| r.start),
                 max_size: value.size.map(|r| r.end),
                 ..Default::default()
@@ -113,6 +144,21 @@ impl MdbxDatabase {
         Ok(mdbx)
     }
 
+    /// Opens an existing database at the given path in read-only mode.
+    /// Tables can't be created on such a database, so they must already exist.
+    pub fn new_read_only<P: AsRef<Path>>(path: P, config: DatabaseConfig) -> Result<Self, Error> {
+        let options = libmdbx::DatabaseOptions {
+            mode: libmdbx::Mode::ReadOnly,
+            ..libmdbx::DatabaseOptions::from(config)
+        };
+        let db = libmdbx::Database::open_with_options(path, options)?;
+
+        Ok(MdbxDatabase {
+            db: Arc::new(db),
+            temp_dir: None,
+        })
+    }
+
     /// Creates a volatile database (in a temporary directory, which cleans itself after use).
     pub fn new_volatile(config: DatabaseConfig) -> Result<Self, Error> {
         let temp_dir = Arc::new(TempDir::new()?);
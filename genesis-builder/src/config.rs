@@ -9,7 +9,7 @@ use time::OffsetDateTime;
 
 /// Struct that defines the genesis configuration that is going to be parsed
 /// from the genesis TOML files.
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
 pub struct GenesisConfig {
     /// Network ID used in blocks, transactions, etc.
     pub network: NetworkId,
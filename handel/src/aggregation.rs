@@ -1,4 +1,5 @@
 use std::{
+    io::Write,
     mem,
     pin::Pin,
     task::{Context, Poll},
@@ -8,6 +9,7 @@ use futures::{
     future::{BoxFuture, Future, FutureExt},
     stream::{BoxStream, Stream, StreamExt},
 };
+use nimiq_hash::{Blake2bHasher, HashOutput, Hasher};
 use nimiq_time::{interval, Interval};
 
 use crate::{
@@ -16,7 +18,7 @@ use crate::{
     identity::IdentityRegistry,
     level::Level,
     network::{LevelUpdateSender, Network},
-    partitioner::Partitioner,
+    partitioner::{Partitioner, PartitioningError},
     pending_contributions::{PendingContribution, PendingContributionList},
     protocol::Protocol,
     store::ContributionStore,
@@ -72,21 +74,46 @@ where
         Option<BoxFuture<'static, (VerificationResult, PendingContribution<P::Contribution>)>>,
 }
 
+/// Derives the seed for the peer order of the levels from our node ID and the identifier of
+/// the aggregation. This makes the order reproducible, while it still differs between
+/// aggregations.
+fn level_seed<TId: Identifier>(node_id: usize, id: &TId) -> u64 {
+    let mut hasher = Blake2bHasher::new();
+    hasher.write_all(&(node_id as u64).to_be_bytes()).unwrap();
+    hasher.write_all(format!("{id:?}").as_bytes()).unwrap();
+
+    let hash = hasher.finish();
+    let mut seed = [0u8; 8];
+    seed.copy_from_slice(&hash.as_bytes()[..8]);
+    u64::from_be_bytes(seed)
+}
+
 impl<TId, P, N> OngoingAggregation<TId, P, N>
 where
     TId: Identifier,
     P: Protocol<TId>,
     N: Network<Contribution = P::Contribution>,
 {
+    /// Creates the aggregation. Fails if the partitioner of the protocol returns invalid levels.
     pub fn new(
         protocol: P,
         config: Config,
         own_contribution: P::Contribution,
         input_stream: LevelUpdateStream<P, TId>,
         sender: LevelUpdateSender<N>,
-    ) -> Self {
+    ) -> Result<Self, PartitioningError> {
         // Invoke the partitioner to create the level structure of peers.
-        let levels: Vec<Level> = Level::create_levels(protocol.partitioner(), protocol.identify());
+        // Each aggregation uses its own seed, such that nodes at the same position don't contact
+        // their peers in the same order.
+        let seed = level_seed(protocol.node_id(), &protocol.identify());
+        log::debug!(
+            id = ?protocol.identify(),
+            node_id = protocol.node_id(),
+            seed,
+            "Creating the aggregation levels"
+        );
+        let levels: Vec<Level> =
+            Level::try_create_levels(protocol.partitioner(), protocol.identify(), seed)?;
 
         // Create an empty list which can later be polled for the best available pending contribution.
         let mut pending_contributions =
@@ -104,7 +131,7 @@ where
         let periodic_update_interval = interval(config.update_interval);
 
         // Create the NextAggregation struct
-        Self {
+        Ok(Self {
             protocol,
             config,
             pending_contributions,
@@ -115,7 +142,7 @@ where
             periodic_update_interval,
             next_level_timeout: 0,
             current_verification: None,
-        }
+        })
     }
 
     /// Starts level `level`
@@ -144,7 +171,9 @@ where
                         best,
                         level.id,
                         !level.receive_complete(),
-                        level.select_next_peers(self.config.peer_count),
+                        level.select_next_peers(
+                            self.config.fan_out.peer_count(level.id, level.num_peers()),
+                        ),
                     );
                 }
             }
@@ -198,15 +227,11 @@ where
                 level_id,
                 "Level complete",
             );
-            {
-                // Acquire write lock and set the level state for this level to completed.
-                self.levels
-                    .get(level_id)
-                    .unwrap() // would have panicked earlier.
-                    .state
-                    .write()
-                    .receive_completed = true;
-            }
+            // Set the level state for this level to completed.
+            self.levels
+                .get(level_id)
+                .unwrap() // would have panicked earlier.
+                .complete();
             // if there is a level with a higher id than the completed one it needs to be activated.
             if level_id + 1 < self.levels.len() {
                 // activate next level
@@ -227,7 +252,9 @@ where
                         multisig,
                         level.id,
                         !level.receive_complete(),
-                        level.select_next_peers(self.config.peer_count),
+                        level.select_next_peers(
+                            self.config.fan_out.peer_count(level.id, level.num_peers()),
+                        ),
                     );
                 }
             }
@@ -282,7 +309,9 @@ where
                 let level = self.levels.get(level_id).unwrap();
                 (
                     level.receive_complete(),
-                    level.select_next_peers(self.config.peer_count),
+                    level.select_next_peers(
+                        self.config.fan_out.peer_count(level_id, level.num_peers()),
+                    ),
                 )
             };
 
@@ -578,6 +607,9 @@ where
     /// The aggregation has just finished and is transitioning from ongoing to finished.
     /// Used to deconstruct the OngoingAggregation and should be `unreachable!()` everywhere but in that location.
     Transitioning,
+    /// The aggregation could not be started because the partitioner returned invalid levels.
+    /// It ends without producing any stream items or network traffic.
+    Aborted,
 }
 
 impl<TId, P, N> Aggregation<TId, P, N>
@@ -597,13 +629,13 @@ where
         let sender = LevelUpdateSender::new(protocol.partitioner().size(), network);
 
         // Aggregations start out as Ongoing
-        Self::Ongoing(OngoingAggregation::new(
-            protocol,
-            config,
-            own_contribution,
-            input_stream,
-            sender,
-        ))
+        match OngoingAggregation::new(protocol, config, own_contribution, input_stream, sender) {
+            Ok(aggregation) => Self::Ongoing(aggregation),
+            Err(error) => {
+                log::error!(%error, "Aborting aggregation, failed to create the levels");
+                Self::Aborted
+            }
+        }
     }
 }
 
@@ -621,6 +653,7 @@ where
             Self::Transitioning => {
                 unreachable!("Aggregation should never be transitioning when polled.")
             }
+            Self::Aborted => return Poll::Ready(None),
             Self::Finished(finished_aggregation) => {
                 // Finished aggregations are simply polled as the future will never produce a value.
                 assert!(finished_aggregation.poll_unpin(cx).is_pending());
@@ -1,5 +1,32 @@
 use std::time::Duration;
 
+/// Policy determining how many peers are contacted on a level.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum FanOutPolicy {
+    /// Contacts the same number of peers on every level.
+    Constant(usize),
+    /// Contacts one peer per `peers_per_contact` peers of the level (rounded up), but at least
+    /// `min` peers. Higher levels contain more peers and thus get more of them contacted.
+    Proportional {
+        min: usize,
+        peers_per_contact: usize,
+    },
+}
+
+impl FanOutPolicy {
+    /// Returns the number of peers to contact on the level with the given index and number of
+    /// peers. The count is not capped by the level size, the peer selection takes care of that.
+    pub fn peer_count(&self, _level: usize, level_size: usize) -> usize {
+        match *self {
+            FanOutPolicy::Constant(count) => count,
+            FanOutPolicy::Proportional {
+                min,
+                peers_per_contact,
+            } => level_size.div_ceil(peers_per_contact.max(1)).max(min),
+        }
+    }
+}
+
 /// Handel configuration settings
 #[derive(Clone, Debug)]
 pub struct Config {
@@ -13,7 +40,7 @@ pub struct Config {
     pub timeout: Duration,
 
     /// How many peers are contacted at each level
-    pub peer_count: usize,
+    pub fan_out: FanOutPolicy,
 }
 
 impl Default for Config {
@@ -22,7 +49,30 @@ impl Default for Config {
             update_count: 1,
             update_interval: Duration::from_millis(500),
             timeout: Duration::from_millis(400),
-            peer_count: 2,
+            fan_out: FanOutPolicy::Constant(2),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use nimiq_test_log::test;
+
+    use super::*;
+
+    #[test]
+    fn it_computes_the_fan_out() {
+        let constant = FanOutPolicy::Constant(2);
+        assert_eq!(constant.peer_count(1, 1), 2);
+        assert_eq!(constant.peer_count(5, 16), 2);
+
+        let proportional = FanOutPolicy::Proportional {
+            min: 2,
+            peers_per_contact: 4,
+        };
+        assert_eq!(proportional.peer_count(1, 1), 2);
+        assert_eq!(proportional.peer_count(4, 8), 2);
+        assert_eq!(proportional.peer_count(5, 16), 4);
+        assert_eq!(proportional.peer_count(6, 25), 7);
+    }
+}
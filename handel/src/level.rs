@@ -1,7 +1,8 @@
-use std::{cmp::min, sync::Arc};
+use std::{cmp::min, fmt, ops::RangeInclusive, sync::Arc, time::Duration};
 
+use nimiq_collections::bitset::BitSet;
 use parking_lot::RwLock;
-use rand::{seq::SliceRandom, thread_rng};
+use rand::{rngs::StdRng, seq::SliceRandom, Rng, SeedableRng};
 
 use crate::{
     contribution::AggregatableContribution,
@@ -9,7 +10,7 @@ use crate::{
 };
 
 /// Struct that defines the state of a level
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Default)]
 pub struct LevelState {
     /// Send is already started
     pub send_started: bool,
@@ -21,10 +22,33 @@ pub struct LevelState {
     pub send_signature_size: usize,
     /// The number of peers that a send is expected to go to
     pub send_peers_count: usize,
+    /// The current weights of the peers for the weighted peer selection
+    pub send_peers_weights: Vec<i128>,
+}
+
+/// A state transition of a level, see [`Level::set_observer`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LevelEvent {
+    /// Sending updates on the level with the given ID was started.
+    Started(usize),
+    /// The contributions of all peers on the level with the given ID were received.
+    Completed(usize),
+}
+
+/// Callback that is invoked on state transitions of a level.
+pub type LevelObserver = Box<dyn Fn(LevelEvent) + Send + Sync>;
+
+/// Returns the total number of peers on all given levels.
+pub fn total_peers(levels: &[Level]) -> usize {
+    levels.iter().map(Level::num_peers).sum()
+}
+
+/// Returns the number of peers on the largest of the given levels, or 0 if there are no levels.
+pub fn max_level_size(levels: &[Level]) -> usize {
+    levels.iter().map(Level::num_peers).max().unwrap_or(0)
 }
 
 /// Struct that defines an Aggregation Level
-#[derive(Debug)]
 pub struct Level {
     /// The ID of this level
     pub id: usize,
@@ -35,6 +59,23 @@ pub struct Level {
     pub send_expected_full_size: usize,
     /// The state of this level
     pub state: RwLock<LevelState>,
+    /// The position of the peer the selection starts at
+    first_peer_pos: usize,
+    /// The callback that is invoked when this level is started or completed
+    observer: Option<LevelObserver>,
+}
+
+impl fmt::Debug for Level {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Level")
+            .field("id", &self.id)
+            .field("peer_ids", &self.peer_ids)
+            .field("send_expected_full_size", &self.send_expected_full_size)
+            .field("state", &self.state)
+            .field("first_peer_pos", &self.first_peer_pos)
+            .field("observer", &self.observer.is_some())
+            .finish()
+    }
 }
 
 impl Level {
@@ -45,13 +86,22 @@ impl Level {
             id,
             peer_ids,
             send_expected_full_size,
-            state: RwLock::new(LevelState {
-                send_started: false,
-                receive_completed: false,
-                send_peers_pos: 0,
-                send_signature_size: 0,
-                send_peers_count: 0,
-            }),
+            state: RwLock::new(LevelState::default()),
+            first_peer_pos: 0,
+            observer: None,
+        }
+    }
+
+    /// Sets the callback that is invoked when this level is started or completed, replacing any
+    /// previously set one. The callback is invoked after the state lock was released, so it may
+    /// access the level.
+    pub fn set_observer(&mut self, observer: LevelObserver) {
+        self.observer = Some(observer);
+    }
+
+    fn notify(&self, event: LevelEvent) {
+        if let Some(observer) = &self.observer {
+            observer(event);
         }
     }
 
@@ -65,19 +115,58 @@ impl Level {
         self.peer_ids.len() == 0
     }
 
-    /// Creates a set of levels given a partitioner
-    pub fn create_levels<P: Partitioner, TId: std::fmt::Debug>(
+    /// Creates a set of levels given a partitioner.
+    ///
+    /// Panics if the partitioner returns invalid ranges, see [`Level::try_create_levels`].
+    pub fn create_levels<P: Partitioner, TId: fmt::Debug>(
         partitioner: Arc<P>,
         id: TId,
+        seed: u64,
     ) -> Vec<Level> {
+        Self::try_create_levels(partitioner, id, seed).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Creates a set of levels given a partitioner.
+    ///
+    /// The peers of each level are shuffled and the peer selection of each level starts at a
+    /// random position. Both are derived from the given seed, such that the levels are
+    /// reproducible, while different seeds spread the order in which peers are contacted.
+    /// The set of peers on each level only depends on the partitioner.
+    ///
+    /// The range of each level must be consecutive to the range covered by all previous levels,
+    /// such that the levels up to any level cover a consecutive range of identities. Fails if the
+    /// partitioner returns ranges violating this or returns an error other than an empty level.
+    pub fn try_create_levels<P: Partitioner, TId: fmt::Debug>(
+        partitioner: Arc<P>,
+        id: TId,
+        seed: u64,
+    ) -> Result<Vec<Level>, PartitioningError> {
         let mut levels: Vec<Level> = Vec::new();
         let mut first_active = false;
         let mut send_expected_full_size: usize = 1;
-        let mut rng = thread_rng();
+        let mut covered: Option<RangeInclusive<usize>> = None;
+        let mut rng = StdRng::seed_from_u64(seed);
 
         for i in 0..partitioner.levels() {
             match partitioner.range(i) {
                 Ok(ids) => {
+                    covered = Some(match covered {
+                        None => ids.clone(),
+                        Some(previous) if ids.end() + 1 == *previous.start() => {
+                            *ids.start()..=*previous.end()
+                        }
+                        Some(previous) if previous.end() + 1 == *ids.start() => {
+                            *previous.start()..=*ids.end()
+                        }
+                        Some(previous) => {
+                            return Err(PartitioningError::NonConsecutiveRanges {
+                                level: i,
+                                previous,
+                                range: ids,
+                            })
+                        }
+                    });
+
                     let mut ids = ids.collect::<Vec<usize>>();
                     ids.shuffle(&mut rng);
 
@@ -88,7 +177,9 @@ impl Level {
                         peers_on_level = ?ids,
                         "Peers on Level",
                     );
-                    let level = Level::new(i, ids, send_expected_full_size);
+                    let mut level = Level::new(i, ids, send_expected_full_size);
+                    level.first_peer_pos = rng.gen_range(0..size.max(1));
+                    level.state.write().send_peers_pos = level.first_peer_pos;
 
                     if !first_active {
                         first_active = true;
@@ -102,11 +193,72 @@ impl Level {
                     let level = Level::new(i, vec![], send_expected_full_size);
                     levels.push(level);
                 }
-                Err(e) => panic!("{}", e),
+                Err(e) => return Err(e),
             }
         }
 
-        levels
+        debug_assert_eq!(
+            total_peers(&levels),
+            partitioner.size(),
+            "Levels don't cover all identities of the partitioner",
+        );
+
+        Ok(levels)
+    }
+
+    /// Creates a set of levels given a partitioner, like [`Level::try_create_levels`], and orders
+    /// the peers within each level by the given latency estimate (see [`Level::order_by_latency`]).
+    pub fn try_create_levels_by_latency<P: Partitioner, TId: fmt::Debug, F>(
+        partitioner: Arc<P>,
+        id: TId,
+        seed: u64,
+        latency: F,
+    ) -> Result<Vec<Level>, PartitioningError>
+    where
+        F: Fn(usize) -> Option<Duration>,
+    {
+        let mut levels = Self::try_create_levels(partitioner, id, seed)?;
+        for level in levels.iter_mut() {
+            level.order_by_latency(&latency);
+        }
+        Ok(levels)
+    }
+
+    /// Orders the peers of this level such that peers with a lower latency estimate are selected
+    /// first by [`Level::select_next_peers`]. Peers without an estimate are moved to the end.
+    ///
+    /// This only permutes the peers of the level, so the level still contains the same peers and
+    /// the selection still cycles through all of them. The ordering is stable, i.e. peers with the
+    /// same (or no) estimate keep their current, randomly shuffled order. The selection starts
+    /// over at the fastest peer.
+    pub fn order_by_latency<F: Fn(usize) -> Option<Duration>>(&mut self, latency: F) {
+        self.peer_ids
+            .sort_by_key(|&peer_id| match latency(peer_id) {
+                Some(latency) => (false, latency),
+                None => (true, Duration::ZERO),
+            });
+        self.first_peer_pos = 0;
+        self.state.get_mut().send_peers_pos = 0;
+    }
+
+    /// Resets the state of this level to its initial state, i.e. not started, not received
+    /// complete and with the peer selection starting over at its first position.
+    pub fn reset(&self) {
+        *self.state.write() = LevelState {
+            send_peers_pos: self.first_peer_pos,
+            ..Default::default()
+        };
+    }
+
+    /// Resets the state of all given levels to the state they had in [`Level::create_levels`],
+    /// i.e. only the first non-empty level is started.
+    pub fn reset_all(levels: &[Level]) {
+        for level in levels {
+            level.reset();
+        }
+        if let Some(level) = levels.iter().find(|level| !level.is_empty()) {
+            level.start();
+        }
     }
 
     /// Returns whether this level is active
@@ -115,6 +267,15 @@ impl Level {
         state.send_started && state.send_peers_count < self.peer_ids.len()
     }
 
+    /// Returns the number of peers of this level that are not yet contained in the given set of
+    /// contributors. A level is complete once there are no remaining contributors.
+    pub fn remaining_contributors(&self, current: &BitSet) -> usize {
+        self.peer_ids
+            .iter()
+            .filter(|&&peer_id| !current.contains(peer_id))
+            .count()
+    }
+
     /// Returns whether this level is received complete
     pub fn receive_complete(&self) -> bool {
         let state = self.state.read();
@@ -143,6 +304,55 @@ impl Level {
         }
     }
 
+    /// Selects the set of next peers to send an update to for this level given a count of them,
+    /// preferring peers with a higher weight. `weights` must contain a weight for each peer in
+    /// `peer_ids`, otherwise the peers are selected as in [`Level::select_next_peers`].
+    ///
+    /// The selection follows a smooth weighted round-robin: Over time, each peer is selected
+    /// proportionally to its weight. Peers are selected at most once per call and a weight of
+    /// zero is treated as one, such that every peer is eventually selected.
+    pub fn select_next_peers_weighted(&self, count: usize, weights: &[u64]) -> Vec<usize> {
+        if weights.len() != self.peer_ids.len() {
+            return self.select_next_peers(count);
+        }
+        if self.id == 0 || self.is_empty() {
+            return vec![];
+        }
+
+        let size = min(count, self.peer_ids.len());
+        let weights: Vec<i128> = weights.iter().map(|&w| w.max(1) as i128).collect();
+        let total_weight: i128 = weights.iter().sum();
+        let mut selected: Vec<usize> = Vec::new();
+        let mut selected_pos: Vec<usize> = Vec::new();
+
+        let mut state = self.state.write();
+        if state.send_peers_weights.len() != weights.len() {
+            state.send_peers_weights = vec![0; weights.len()];
+        }
+        for _ in 0..size {
+            let mut best_pos: Option<usize> = None;
+            for (pos, weight) in weights.iter().enumerate() {
+                state.send_peers_weights[pos] += weight;
+                if selected_pos.contains(&pos) {
+                    continue;
+                }
+                if best_pos.map_or(true, |best| {
+                    state.send_peers_weights[pos] > state.send_peers_weights[best]
+                }) {
+                    best_pos = Some(pos);
+                }
+            }
+
+            // NOTE: Unwrap is safe, since there are more peers than selected ones
+            let best_pos = best_pos.unwrap();
+            state.send_peers_weights[best_pos] -= total_weight;
+            selected_pos.push(best_pos);
+            selected.push(self.peer_ids[best_pos]);
+        }
+
+        selected
+    }
+
     /// Updates the signature to send
     pub fn update_signature_to_send<C: AggregatableContribution>(&self, signature: &C) -> bool {
         let mut state = self.state.write();
@@ -155,7 +365,13 @@ impl Level {
         state.send_peers_count = 0;
 
         if state.send_signature_size == self.send_expected_full_size {
+            let started = !state.send_started;
             state.send_started = true;
+            drop(state);
+
+            if started {
+                self.notify(LevelEvent::Started(self.id));
+            }
             return true;
         }
 
@@ -168,23 +384,42 @@ impl Level {
     pub fn start(&self) -> bool {
         let mut state = self.state.write();
         if state.send_started {
-            false
-        } else {
-            state.send_started = true;
-            true
+            return false;
         }
+        state.send_started = true;
+        drop(state);
+
+        self.notify(LevelEvent::Started(self.id));
+        true
+    }
+
+    /// Marks the level as received complete if not already completed.
+    ///
+    /// If the level was completed before returns false, otherwise returns true.
+    pub fn complete(&self) -> bool {
+        let mut state = self.state.write();
+        if state.receive_completed {
+            return false;
+        }
+        state.receive_completed = true;
+        drop(state);
+
+        self.notify(LevelEvent::Completed(self.id));
+        true
     }
 }
 
 #[cfg(test)]
 mod test {
-    use nimiq_collections::bitset::BitSet;
+    use std::sync::Weak;
+
     use nimiq_test_log::test;
-    use rand::Rng;
+    use parking_lot::Mutex;
+    use rand::{thread_rng, Rng};
     use serde::{Deserialize, Serialize};
 
     use super::*;
-    use crate::contribution::ContributionError;
+    use crate::{contribution::ContributionError, partitioner::BinomialPartitioner};
 
     /// Dump Aggregate adding numbers.
     #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -202,6 +437,110 @@ mod test {
         }
     }
 
+    /// Partitioner returning the given ranges.
+    struct RangePartitioner {
+        ranges: Vec<RangeInclusive<usize>>,
+    }
+
+    impl Partitioner for RangePartitioner {
+        fn levels(&self) -> usize {
+            self.ranges.len()
+        }
+
+        fn size(&self) -> usize {
+            self.ranges.iter().map(|range| range.clone().count()).sum()
+        }
+
+        fn level_size(&self, level: usize) -> usize {
+            self.ranges[level].clone().count()
+        }
+
+        fn range(&self, level: usize) -> Result<RangeInclusive<usize>, PartitioningError> {
+            Ok(self.ranges[level].clone())
+        }
+
+        fn combine<C: AggregatableContribution>(
+            &self,
+            _signatures: Vec<&C>,
+            _level: usize,
+        ) -> Option<C> {
+            None
+        }
+    }
+
+    #[test]
+    fn it_rejects_non_consecutive_ranges() {
+        for num_ids in 1..20 {
+            for node_id in 0..num_ids {
+                let partitioner = Arc::new(BinomialPartitioner::new(node_id, num_ids));
+                assert!(Level::try_create_levels(partitioner, node_id, 0).is_ok());
+            }
+        }
+
+        let partitioner = Arc::new(RangePartitioner {
+            ranges: vec![2..=2, 3..=3, 0..=1, 6..=7],
+        });
+        assert_eq!(
+            Level::try_create_levels(partitioner, 2, 0).err(),
+            Some(PartitioningError::NonConsecutiveRanges {
+                level: 3,
+                previous: 0..=3,
+                range: 6..=7,
+            })
+        );
+    }
+
+    #[test]
+    fn it_computes_level_sizes() {
+        assert_eq!(total_peers(&[]), 0);
+        assert_eq!(max_level_size(&[]), 0);
+
+        let levels = vec![
+            Level::new(0, vec![2], 1),
+            Level::new(1, vec![3], 2),
+            Level::new(2, vec![], 3),
+            Level::new(3, vec![4, 5, 6, 7], 3),
+        ];
+        assert_eq!(total_peers(&levels), 6);
+        assert_eq!(max_level_size(&levels), 4);
+
+        // The last level contains half of the identities.
+        let partitioner = Arc::new(BinomialPartitioner::new(5, 16));
+        let levels = Level::create_levels(partitioner, 5, 0);
+        assert_eq!(total_peers(&levels), 16);
+        assert_eq!(max_level_size(&levels), 8);
+    }
+
+    #[test]
+    fn it_notifies_the_observer() {
+        let events = Arc::new(Mutex::new(vec![]));
+        let mut level = Level::new(2, vec![2, 3], 2);
+        let observed = Arc::clone(&events);
+        level.set_observer(Box::new(move |event| observed.lock().push(event)));
+
+        assert!(level.start());
+        assert!(!level.start());
+        assert!(level.complete());
+        assert!(!level.complete());
+        assert_eq!(
+            *events.lock(),
+            vec![LevelEvent::Started(2), LevelEvent::Completed(2)]
+        );
+
+        // The state lock is released before the callback is invoked.
+        let level = Arc::new_cyclic(|weak: &Weak<Level>| {
+            let mut level = Level::new(1, vec![1], 2);
+            let weak = weak.clone();
+            level.set_observer(Box::new(move |_| {
+                let level = weak.upgrade().unwrap();
+                assert!(level.state.try_write().is_some());
+            }));
+            level
+        });
+        assert!(level.start());
+        assert!(level.complete());
+    }
+
     #[test]
     fn it_can_handle_empty_level() {
         let mut rng = thread_rng();
@@ -257,6 +596,124 @@ mod test {
         }
     }
 
+    #[test]
+    fn it_orders_peers_by_latency() {
+        let mut level = Level::new(1, vec![4, 5, 6, 7], 2);
+        level.order_by_latency(|peer_id| match peer_id {
+            4 => Some(Duration::from_millis(30)),
+            6 => Some(Duration::from_millis(10)),
+            7 => Some(Duration::from_millis(20)),
+            _ => None,
+        });
+        assert_eq!(level.peer_ids, vec![6, 7, 4, 5]);
+
+        // The selection still cycles through all peers, starting with the fastest ones.
+        assert_eq!(level.select_next_peers(3), vec![6, 7, 4]);
+        assert_eq!(level.select_next_peers(3), vec![5, 6, 7]);
+    }
+
+    #[test]
+    fn it_selects_next_peers_weighted() {
+        let level = Level::new(1, vec![4, 5, 6], 2);
+        let weights = [1, 0, 4];
+
+        // Within a cycle of the total weight, each peer is selected according to its weight.
+        let mut counts = [0; 3];
+        for _ in 0..6 {
+            let next_peers = level.select_next_peers_weighted(1, &weights);
+            assert_eq!(next_peers.len(), 1);
+            counts[next_peers[0] - 4] += 1;
+        }
+        assert_eq!(counts, [1, 1, 4]);
+
+        // Peers are selected at most once per call.
+        for _ in 0..6 {
+            let mut next_peers = level.select_next_peers_weighted(2, &weights);
+            next_peers.dedup();
+            assert_eq!(next_peers.len(), 2);
+        }
+
+        // Without matching weights, peers are selected uniformly.
+        let level = Level::new(1, vec![4, 5, 6], 2);
+        assert_eq!(level.select_next_peers_weighted(2, &[]), vec![4, 5]);
+    }
+
+    #[test]
+    fn it_counts_remaining_contributors() {
+        let level = Level::new(3, vec![4, 5, 6, 7], 4);
+        assert_eq!(level.remaining_contributors(&BitSet::new()), 4);
+
+        // Contributors from other levels don't count.
+        let mut contributors = BitSet::new();
+        contributors.insert(0);
+        contributors.insert(5);
+        assert_eq!(level.remaining_contributors(&contributors), 3);
+
+        contributors.insert(4);
+        contributors.insert(7);
+        assert_eq!(level.remaining_contributors(&contributors), 1);
+
+        contributors.insert(6);
+        assert_eq!(level.remaining_contributors(&contributors), 0);
+    }
+
+    #[test]
+    fn it_resets_levels() {
+        let partitioner = Arc::new(BinomialPartitioner::new(0, 8));
+        let levels = Level::create_levels(partitioner, 0, 0);
+
+        for level in &levels {
+            level.start();
+            level.select_next_peers(1);
+            level.state.write().receive_completed = true;
+        }
+
+        Level::reset_all(&levels);
+
+        for level in &levels {
+            let state = level.state.read();
+            assert_eq!(state.send_started, level.id == 0);
+            assert!(!state.receive_completed);
+            assert_eq!(state.send_peers_pos, level.first_peer_pos);
+            assert_eq!(state.send_signature_size, 0);
+            assert_eq!(state.send_peers_count, 0);
+        }
+    }
+
+    #[test]
+    fn it_creates_reproducible_levels() {
+        let create_levels = |seed| {
+            let partitioner = Arc::new(BinomialPartitioner::new(3, 64));
+            Level::create_levels(partitioner, 3, seed)
+        };
+        let levels = create_levels(1);
+        let next_peers = |levels: &[Level]| {
+            levels
+                .iter()
+                .map(|level| level.select_next_peers(2))
+                .collect::<Vec<_>>()
+        };
+        let first = next_peers(&levels);
+
+        // The same seed results in the same peer selection.
+        assert_eq!(next_peers(&create_levels(1)), first);
+
+        // Another seed results in the same levels, but a different peer selection.
+        let other_levels = create_levels(2);
+        for (level, other_level) in levels.iter().zip(&other_levels) {
+            let mut peer_ids = level.peer_ids.clone();
+            let mut other_peer_ids = other_level.peer_ids.clone();
+            peer_ids.sort_unstable();
+            other_peer_ids.sort_unstable();
+            assert_eq!(peer_ids, other_peer_ids);
+        }
+        assert_ne!(next_peers(&other_levels), first);
+
+        // The selection starts over at the same position after a reset.
+        Level::reset_all(&levels);
+        assert_eq!(next_peers(&levels), first);
+    }
+
     #[test]
     fn it_updates_signature_to_send() {
         let mut rng = thread_rng();
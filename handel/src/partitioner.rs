@@ -11,6 +11,12 @@ pub enum PartitioningError {
     InvalidLevel { level: usize },
     #[error("Empty level: {level}")]
     EmptyLevel { level: usize },
+    #[error("Range {range:?} of level {level} is not consecutive to the range {previous:?} covered by the previous levels")]
+    NonConsecutiveRanges {
+        level: usize,
+        previous: RangeInclusive<usize>,
+        range: RangeInclusive<usize>,
+    },
 }
 
 pub trait Partitioner: Send + Sync {
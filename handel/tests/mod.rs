@@ -10,7 +10,7 @@ use nimiq_bls::PublicKey;
 use nimiq_collections::bitset::BitSet;
 use nimiq_handel::{
     aggregation::Aggregation,
-    config::Config,
+    config::{Config, FanOutPolicy},
     contribution::{AggregatableContribution, ContributionError},
     evaluator::WeightedVote,
     identity::{Identity, IdentityRegistry, WeightRegistry},
@@ -210,7 +210,7 @@ async fn it_can_aggregate() {
         update_count: 1,
         update_interval: Duration::from_millis(500),
         timeout: Duration::from_millis(500),
-        peer_count: 1,
+        fan_out: FanOutPolicy::Constant(1),
     };
 
     let stopped = Arc::new(RwLock::new(false));
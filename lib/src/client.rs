@@ -1,4 +1,4 @@
-use std::{fs, num::NonZeroU8, sync::Arc};
+use std::{num::NonZeroU8, sync::Arc};
 
 use nimiq_block::Block;
 #[cfg(feature = "full-consensus")]
@@ -7,6 +7,8 @@ use nimiq_blockchain_interface::AbstractBlockchain;
 use nimiq_blockchain_proxy::BlockchainProxy;
 use nimiq_bls::cache::PublicKeyCache;
 #[cfg(feature = "full-consensus")]
+use nimiq_consensus::sync::history::DiversityPolicy;
+#[cfg(feature = "full-consensus")]
 use nimiq_consensus::Error::BlockchainError;
 use nimiq_consensus::{
     sync::syncer_proxy::SyncerProxy, Consensus as AbstractConsensus,
@@ -55,7 +57,6 @@ use parking_lot::{Mutex, RwLock};
 use rand::SeedableRng;
 #[cfg(feature = "zkp-prover")]
 use rand_chacha::ChaCha20Rng;
-use rustls_pemfile::Item;
 
 use crate::{
     config::config::{ClientConfig, SyncMode},
@@ -277,44 +278,16 @@ impl ClientInner {
             .map(|seed| seed.address)
             .collect();
 
-        let tls_config = if let Some(tls_config) = config.network.tls {
-            // Check that the provided private key has the expected format and convert the PEM file to DER format.
-            let private_key = fs::read(tls_config.private_key).and_then(|private_key_bytes| {
-                match rustls_pemfile::read_one(&mut &*private_key_bytes)? {
-                    Some(Item::Sec1Key(key)) => Ok(key.secret_sec1_der().to_vec()),
-                    Some(Item::Pkcs8Key(key)) => Ok(key.secret_pkcs8_der().to_vec()),
-                    Some(Item::Pkcs1Key(key)) => Ok(key.secret_pkcs1_der().to_vec()),
-                    _ => Err(std::io::Error::new(
-                        std::io::ErrorKind::InvalidData,
-                        "Invalid TLS private key",
-                    )),
-                }
-            })?;
-            // Check that the provided certificates have the expected format and convert the PEM file to a list of
-            // certificates in DER format.
-            // We could have several certificates in the same file, read them all and build the array of certificates
-            // that the network requires.
-            let certificates = fs::read(tls_config.certificates).and_then(|certificate_bytes| {
-                rustls_pemfile::read_all(&mut &*certificate_bytes)
-                    .map(|item| match item {
-                        Ok(Item::X509Certificate(cert)) => Ok(cert.to_vec()),
-                        _ => Err(std::io::Error::new(
-                            std::io::ErrorKind::InvalidData,
-                            "Invalid TLS certificate(s)",
-                        )),
-                    })
-                    .collect()
-            })?;
-            Some(NetworkTls {
-                private_key,
-                certificates,
+        let tls_config = config
+            .network
+            .tls
+            .map(|tls_config| {
+                NetworkTls::from_pem(&tls_config.certificates, &tls_config.private_key)
             })
-        } else {
-            None
-        };
+            .transpose()?;
 
         // Setup libp2p network
-        let network_config = NetworkConfig::new(
+        let mut network_config = NetworkConfig::new(
             identity_keypair,
             peer_contact,
             seeds,
@@ -331,6 +304,14 @@ impl ClientInner {
                 .dht_quorum
                 .unwrap_or(NonZeroU8::new(3).unwrap()),
         );
+        network_config.identify_agent_version = config.network.user_agent.to_string();
+        if let Err(errors) = network_config.validate() {
+            let errors: Vec<String> = errors.iter().map(ToString::to_string).collect();
+            return Err(Error::config_error(format!(
+                "Invalid network configuration: {}",
+                errors.join(", ")
+            )));
+        }
 
         log::debug!(
             addresses = ?config.network.listen_addresses,
@@ -382,6 +363,8 @@ impl ClientInner {
             SyncMode::History => {
                 blockchain_config.keep_history = true;
                 blockchain_config.index_history = config.consensus.index_history;
+                blockchain_config.history_read_replica =
+                    config.consensus.history_read_replica.clone();
                 let blockchain = match Blockchain::new(
                     environment.clone(),
                     blockchain_config,
@@ -419,6 +402,11 @@ impl ClientInner {
                     Arc::clone(&network),
                     bls_cache,
                     network_events,
+                    config.consensus.history_chunk_concurrency,
+                    config
+                        .consensus
+                        .history_sync_min_subnets
+                        .map_or(DiversityPolicy::Disabled, DiversityPolicy::MinSubnets),
                 )
                 .await;
                 (blockchain_proxy, syncer, zkp_component)
@@ -523,7 +511,10 @@ impl ClientInner {
                     let voting_key = config.storage.voting_keypair()?;
 
                     // Load fee key (before we give away ownership of the storage config)
-                    let fee_key = config.storage.fee_keypair()?;
+                    let fee_key = match validator_config.fee_key {
+                        Some(fee_key) => fee_key,
+                        None => config.storage.fee_keypair()?,
+                    };
 
                     let validator_network =
                         Arc::new(ValidatorNetworkImpl::new(Arc::clone(&network)));
@@ -573,7 +564,7 @@ impl ClientInner {
         }
 
         // Start network.
-        network.listen_on(config.network.listen_addresses).await;
+        network.listen_on(config.network.listen_addresses).await?;
         network.start_connecting().await;
 
         Ok(Client {
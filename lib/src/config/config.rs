@@ -13,14 +13,14 @@ use derive_builder::Builder;
 #[cfg(feature = "validator")]
 use nimiq_bls::{KeyPair as BlsKeyPair, SecretKey as BlsSecretKey};
 #[cfg(feature = "database-storage")]
-use nimiq_database::mdbx::MdbxDatabase;
+use nimiq_database::mdbx::{MdbxDatabase, SyncMode as DatabaseSyncMode};
 use nimiq_hash::{Blake2bHash, Hash};
 #[cfg(feature = "validator")]
 use nimiq_keys::{Address, KeyPair, PrivateKey};
 #[cfg(feature = "nimiq-mempool")]
 use nimiq_mempool::{config::MempoolConfig, filter::MempoolRules};
 use nimiq_network_interface::Multiaddr;
-use nimiq_network_libp2p::{Keypair as IdentityKeypair, Libp2pKeyPair};
+use nimiq_network_libp2p::{Keypair as IdentityKeypair, Libp2pKeyPair, TlsConfig as NetworkTls};
 use nimiq_primitives::{networks::NetworkId, policy::Policy};
 use nimiq_serde::Deserialize;
 #[cfg(feature = "validator")]
@@ -31,7 +31,11 @@ use subtle::ConstantTimeEq;
 
 #[cfg(feature = "database-storage")]
 use crate::config::config_file::DatabaseSettings;
-#[cfg(any(feature = "rpc-server", feature = "metrics-server"))]
+#[cfg(any(
+    feature = "rpc-server",
+    feature = "metrics-server",
+    feature = "validator"
+))]
 use crate::config::consts;
 #[cfg(feature = "metrics-server")]
 use crate::config::consts::default_bind;
@@ -58,6 +62,10 @@ pub enum SyncMode {
     /// Full nodes: They use LightMacroSync + StateLiveSync
     Full,
     /// Light nodes: They use LightMacroSync + BlockLiveSync
+    ///
+    /// Unlike full nodes, light nodes never download the accounts state. They verify the chain
+    /// using zero-knowledge proofs and only keep recent blocks, so their storage stays small
+    /// permanently. They use a separate database (`<network>-light-consensus`).
     Light,
 }
 
@@ -92,6 +100,19 @@ pub struct ConsensusConfig {
     #[builder(default = "true")]
     /// History indices enabled. Only effective for history nodes (default: `true`)
     pub index_history: bool,
+    #[builder(default = "12")]
+    /// Maximum number of history chunks requested concurrently during the history sync
+    pub history_chunk_concurrency: usize,
+    #[builder(default)]
+    /// Minimum number of distinct subnets the peers of a history sync target should be in.
+    /// This only affects the choice of the sync target, not which of its peers the history
+    /// is requested from.
+    /// Only effective for history nodes (default: `None`, i.e. not considered)
+    pub history_sync_min_subnets: Option<usize>,
+    #[builder(default)]
+    /// Path of a read-only replica of the database to serve history chunks from.
+    /// Only effective for history nodes
+    pub history_read_replica: Option<PathBuf>,
 }
 
 impl Default for ConsensusConfig {
@@ -102,6 +123,9 @@ impl Default for ConsensusConfig {
             max_epochs_stored: Policy::MIN_EPOCHS_STORED,
             full_sync_threshold: 10800,
             index_history: true,
+            history_chunk_concurrency: 12,
+            history_sync_min_subnets: None,
+            history_read_replica: None,
         }
     }
 }
@@ -197,6 +221,10 @@ pub struct FileStorageConfig {
     #[cfg(feature = "validator")]
     pub voting_key: Option<Sensitive<String>>,
 
+    /// If set, the voting key file is encrypted with this password.
+    #[cfg(feature = "validator")]
+    pub voting_key_password: Option<Sensitive<String>>,
+
     /// Path to signing key.
     #[cfg(feature = "validator")]
     pub signing_key_path: Option<PathBuf>,
@@ -228,6 +256,8 @@ impl FileStorageConfig {
             #[cfg(feature = "validator")]
             voting_key: None,
             #[cfg(feature = "validator")]
+            voting_key_password: None,
+            #[cfg(feature = "validator")]
             fee_key_path: Some(path.join("fee_key.dat")),
             #[cfg(feature = "validator")]
             fee_key: None,
@@ -275,6 +305,11 @@ pub struct DatabaseConfig {
     /// Recommended: 600
     #[builder(default = "600")]
     max_readers: u32,
+
+    /// How durably writes are persisted. Less durable modes increase the write throughput at the
+    /// risk of losing data on a system crash. Default: durable
+    #[builder(default)]
+    sync_mode: DatabaseSyncMode,
 }
 #[cfg(feature = "database-storage")]
 impl Default for DatabaseConfig {
@@ -284,6 +319,7 @@ impl Default for DatabaseConfig {
             size: 1024 * 1024 * 1024 * 1024,
             max_dbs: 20,
             max_readers: 600,
+            sync_mode: DatabaseSyncMode::Durable,
         }
     }
 }
@@ -297,6 +333,9 @@ impl From<Option<DatabaseSettings>> for DatabaseConfig {
                 size: db_settings.size.unwrap_or(default.size),
                 max_dbs: db_settings.max_dbs.unwrap_or(default.max_dbs),
                 max_readers: db_settings.max_readers.unwrap_or(default.max_readers),
+                sync_mode: db_settings
+                    .sync_mode
+                    .map_or(default.sync_mode, DatabaseSyncMode::from),
             }
         } else {
             default
@@ -314,10 +353,17 @@ impl From<Option<DatabaseSettings>> for DatabaseConfig {
 pub enum StorageConfig {
     /// This will store the database in a volatile storage. After the client shuts
     /// down all data will be lost.
-    /// This can be used for browser based environments
+    /// This can be used for browser based environments. The web client runs a light node, which
+    /// keeps its state in memory and doesn't open a database, so it doesn't need a persistent
+    /// storage backend.
     ///
     Volatile,
 
+    /// Like `Volatile`, but with the given maximum database size in bytes instead of the size
+    /// configured in the `DatabaseConfig`. Writes fail once the database is full.
+    ///
+    VolatileSized(usize),
+
     /// This will store the database and key files at specific paths. This is not available when
     /// compiled to WebAssembly.
     ///
@@ -353,11 +399,18 @@ impl StorageConfig {
             max_tables: Some(db_config.max_dbs as u64),
             size: Some(-1..db_config.size as isize),
             max_readers: Some(db_config.max_readers),
+            sync_mode: db_config.sync_mode,
             ..Default::default()
         };
 
         Ok(match self {
             StorageConfig::Volatile => MdbxDatabase::new_volatile(config)?,
+            StorageConfig::VolatileSized(size) => {
+                MdbxDatabase::new_volatile(mdbx::DatabaseConfig {
+                    size: Some(-1..*size as isize),
+                    ..config
+                })?
+            }
             StorageConfig::Filesystem(file_storage) => {
                 let db_path = file_storage.database_parent.join(db_name);
                 let db_path = db_path
@@ -377,7 +430,9 @@ impl StorageConfig {
     #[cfg(feature = "validator")]
     pub(crate) fn voting_keypair(&self) -> Result<BlsKeyPair, Error> {
         Ok(match self {
-            StorageConfig::Volatile => BlsKeyPair::generate_default_csprng(),
+            StorageConfig::Volatile | StorageConfig::VolatileSized(_) => {
+                BlsKeyPair::generate_default_csprng()
+            }
             StorageConfig::Filesystem(file_storage) => {
                 let key_path = file_storage
                     .voting_key_path
@@ -393,16 +448,24 @@ impl StorageConfig {
                     })?
                     .to_string();
 
-                FileStore::new(key_path).load_or_store(|| {
-                    if let Some(key) = file_storage.voting_key.as_ref() {
-                        // TODO: handle errors
-                        let secret_key =
-                            BlsSecretKey::deserialize_from_vec(&hex::decode(key).unwrap()).unwrap();
-                        secret_key.into()
-                    } else {
-                        BlsKeyPair::generate_default_csprng()
-                    }
-                })?
+                let file_store = FileStore::new(key_path);
+                let mut voting_key = file_storage
+                    .voting_key
+                    .as_ref()
+                    .map(|key| decode_key::<BlsSecretKey>(key, "validator key"))
+                    .transpose()?;
+                let initial_keypair = || {
+                    voting_key
+                        .take()
+                        .map(BlsKeyPair::from)
+                        .unwrap_or_else(BlsKeyPair::generate_default_csprng)
+                };
+
+                if let Some(password) = file_storage.voting_key_password.as_ref() {
+                    file_store.load_or_store_encrypted(password.as_bytes(), initial_keypair)?
+                } else {
+                    file_store.load_or_store(initial_keypair)?
+                }
             }
         })
     }
@@ -410,7 +473,9 @@ impl StorageConfig {
     #[cfg(feature = "validator")]
     pub(crate) fn fee_keypair(&self) -> Result<KeyPair, Error> {
         Ok(match self {
-            StorageConfig::Volatile => KeyPair::generate_default_csprng(),
+            StorageConfig::Volatile | StorageConfig::VolatileSized(_) => {
+                KeyPair::generate_default_csprng()
+            }
             StorageConfig::Filesystem(file_storage) => {
                 let key_path = file_storage
                     .fee_key_path
@@ -426,15 +491,16 @@ impl StorageConfig {
                     })?
                     .to_string();
 
+                let mut fee_key = file_storage
+                    .fee_key
+                    .as_ref()
+                    .map(|key| decode_key::<PrivateKey>(key, "fee key"))
+                    .transpose()?;
                 FileStore::new(key_path).load_or_store(|| {
-                    if let Some(key) = file_storage.fee_key.as_ref() {
-                        // TODO: handle errors
-                        KeyPair::from(
-                            PrivateKey::deserialize_from_vec(&hex::decode(key).unwrap()).unwrap(),
-                        )
-                    } else {
-                        KeyPair::generate_default_csprng()
-                    }
+                    fee_key
+                        .take()
+                        .map(KeyPair::from)
+                        .unwrap_or_else(KeyPair::generate_default_csprng)
                 })?
             }
         })
@@ -443,7 +509,9 @@ impl StorageConfig {
     #[cfg(feature = "validator")]
     pub(crate) fn signing_keypair(&self) -> Result<KeyPair, Error> {
         Ok(match self {
-            StorageConfig::Volatile => KeyPair::generate_default_csprng(),
+            StorageConfig::Volatile | StorageConfig::VolatileSized(_) => {
+                KeyPair::generate_default_csprng()
+            }
             StorageConfig::Filesystem(file_storage) => {
                 let key_path = file_storage
                     .signing_key_path
@@ -459,15 +527,16 @@ impl StorageConfig {
                     })?
                     .to_string();
 
+                let mut signing_key = file_storage
+                    .signing_key
+                    .as_ref()
+                    .map(|key| decode_key::<PrivateKey>(key, "warm key"))
+                    .transpose()?;
                 FileStore::new(key_path).load_or_store(|| {
-                    if let Some(key) = file_storage.signing_key.as_ref() {
-                        // TODO: handle errors
-                        KeyPair::from(
-                            PrivateKey::deserialize_from_vec(&hex::decode(key).unwrap()).unwrap(),
-                        )
-                    } else {
-                        KeyPair::generate_default_csprng()
-                    }
+                    signing_key
+                        .take()
+                        .map(KeyPair::from)
+                        .unwrap_or_else(KeyPair::generate_default_csprng)
                 })?
             }
         })
@@ -475,23 +544,36 @@ impl StorageConfig {
 
     pub(crate) fn identity_keypair(&self) -> Result<IdentityKeypair, Error> {
         match self {
-            StorageConfig::Volatile => Ok(IdentityKeypair::generate_ed25519()),
+            StorageConfig::Volatile | StorageConfig::VolatileSized(_) => {
+                Ok(IdentityKeypair::generate_ed25519())
+            }
             StorageConfig::Filesystem(file_storage) => {
-                Ok(FileStore::new(&file_storage.peer_key_path)
-                    .load_or_store(|| {
-                        if let Some(key) = file_storage.peer_key.as_ref() {
-                            // TODO: handle errors
-                            Libp2pKeyPair::deserialize_from_vec(&hex::decode(key).unwrap()).unwrap()
-                        } else {
-                            Libp2pKeyPair(IdentityKeypair::generate_ed25519())
-                        }
-                    })?
-                    .0)
+                // Peer keys stored before the key type discriminant was introduced contain only
+                // the raw Ed25519 keypair. They are still read, but not converted, such that older
+                // versions can read them as well.
+                let mut peer_key = file_storage
+                    .peer_key
+                    .as_ref()
+                    .map(|key| decode_key::<Libp2pKeyPair>(key, "peer key"))
+                    .transpose()?;
+                let keypair = FileStore::new(&file_storage.peer_key_path).load_or_store(|| {
+                    peer_key
+                        .take()
+                        .unwrap_or_else(|| Libp2pKeyPair(IdentityKeypair::generate_ed25519()))
+                })?;
+                Ok(keypair.0)
             }
         }
     }
 }
 
+/// Decodes a hex encoded key given in the config file.
+fn decode_key<T: Deserialize>(key: &str, name: &str) -> Result<T, Error> {
+    let bytes = hex::decode(key)
+        .map_err(|_| Error::config_error(format!("The {name} is not valid hex")))?;
+    T::deserialize_from_vec(&bytes).map_err(|_| Error::config_error(format!("Invalid {name}")))
+}
+
 impl From<FileStorageConfig> for StorageConfig {
     fn from(config: FileStorageConfig) -> Self {
         StorageConfig::Filesystem(config)
@@ -512,6 +594,10 @@ pub struct ValidatorConfig {
 
     /// Config if the validator automatically reactivates itself.
     pub automatic_reactivate: bool,
+
+    /// The fee key. If not set, the fee key is loaded from the storage (or generated, if no fee
+    /// key is stored yet).
+    pub fee_key: Option<KeyPair>,
 }
 
 /// Credentials for JSON RPC server, metrics server or websocket RPC server
@@ -665,6 +751,21 @@ impl ClientConfig {
     pub fn builder() -> ClientConfigBuilder {
         ClientConfigBuilder::default()
     }
+
+    /// Loads the client configuration from a TOML config file. See `client.example.toml` for the
+    /// available settings.
+    ///
+    pub fn from_toml_file<P: AsRef<Path>>(path: P) -> Result<ClientConfig, Error> {
+        let path = path.as_ref();
+        let config_file = ConfigFile::from_file(path).map_err(|e| {
+            Error::config_error(format!(
+                "Failed to load config file {}: {e}",
+                path.display()
+            ))
+        })?;
+
+        Self::builder().config_file(&config_file)?.build()
+    }
 }
 
 impl ClientConfigBuilder {
@@ -672,11 +773,24 @@ impl ClientConfigBuilder {
     ///
     pub fn build(&self) -> Result<ClientConfig, Error> {
         // NOTE: We rename the generated builder and make it private to map the error from a plain
-        // `String` to an actual Error.
-        // We could also put some validation here.
+        // `String` to an actual Error and to validate the configuration.
+
+        let config = self
+            .build_internal()
+            .map_err(|e| Error::config_error(e.to_string()))?;
+
+        // Make sure the TLS key and certificates can be loaded, such that a misconfiguration
+        // doesn't only surface when the network is started.
+        if let Some(tls) = &config.network.tls {
+            NetworkTls::from_pem(&tls.certificates, &tls.private_key).map_err(|e| {
+                Error::config_error(format!(
+                    "Invalid TLS configuration (certificates: {}, private key: {}): {e}",
+                    tls.certificates, tls.private_key
+                ))
+            })?;
+        }
 
-        self.build_internal()
-            .map_err(|e| Error::config_error(e.to_string()))
+        Ok(config)
     }
 
     /// Sets the network ID to the Albatross DevNet
@@ -698,12 +812,55 @@ impl ClientConfigBuilder {
         self.consensus(consensus_config)
     }
 
+    /// Sets the seed nodes the network initially dials, replacing any previously set seeds.
+    pub fn seed_nodes(&mut self, addresses: Vec<Multiaddr>) -> &mut Self {
+        self.network
+            .get_or_insert_with(NetworkConfig::default)
+            .seeds = addresses
+            .into_iter()
+            .map(|address| Seed { address })
+            .collect();
+        self
+    }
+
+    /// Adds a seed node the network initially dials.
+    pub fn add_seed_node(&mut self, address: Multiaddr) -> &mut Self {
+        self.network
+            .get_or_insert_with(NetworkConfig::default)
+            .seeds
+            .push(Seed { address });
+        self
+    }
+
     /// Configures the storage to be volatile. All data will be lost after shutdown of the client.
     pub fn volatile(&mut self) -> &mut Self {
         self.storage = Some(StorageConfig::Volatile);
         self
     }
 
+    /// Configures the storage to be volatile with the given maximum database size in bytes.
+    /// All data will be lost after shutdown of the client.
+    pub fn volatile_sized(&mut self, size: usize) -> &mut Self {
+        self.storage = Some(StorageConfig::VolatileSized(size));
+        self
+    }
+
+    #[cfg(feature = "validator")]
+    /// Configures the client to run a validator with the given address. If no fee key is given,
+    /// it is loaded from the storage.
+    pub fn validator_config(
+        &mut self,
+        validator_address: Address,
+        automatic_reactivate: bool,
+        fee_key: Option<KeyPair>,
+    ) -> &mut Self {
+        self.validator(ValidatorConfig {
+            validator_address,
+            automatic_reactivate,
+            fee_key,
+        })
+    }
+
     #[cfg(feature = "nimiq-mempool")]
     /// Sets the mempool filter rules
     pub fn mempool(
@@ -776,6 +933,15 @@ impl ClientConfigBuilder {
         if let Some(full_sync_threshold) = config_file.consensus.full_sync_threshold {
             consensus.full_sync_threshold = full_sync_threshold;
         }
+        if let Some(history_chunk_concurrency) = config_file.consensus.history_chunk_concurrency {
+            consensus.history_chunk_concurrency = history_chunk_concurrency;
+        }
+        if let Some(history_sync_min_subnets) = config_file.consensus.history_sync_min_subnets {
+            consensus.history_sync_min_subnets = Some(history_sync_min_subnets);
+        }
+        if let Some(history_read_replica) = &config_file.consensus.history_read_replica {
+            consensus.history_read_replica = Some(history_read_replica.into());
+        }
         self.consensus(consensus);
 
         // Configure network
@@ -801,6 +967,7 @@ impl ClientConfigBuilder {
             self.validator(ValidatorConfig {
                 validator_address: Address::from_any_str(&validator_config.validator_address)?,
                 automatic_reactivate: validator_config.automatic_reactivate,
+                fee_key: None,
             });
 
             if let Some(key_path) = &validator_config.voting_key_file {
@@ -809,6 +976,15 @@ impl ClientConfigBuilder {
             if let Some(key) = &validator_config.voting_key {
                 file_storage.voting_key = Some(key.to_owned());
             }
+            if validator_config.encrypted_voting_key {
+                let password = std::env::var(consts::VOTING_KEY_PASSWORD_ENV).map_err(|_| {
+                    Error::config_error(format!(
+                        "The voting key is encrypted, but {} is not set",
+                        consts::VOTING_KEY_PASSWORD_ENV
+                    ))
+                })?;
+                file_storage.voting_key_password = Some(Sensitive(password));
+            }
             if let Some(key_path) = &validator_config.fee_key_file {
                 file_storage.fee_key_path = Some(PathBuf::from(key_path));
             }
@@ -828,6 +1004,12 @@ impl ClientConfigBuilder {
         #[cfg(feature = "database-storage")]
         self.database(config_file.database.clone());
 
+        // Configure mempool
+        #[cfg(feature = "nimiq-mempool")]
+        if let Some(mempool_settings) = config_file.mempool.as_ref() {
+            self.mempool = Some(mempool_settings.clone().into());
+        }
+
         // Configure the zk prover
         if let Some(zkp_settings) = config_file.zk_prover.as_ref() {
             let prover_keys_path = zkp_settings
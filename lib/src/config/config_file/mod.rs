@@ -222,9 +222,16 @@ pub struct ConsensusSettings {
     pub min_peers: Option<usize>,
     /// Minimum distance away, in number of blocks, from the head to switch from state sync to live sync
     pub full_sync_threshold: Option<u32>,
+    /// Maximum number of history chunks requested concurrently during the history sync
+    pub history_chunk_concurrency: Option<usize>,
+    /// Minimum number of distinct subnets the peers of a history sync target should be in
+    pub history_sync_min_subnets: Option<usize>,
     /// History indices enabled. Only effective for history nodes (default: `true`)
     #[serde(default = "default_true")]
     pub index_history: bool,
+    /// Path of a read-only replica of the database to serve history chunks from.
+    /// Only effective for history nodes
+    pub history_read_replica: Option<String>,
 }
 
 impl Default for ConsensusSettings {
@@ -235,7 +242,10 @@ impl Default for ConsensusSettings {
             network: None,
             min_peers: None,
             full_sync_threshold: None,
+            history_chunk_concurrency: None,
+            history_sync_min_subnets: None,
             index_history: true,
+            history_read_replica: None,
         }
     }
 }
@@ -370,6 +380,33 @@ pub struct DatabaseSettings {
     pub size: Option<usize>,
     pub max_dbs: Option<u32>,
     pub max_readers: Option<u32>,
+    pub sync_mode: Option<DatabaseSyncMode>,
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+/// How durably the database persists writes
+pub enum DatabaseSyncMode {
+    /// Every commit is flushed to disk
+    Durable,
+    /// The meta page is not flushed on commit, the last transactions may be lost on a system crash
+    NoMetaSync,
+    /// Flushing is left to the operating system, recent transactions may be lost on a system crash
+    SafeNoSync,
+    /// Flushing is left to the operating system, the database may be corrupted on a system crash
+    UtterlyNoSync,
+}
+
+#[cfg(feature = "database-storage")]
+impl From<DatabaseSyncMode> for nimiq_database::mdbx::SyncMode {
+    fn from(sync_mode: DatabaseSyncMode) -> Self {
+        match sync_mode {
+            DatabaseSyncMode::Durable => Self::Durable,
+            DatabaseSyncMode::NoMetaSync => Self::NoMetaSync,
+            DatabaseSyncMode::SafeNoSync => Self::SafeNoSync,
+            DatabaseSyncMode::UtterlyNoSync => Self::UtterlyNoSync,
+        }
+    }
 }
 
 #[derive(Clone, Debug, Deserialize)]
@@ -476,6 +513,8 @@ pub struct ValidatorSettings {
     pub signing_key: Option<Sensitive<String>>,
     pub voting_key_file: Option<String>,
     pub voting_key: Option<Sensitive<String>>,
+    #[serde(default)]
+    pub encrypted_voting_key: bool,
     pub fee_key_file: Option<String>,
     pub fee_key: Option<Sensitive<String>>,
     #[serde(default)]
@@ -12,6 +12,9 @@ pub const RPC_DEFAULT_PORT: u16 = 8648;
 /// The default port for the metrics server
 pub const METRICS_DEFAULT_PORT: u16 = 9100;
 
+/// The environment variable holding the password of an encrypted voting key file
+pub const VOTING_KEY_PASSWORD_ENV: &str = "NIMIQ_VOTING_KEY_PASSWORD";
+
 /// Returns the default bind, i.e. localhost
 pub fn default_bind() -> IpAddr {
     IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1))
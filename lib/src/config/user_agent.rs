@@ -14,6 +14,60 @@ use std::{env, fmt, str::FromStr};
 #[derive(Clone, Debug, Eq, PartialEq, Hash)]
 pub struct UserAgent(String);
 
+impl UserAgent {
+    /// Parses a user agent string of the format used by [`UserAgent::default`], e.g.
+    /// `core-rs-albatross/0.1.0 (native; linux x86_64)`, into its fields.
+    ///
+    /// Returns `None` if the string doesn't follow this format or if the version isn't a semantic
+    /// version (`major.minor.patch`, optionally followed by a pre-release suffix).
+    pub fn parse(s: &str) -> Option<ParsedUserAgent> {
+        let (product, details) = s.trim().split_once(' ')?;
+        let (client, version) = product.split_once('/')?;
+        if client.is_empty() || !is_semantic_version(version) {
+            return None;
+        }
+
+        let details = details.strip_prefix('(')?.strip_suffix(')')?;
+        let (platform, system) = details.split_once("; ")?;
+        let (os, arch) = system.split_once(' ')?;
+        if platform.is_empty() || os.is_empty() || arch.is_empty() || arch.contains(' ') {
+            return None;
+        }
+
+        Some(ParsedUserAgent {
+            client: client.to_string(),
+            version: version.to_string(),
+            platform: platform.to_string(),
+            os: os.to_string(),
+            arch: arch.to_string(),
+        })
+    }
+}
+
+fn is_semantic_version(version: &str) -> bool {
+    let core = version.split_once('-').map_or(version, |(core, _)| core);
+    let parts: Vec<&str> = core.split('.').collect();
+    parts.len() == 3
+        && parts
+            .iter()
+            .all(|part| !part.is_empty() && part.chars().all(|c| c.is_ascii_digit()))
+}
+
+/// The fields of a user agent string, as returned by [`UserAgent::parse`].
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub struct ParsedUserAgent {
+    /// The client name, e.g. `core-rs-albatross`.
+    pub client: String,
+    /// The semantic version of the client, e.g. `0.1.0`.
+    pub version: String,
+    /// The platform the client runs on, e.g. `native`.
+    pub platform: String,
+    /// The operating system, e.g. `linux`.
+    pub os: String,
+    /// The CPU architecture, e.g. `x86_64`.
+    pub arch: String,
+}
+
 impl FromStr for UserAgent {
     type Err = ();
 
@@ -17,6 +17,9 @@ pub enum Error {
     #[error("Network error: {0}")]
     Network(#[from] nimiq_network_libp2p::NetworkError),
 
+    #[error("TLS configuration error: {0}")]
+    Tls(#[from] nimiq_network_libp2p::TlsError),
+
     #[error("File store error: {0}")]
     FileStore(#[from] nimiq_utils::file_store::Error),
 
@@ -1,9 +1,17 @@
 use std::path::PathBuf;
 
-use nimiq_lib::config::{
-    config::{ClientConfigBuilder, DatabaseConfig, DatabaseConfigBuilder, FileStorageConfig},
-    config_file::ConfigFile,
+use nimiq_lib::{
+    config::{
+        config::{
+            ClientConfig, ClientConfigBuilder, DatabaseConfig, DatabaseConfigBuilder,
+            FileStorageConfig, NetworkConfig, StorageConfig, SyncMode, TlsConfig,
+        },
+        config_file::{ConfigFile, DatabaseSyncMode},
+    },
+    error::Error,
 };
+use nimiq_network_interface::Multiaddr;
+use nimiq_primitives::networks::NetworkId;
 use nimiq_test_log::test;
 
 #[test]
@@ -16,6 +24,32 @@ fn config_file_no_db_entry() {
     assert_eq!(config.database, DatabaseConfig::default());
 }
 
+#[test]
+fn config_file_history_read_replica() {
+    let config_file: ConfigFile = toml::from_str(r#""#).unwrap();
+    let mut config_builder = ClientConfigBuilder::default();
+    config_builder.config_file(&config_file).unwrap();
+    let config = config_builder.build().unwrap();
+
+    assert_eq!(config.consensus.history_read_replica, None);
+
+    let config_file: ConfigFile = toml::from_str(
+        r#"
+    [consensus]
+    history_read_replica = "/mnt/snapshot/nimiq"
+    "#,
+    )
+    .unwrap();
+    let mut config_builder = ClientConfigBuilder::default();
+    config_builder.config_file(&config_file).unwrap();
+    let config = config_builder.build().unwrap();
+
+    assert_eq!(
+        config.consensus.history_read_replica,
+        Some(PathBuf::from("/mnt/snapshot/nimiq"))
+    );
+}
+
 #[test]
 fn config_file_partial_db_entry() {
     // Only the DB entry
@@ -113,3 +147,141 @@ fn config_file_partial_db_entry() {
 
     assert_eq!(config.storage, db_config.into());
 }
+
+#[test]
+fn config_volatile_sized_storage() {
+    let config = ClientConfigBuilder::default()
+        .volatile_sized(1024 * 1024usize)
+        .build()
+        .unwrap();
+
+    assert_eq!(config.storage, StorageConfig::VolatileSized(1024 * 1024));
+}
+
+#[test]
+fn config_file_db_sync_mode() {
+    let config_file: ConfigFile = toml::from_str(
+        r#"
+    [database]
+    sync_mode = "safe-no-sync"
+    "#,
+    )
+    .unwrap();
+    assert_eq!(
+        config_file.database.as_ref().unwrap().sync_mode,
+        Some(DatabaseSyncMode::SafeNoSync)
+    );
+
+    let mut config_builder = ClientConfigBuilder::default();
+    config_builder.config_file(&config_file).unwrap();
+    let config = config_builder.build().unwrap();
+
+    assert_ne!(config.database, DatabaseConfig::default());
+
+    // Unknown sync modes are rejected.
+    assert!(toml::from_str::<ConfigFile>(
+        r#"
+    [database]
+    sync_mode = "sometimes"
+    "#,
+    )
+    .is_err());
+}
+
+#[test]
+fn config_invalid_tls_files() {
+    let result = ClientConfigBuilder::default()
+        .network(NetworkConfig {
+            tls: Some(TlsConfig {
+                private_key: "/not/valid/path/key.pem".to_string(),
+                certificates: "/not/valid/path/cert.pem".to_string(),
+            }),
+            ..Default::default()
+        })
+        .build();
+
+    assert!(result.is_err());
+}
+
+#[test]
+fn config_from_toml_file() {
+    let path = std::env::temp_dir().join(format!("nimiq-config-test-{}.toml", std::process::id()));
+
+    std::fs::write(
+        &path,
+        r#"
+    [consensus]
+    network = "dev-albatross"
+    sync_mode = "light"
+
+    [mempool]
+    size_limit = 4242
+    "#,
+    )
+    .unwrap();
+    let config = ClientConfig::from_toml_file(&path).unwrap();
+    assert_eq!(config.network_id, NetworkId::DevAlbatross);
+    assert_eq!(config.consensus.sync_mode, SyncMode::Light);
+    #[cfg(feature = "nimiq-mempool")]
+    assert_eq!(config.mempool.size_limit, 4242);
+
+    std::fs::write(
+        &path,
+        r#"
+    [consensus]
+    network = "no-such-network"
+    "#,
+    )
+    .unwrap();
+    assert!(matches!(
+        ClientConfig::from_toml_file(&path),
+        Err(Error::Config(_))
+    ));
+
+    std::fs::remove_file(&path).unwrap();
+    assert!(matches!(
+        ClientConfig::from_toml_file(&path),
+        Err(Error::Config(_))
+    ));
+}
+
+#[test]
+fn config_seed_nodes() {
+    let seed1: Multiaddr = "/dns4/seed1.example.com/tcp/8443/wss".parse().unwrap();
+    let seed2: Multiaddr = "/ip4/127.0.0.1/tcp/8443/ws".parse().unwrap();
+
+    let config = ClientConfigBuilder::default()
+        .seed_nodes(vec![seed1.clone()])
+        .add_seed_node(seed2.clone())
+        .build()
+        .unwrap();
+
+    let seeds: Vec<Multiaddr> = config
+        .network
+        .seeds
+        .into_iter()
+        .map(|seed| seed.address)
+        .collect();
+    assert_eq!(seeds, vec![seed1, seed2]);
+}
+
+#[cfg(feature = "validator")]
+#[test]
+fn config_validator_with_fee_key() {
+    use nimiq_keys::{Address, KeyPair};
+    use nimiq_utils::key_rng::SecureGenerate;
+
+    let fee_key = KeyPair::generate_default_csprng();
+    let validator_address = Address::from(&KeyPair::generate_default_csprng());
+
+    let config = ClientConfigBuilder::default()
+        .volatile()
+        .validator_config(validator_address.clone(), true, Some(fee_key.clone()))
+        .build()
+        .unwrap();
+
+    let validator = config.validator.unwrap();
+    assert_eq!(validator.validator_address, validator_address);
+    assert!(validator.automatic_reactivate);
+    assert_eq!(validator.fee_key, Some(fee_key));
+}
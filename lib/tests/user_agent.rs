@@ -0,0 +1,42 @@
+use nimiq_lib::config::user_agent::{ParsedUserAgent, UserAgent};
+use nimiq_test_log::test;
+
+#[test]
+fn user_agent_parse() {
+    assert_eq!(
+        UserAgent::parse("core-rs-albatross/0.1.0 (native; linux x86_64)"),
+        Some(ParsedUserAgent {
+            client: "core-rs-albatross".to_string(),
+            version: "0.1.0".to_string(),
+            platform: "native".to_string(),
+            os: "linux".to_string(),
+            arch: "x86_64".to_string(),
+        })
+    );
+
+    let parsed = UserAgent::parse("nimiq-web-client/1.2.3-rc.1 (web; macos aarch64)").unwrap();
+    assert_eq!(parsed.version, "1.2.3-rc.1");
+    assert_eq!(parsed.platform, "web");
+}
+
+#[test]
+fn user_agent_parse_default() {
+    assert!(UserAgent::parse(&UserAgent::default().to_string()).is_some());
+}
+
+#[test]
+fn user_agent_parse_invalid() {
+    for user_agent in [
+        "",
+        "core-rs-albatross",
+        "core-rs-albatross/0.1.0",
+        "core-rs-albatross/0.1 (native; linux x86_64)",
+        "core-rs-albatross/unknown (native; linux x86_64)",
+        "/0.1.0 (native; linux x86_64)",
+        "core-rs-albatross/0.1.0 (native linux x86_64)",
+        "core-rs-albatross/0.1.0 (native; linux)",
+        "core-rs-albatross/0.1.0 native; linux x86_64",
+    ] {
+        assert_eq!(UserAgent::parse(user_agent), None, "{user_agent}");
+    }
+}
@@ -13,6 +13,7 @@ use tokio_stream::wrappers::errors::BroadcastStreamRecvError;
 use crate::{
     peer_info::*,
     request::{Message, Request, RequestError},
+    Multiaddr,
 };
 
 /// Network events that the network will report when subscribing
@@ -22,8 +23,37 @@ pub enum NetworkEvent<P> {
     PeerJoined(P, PeerInfo),
     /// A peer disconnected
     PeerLeft(P),
+    /// A peer didn't complete the handshake in time and its connection was closed
+    HandshakeTimeout(P),
+    /// A peer doesn't support our protocol version and its connection was closed
+    IncompatiblePeer {
+        peer: P,
+        /// The protocol version we speak
+        our_version: String,
+    },
     /// DHT is ready (bootstrapped and in server mode) to publish records
     DhtReady,
+    /// The first DHT bootstrap completed and the routing table is populated enough
+    /// to perform lookups. This is only emitted once.
+    DhtBootstrapped,
+    /// Our reachability from other peers changed
+    ReachabilityChanged {
+        old: Reachability,
+        new: Reachability,
+        /// The observed external address that made us publicly reachable, if any
+        address: Option<Multiaddr>,
+    },
+}
+
+/// Reachability of our own node from other peers
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Reachability {
+    /// Reachability hasn't been determined yet
+    Unknown,
+    /// We are publicly reachable
+    Public,
+    /// We are not reachable from other peers (e.g. we are behind a NAT)
+    Private,
 }
 
 pub type SubscribeEvents<PeerId> =
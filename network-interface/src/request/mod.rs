@@ -114,6 +114,21 @@ pub enum InboundRequestError {
     ExceedsRateLimit = 5,
 }
 
+/// Priority class of a request type.
+///
+/// Outgoing requests and responses of a higher priority are dispatched by the network
+/// before the ones of lower priority that are queued at the same time.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum RequestPriority {
+    /// Consensus critical messages
+    High,
+    /// Regular messages
+    #[default]
+    Normal,
+    /// Bulk messages, e.g. history or state chunks
+    Low,
+}
+
 pub trait RequestKind {
     const EXPECT_RESPONSE: bool;
 }
@@ -136,6 +151,7 @@ pub trait RequestCommon:
     type Response: Deserialize + Serialize + Send;
     const MAX_REQUESTS: u32;
     const TIME_WINDOW: Duration = DEFAULT_MAX_REQUEST_RESPONSE_TIME_WINDOW;
+    const PRIORITY: RequestPriority = RequestPriority::Normal;
 
     /// Returns the type name of the given request type `T`.
     /// This only works for
@@ -0,0 +1,303 @@
+use std::{
+    collections::HashMap,
+    io::{self, IoSlice, IoSliceMut},
+    pin::Pin,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Weak,
+    },
+    task::{Context, Poll},
+    time::Duration,
+};
+
+use futures::{AsyncRead, AsyncWrite};
+use instant::Instant;
+use libp2p::{
+    core::muxing::{StreamMuxer, StreamMuxerBox, StreamMuxerEvent, SubstreamBox},
+    PeerId,
+};
+use parking_lot::Mutex;
+
+/// Bandwidth used by the connections to a peer or by all connections together.
+/// Bytes are counted on the multiplexed substreams, so encryption and multiplexing overhead
+/// isn't included.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct BandwidthStats {
+    /// Total number of bytes sent.
+    pub bytes_sent: u64,
+    /// Total number of bytes received.
+    pub bytes_received: u64,
+    /// Bytes sent per second, averaged over the last rate window.
+    pub send_rate: f64,
+    /// Bytes received per second, averaged over the last rate window.
+    pub receive_rate: f64,
+}
+
+/// Byte counters of one or more connections.
+struct Counters {
+    sent: AtomicU64,
+    received: AtomicU64,
+    rate: Mutex<RateWindow>,
+}
+
+/// State to compute the send and receive rates over a short time window.
+struct RateWindow {
+    start: Instant,
+    sent_at_start: u64,
+    received_at_start: u64,
+    send_rate: f64,
+    receive_rate: f64,
+}
+
+impl Counters {
+    /// Minimum duration over which the rates are averaged.
+    const RATE_WINDOW: Duration = Duration::from_secs(5);
+
+    fn new() -> Self {
+        Self {
+            sent: AtomicU64::new(0),
+            received: AtomicU64::new(0),
+            rate: Mutex::new(RateWindow {
+                start: Instant::now(),
+                sent_at_start: 0,
+                received_at_start: 0,
+                send_rate: 0.0,
+                receive_rate: 0.0,
+            }),
+        }
+    }
+
+    fn stats(&self, now: Instant) -> BandwidthStats {
+        let bytes_sent = self.sent.load(Ordering::Relaxed);
+        let bytes_received = self.received.load(Ordering::Relaxed);
+
+        // The rates are updated once a window has passed and then stay fixed for the next window.
+        let mut rate = self.rate.lock();
+        let elapsed = now.saturating_duration_since(rate.start);
+        if elapsed >= Self::RATE_WINDOW {
+            let secs = elapsed.as_secs_f64();
+            rate.send_rate = bytes_sent.saturating_sub(rate.sent_at_start) as f64 / secs;
+            rate.receive_rate = bytes_received.saturating_sub(rate.received_at_start) as f64 / secs;
+            rate.start = now;
+            rate.sent_at_start = bytes_sent;
+            rate.received_at_start = bytes_received;
+        }
+
+        BandwidthStats {
+            bytes_sent,
+            bytes_received,
+            send_rate: rate.send_rate,
+            receive_rate: rate.receive_rate,
+        }
+    }
+}
+
+/// Keeps track of the bandwidth used per peer and in total.
+///
+/// The counters of a peer are shared among all its connections and are dropped together with
+/// the last connection to the peer.
+pub(crate) struct BandwidthTracker {
+    peers: Mutex<HashMap<PeerId, Weak<Counters>>>,
+    total: Arc<Counters>,
+}
+
+impl BandwidthTracker {
+    pub(crate) fn new() -> Self {
+        Self {
+            peers: Mutex::new(HashMap::new()),
+            total: Arc::new(Counters::new()),
+        }
+    }
+
+    /// Wraps the muxer of a new connection to the given peer such that its traffic is counted.
+    pub(crate) fn track(&self, peer_id: PeerId, muxer: StreamMuxerBox) -> StreamMuxerBox {
+        let mut peers = self.peers.lock();
+        peers.retain(|_, counters| counters.strong_count() > 0);
+
+        let counters = match peers.get(&peer_id).and_then(Weak::upgrade) {
+            Some(counters) => counters,
+            None => {
+                let counters = Arc::new(Counters::new());
+                peers.insert(peer_id, Arc::downgrade(&counters));
+                counters
+            }
+        };
+
+        StreamMuxerBox::new(CountingMuxer {
+            inner: muxer,
+            peer: counters,
+            total: Arc::clone(&self.total),
+        })
+    }
+
+    /// Returns the bandwidth used by the connections to the given peer, if it is connected.
+    pub(crate) fn peer_stats(&self, peer_id: &PeerId) -> Option<BandwidthStats> {
+        let counters = self.peers.lock().get(peer_id)?.upgrade()?;
+        Some(counters.stats(Instant::now()))
+    }
+
+    /// Returns the bandwidth used by all connections since the network was started.
+    pub(crate) fn total_stats(&self) -> BandwidthStats {
+        self.total.stats(Instant::now())
+    }
+}
+
+/// Stream muxer that counts the bytes transferred over its substreams.
+struct CountingMuxer {
+    inner: StreamMuxerBox,
+    peer: Arc<Counters>,
+    total: Arc<Counters>,
+}
+
+impl CountingMuxer {
+    fn wrap(&self, inner: SubstreamBox) -> CountingSubstream {
+        CountingSubstream {
+            inner,
+            peer: Arc::clone(&self.peer),
+            total: Arc::clone(&self.total),
+        }
+    }
+}
+
+impl StreamMuxer for CountingMuxer {
+    type Substream = CountingSubstream;
+    type Error = io::Error;
+
+    fn poll_inbound(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<Self::Substream, Self::Error>> {
+        let substream = futures::ready!(Pin::new(&mut self.inner).poll_inbound(cx))?;
+        Poll::Ready(Ok(self.wrap(substream)))
+    }
+
+    fn poll_outbound(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<Self::Substream, Self::Error>> {
+        let substream = futures::ready!(Pin::new(&mut self.inner).poll_outbound(cx))?;
+        Poll::Ready(Ok(self.wrap(substream)))
+    }
+
+    fn poll_close(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Pin::new(&mut self.inner).poll_close(cx)
+    }
+
+    fn poll(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<StreamMuxerEvent, Self::Error>> {
+        Pin::new(&mut self.inner).poll(cx)
+    }
+}
+
+/// Substream that counts the bytes read from and written to it.
+struct CountingSubstream {
+    inner: SubstreamBox,
+    peer: Arc<Counters>,
+    total: Arc<Counters>,
+}
+
+impl CountingSubstream {
+    fn note_sent(&self, num_bytes: usize) {
+        self.peer
+            .sent
+            .fetch_add(num_bytes as u64, Ordering::Relaxed);
+        self.total
+            .sent
+            .fetch_add(num_bytes as u64, Ordering::Relaxed);
+    }
+
+    fn note_received(&self, num_bytes: usize) {
+        self.peer
+            .received
+            .fetch_add(num_bytes as u64, Ordering::Relaxed);
+        self.total
+            .received
+            .fetch_add(num_bytes as u64, Ordering::Relaxed);
+    }
+}
+
+impl AsyncRead for CountingSubstream {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        let num_bytes = futures::ready!(Pin::new(&mut self.inner).poll_read(cx, buf))?;
+        self.note_received(num_bytes);
+        Poll::Ready(Ok(num_bytes))
+    }
+
+    fn poll_read_vectored(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &mut [IoSliceMut<'_>],
+    ) -> Poll<io::Result<usize>> {
+        let num_bytes = futures::ready!(Pin::new(&mut self.inner).poll_read_vectored(cx, bufs))?;
+        self.note_received(num_bytes);
+        Poll::Ready(Ok(num_bytes))
+    }
+}
+
+impl AsyncWrite for CountingSubstream {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        let num_bytes = futures::ready!(Pin::new(&mut self.inner).poll_write(cx, buf))?;
+        self.note_sent(num_bytes);
+        Poll::Ready(Ok(num_bytes))
+    }
+
+    fn poll_write_vectored(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &[IoSlice<'_>],
+    ) -> Poll<io::Result<usize>> {
+        let num_bytes = futures::ready!(Pin::new(&mut self.inner).poll_write_vectored(cx, bufs))?;
+        self.note_sent(num_bytes);
+        Poll::Ready(Ok(num_bytes))
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.inner).poll_flush(cx)
+    }
+
+    fn poll_close(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.inner).poll_close(cx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use nimiq_test_log::test;
+
+    use super::*;
+
+    #[test]
+    fn it_computes_rates_over_a_window() {
+        let counters = Counters::new();
+        let start = counters.rate.lock().start;
+
+        counters.sent.fetch_add(1000, Ordering::Relaxed);
+        counters.received.fetch_add(500, Ordering::Relaxed);
+
+        // The rates are only computed once the window has passed.
+        let stats = counters.stats(start + Duration::from_secs(1));
+        assert_eq!(stats.bytes_sent, 1000);
+        assert_eq!(stats.bytes_received, 500);
+        assert_eq!(stats.send_rate, 0.0);
+
+        let stats = counters.stats(start + Duration::from_secs(5));
+        assert_eq!(stats.send_rate, 200.0);
+        assert_eq!(stats.receive_rate, 100.0);
+
+        counters.sent.fetch_add(1000, Ordering::Relaxed);
+        let stats = counters.stats(start + Duration::from_secs(15));
+        assert_eq!(stats.bytes_sent, 2000);
+        assert_eq!(stats.send_rate, 100.0);
+        assert_eq!(stats.receive_rate, 0.0);
+    }
+}
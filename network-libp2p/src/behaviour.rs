@@ -1,10 +1,10 @@
 use std::{iter, sync::Arc};
 
 use libp2p::{
-    autonat, connection_limits, gossipsub,
+    autonat, connection_limits, gossipsub, identify,
     kad::{self, store::MemoryStore},
-    ping, request_response,
-    swarm::NetworkBehaviour,
+    ping, relay, request_response,
+    swarm::{behaviour::toggle::Toggle, NetworkBehaviour},
     Multiaddr, PeerId, StreamProtocol,
 };
 use parking_lot::RwLock;
@@ -13,7 +13,7 @@ use crate::{
     connection_pool,
     discovery::{self, peer_contacts::PeerContactBook},
     dispatch::codecs::MessageCodec,
-    Config,
+    Config, REQRES_PROTOCOL,
 };
 
 /// Maximum simultaneous libp2p connections per peer
@@ -35,6 +35,9 @@ pub struct Behaviour {
     pub dht: kad::Behaviour<MemoryStore>,
     pub gossipsub: gossipsub::Behaviour,
     pub autonat: autonat::Behaviour,
+    pub identify: identify::Behaviour,
+    pub relay_client: Toggle<relay::client::Behaviour>,
+    pub relay_server: Toggle<relay::Behaviour>,
     pub ping: ping::Behaviour,
     pub request_response: request_response::Behaviour<MessageCodec>,
 }
@@ -45,13 +48,24 @@ impl Behaviour {
         contacts: Arc<RwLock<PeerContactBook>>,
         peer_score_params: gossipsub::PeerScoreParams,
         force_dht_server_mode: bool,
+        relay_client: Option<relay::client::Behaviour>,
     ) -> Self {
         let public_key = config.keypair.public();
         let peer_id = public_key.to_peer_id();
 
         // DHT behaviour
         let store = MemoryStore::new(peer_id);
-        let mut dht = kad::Behaviour::with_config(peer_id, store, config.kademlia);
+        let mut kademlia = config.kademlia;
+        if let Some(record_ttl) = config.dht_record_ttl {
+            kademlia.set_record_ttl(Some(record_ttl));
+        }
+        if let Some(publication_interval) = config.dht_publication_interval {
+            kademlia.set_publication_interval(Some(publication_interval));
+        }
+        if let Some(provider_publication_interval) = config.dht_provider_publication_interval {
+            kademlia.set_provider_publication_interval(Some(provider_publication_interval));
+        }
+        let mut dht = kad::Behaviour::with_config(peer_id, store, kademlia);
         if force_dht_server_mode {
             dht.set_mode(Some(kad::Mode::Server));
         }
@@ -86,10 +100,15 @@ impl Behaviour {
             config.seeds,
             config.discovery.required_services,
             config.desired_peer_count,
+            config.protected_peers,
+            config.connection_backoff_base,
+            config.connection_backoff_max,
+            config.peer_filter,
+            config.max_concurrent_dials,
         );
 
         // Request Response behaviour
-        let protocol = StreamProtocol::new("/nimiq/reqres/0.0.1");
+        let protocol = StreamProtocol::new(REQRES_PROTOCOL);
         let req_res_config = request_response::Config::default().with_max_concurrent_streams(1000);
         let request_response = request_response::Behaviour::new(
             iter::once((protocol, request_response::ProtocolSupport::Full)),
@@ -103,6 +122,27 @@ impl Behaviour {
         }
         let autonat = autonat::Behaviour::new(peer_id, autonat_config);
 
+        // Identify behaviour
+        let identify = identify::Behaviour::new(
+            identify::Config::new(config.identify_protocol_version, public_key)
+                .with_agent_version(config.identify_agent_version),
+        );
+
+        // Relay server behaviour
+        let relay_server = config.relay_server.map(|limits| {
+            let relay_config = relay::Config {
+                max_reservations: limits.max_reservations,
+                max_reservations_per_peer: limits.max_reservations_per_peer,
+                reservation_duration: limits.reservation_duration,
+                max_circuits: limits.max_circuits,
+                max_circuits_per_peer: limits.max_circuits_per_peer,
+                max_circuit_duration: limits.max_circuit_duration,
+                max_circuit_bytes: limits.max_circuit_bytes,
+                ..Default::default()
+            };
+            relay::Behaviour::new(peer_id, relay_config)
+        });
+
         // Connection limits behaviour
         let limits = connection_limits::ConnectionLimits::default()
             .with_max_pending_incoming(Some(16))
@@ -120,6 +160,9 @@ impl Behaviour {
             pool,
             request_response,
             autonat,
+            identify,
+            relay_client: relay_client.into(),
+            relay_server: relay_server.into(),
             connection_limits,
         }
     }
@@ -130,9 +173,9 @@ impl Behaviour {
         self.dht.add_address(&peer_id, address);
     }
 
-    /// Removes a peer from the DHT
-    pub fn remove_peer(&mut self, peer_id: PeerId) {
-        self.dht.remove_peer(&peer_id);
+    /// Removes a peer from the DHT. Returns whether the peer was in the routing table.
+    pub fn remove_peer(&mut self, peer_id: PeerId) -> bool {
+        self.dht.remove_peer(&peer_id).is_some()
     }
 
     /// Removes a peer address from the DHT
@@ -1,12 +1,20 @@
-use std::{num::NonZeroU8, time::Duration};
+use std::{
+    collections::{HashMap, HashSet},
+    num::NonZeroU8,
+    time::Duration,
+};
 
-use libp2p::{gossipsub, identity::Keypair, kad, Multiaddr, StreamProtocol};
+use libp2p::{
+    gossipsub, identity::Keypair, kad, multiaddr::Protocol, Multiaddr, PeerId, StreamProtocol,
+};
 use nimiq_hash::Blake2bHash;
 use nimiq_network_interface::{network::MIN_SUPPORTED_MSG_SIZE, peer_info::Services};
 use sha2::{Digest, Sha256};
+use thiserror::Error;
 
 use crate::{
     discovery::{self, peer_contacts::PeerContact},
+    rate_limiting::RateLimitConfig,
     DHT_PROTOCOL,
 };
 
@@ -19,6 +27,77 @@ pub struct TlsConfig {
     pub certificates: Vec<Vec<u8>>,
 }
 
+/// Limits of the circuit relay v2 server
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RelayLimits {
+    /// Maximum number of reservations in total.
+    pub max_reservations: usize,
+    /// Maximum number of reservations per peer.
+    pub max_reservations_per_peer: usize,
+    /// Time after which a reservation expires if it isn't renewed.
+    pub reservation_duration: Duration,
+    /// Maximum number of relayed connections in total.
+    pub max_circuits: usize,
+    /// Maximum number of relayed connections per peer.
+    pub max_circuits_per_peer: usize,
+    /// Maximum duration of a relayed connection.
+    pub max_circuit_duration: Duration,
+    /// Maximum number of bytes relayed per connection.
+    pub max_circuit_bytes: u64,
+}
+
+impl Default for RelayLimits {
+    fn default() -> Self {
+        Self {
+            max_reservations: 128,
+            max_reservations_per_peer: 4,
+            reservation_duration: Duration::from_secs(60 * 60), // 1h
+            max_circuits: 16,
+            max_circuits_per_peer: 4,
+            max_circuit_duration: Duration::from_secs(2 * 60), // 2 min
+            max_circuit_bytes: 1 << 17,                        // 128 KiB
+        }
+    }
+}
+
+/// Filter restricting the peers the network may connect to
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PeerFilter {
+    /// Only the listed peers are allowed to connect
+    AllowList(HashSet<PeerId>),
+    /// All peers except the listed ones are allowed to connect
+    DenyList(HashSet<PeerId>),
+}
+
+impl PeerFilter {
+    /// Returns whether connections with the given peer are allowed
+    pub fn allows(&self, peer_id: &PeerId) -> bool {
+        match self {
+            PeerFilter::AllowList(peer_ids) => peer_ids.contains(peer_id),
+            PeerFilter::DenyList(peer_ids) => !peer_ids.contains(peer_id),
+        }
+    }
+}
+
+/// An inconsistency found by [`Config::validate`]
+#[derive(Clone, Debug, Error, PartialEq, Eq)]
+pub enum ConfigError {
+    #[error("The relay client is enabled, but no relay peers are configured")]
+    NoRelayPeers,
+
+    #[error("Relay peer address {0} doesn't contain a peer ID")]
+    RelayPeerWithoutPeerId(Multiaddr),
+
+    #[error("Listen address {0} requires QUIC, but QUIC is disabled")]
+    QuicDisabled(Multiaddr),
+
+    #[error("The connection backoff base {base:?} exceeds the maximum backoff {max:?}")]
+    InvalidConnectionBackoff { base: Duration, max: Duration },
+
+    #[error("No concurrent dials are allowed")]
+    NoConcurrentDials,
+}
+
 /// LibP2P network configuration
 pub struct Config {
     pub keypair: Keypair,
@@ -33,8 +112,61 @@ pub struct Config {
     pub desired_peer_count: usize,
     pub autonat_allow_non_global_ips: bool,
     pub only_secure_ws_connections: bool,
+    /// Adds a QUIC transport next to the other transports, such that `/quic-v1` addresses can be
+    /// listened on and dialed. Ignored on wasm and if only secure WebSocket connections are allowed.
+    pub enable_quic: bool,
+    /// Enables the circuit relay v2 client. If a direct dial to a peer fails, the peer is then
+    /// dialed through the `relay_peers`. We also reserve a slot on each relay peer, such that
+    /// others can reach us through it.
+    pub enable_relay_client: bool,
+    /// Addresses of the relay peers, including their `/p2p` peer ID.
+    pub relay_peers: Vec<Multiaddr>,
+    /// Enables the circuit relay v2 server with the given limits, such that peers that aren't
+    /// publicly reachable can be reached through us.
+    pub relay_server: Option<RelayLimits>,
     pub allow_loopback_addresses: bool,
+    /// Agent version advertised by the identify protocol, usually the node's user agent.
+    pub identify_agent_version: String,
+    /// Protocol version advertised by the identify protocol.
+    pub identify_protocol_version: String,
     pub dht_quorum: NonZeroU8,
+    /// Time-to-live of the DHT records. If `None`, the default TTL configured in `kademlia` is used.
+    pub dht_record_ttl: Option<Duration>,
+    /// Interval in which DHT records are republished. If `None`, the default interval configured
+    /// in `kademlia` is used.
+    pub dht_publication_interval: Option<Duration>,
+    /// Interval in which we re-announce ourselves as provider of the keys we provide. If `None`,
+    /// the default interval configured in `kademlia` is used.
+    pub dht_provider_publication_interval: Option<Duration>,
+    /// Peers that are never evicted from the connection pool to make room for other peers.
+    pub protected_peers: HashSet<PeerId>,
+    /// Rate limits per request type name, overriding the limits defined by the request types.
+    /// Request types that are not listed use their own default limits.
+    pub rate_limits: HashMap<String, RateLimitConfig>,
+    /// Addresses to listen on once the network is created
+    pub listen_addresses: Vec<Multiaddr>,
+    /// Initial delay before re-dialing a peer after a failed dial attempt.
+    /// Consecutive failures double this delay up to `connection_backoff_max`.
+    pub connection_backoff_base: Duration,
+    /// Maximum delay before re-dialing a peer after repeated failed dial attempts.
+    pub connection_backoff_max: Duration,
+    /// Maximum number of dials the connection pool has in progress at the same time.
+    /// Further peers and seeds are dialed as earlier dials resolve.
+    pub max_concurrent_dials: usize,
+    /// Number of populated Kademlia buckets required after the first successful bootstrap
+    /// before `NetworkEvent::DhtBootstrapped` is emitted.
+    pub dht_bootstrap_min_buckets: usize,
+    /// Optional filter for the peers that can connect or be dialed. Connections violating
+    /// it are rejected by the connection pool.
+    pub peer_filter: Option<PeerFilter>,
+    /// Maximum time a peer may be disconnected for its next connection to be reported
+    /// as a reconnection.
+    pub peer_reconnect_window: Duration,
+    /// Number of recently seen gossipsub message IDs that are remembered to suppress duplicate
+    /// messages before they are dispatched. Zero disables the deduplication.
+    pub gossip_dedup_cache_size: usize,
+    /// Time for which a gossipsub message ID is remembered for the deduplication.
+    pub gossip_dedup_cache_ttl: Duration,
 }
 
 impl Config {
@@ -98,8 +230,140 @@ impl Config {
             desired_peer_count,
             autonat_allow_non_global_ips,
             only_secure_ws_connections,
+            enable_quic: false,
+            enable_relay_client: false,
+            relay_peers: vec![],
+            relay_server: None,
             allow_loopback_addresses,
+            identify_agent_version: format!("nimiq-network-libp2p/{}", env!("CARGO_PKG_VERSION")),
+            identify_protocol_version: "/nimiq/0.0.1".to_string(),
             dht_quorum,
+            dht_record_ttl: None,
+            dht_publication_interval: None,
+            dht_provider_publication_interval: None,
+            protected_peers: HashSet::new(),
+            rate_limits: HashMap::new(),
+            listen_addresses: vec![],
+            connection_backoff_base: Duration::from_secs(1),
+            connection_backoff_max: Duration::from_secs(5 * 60), // 5 min
+            max_concurrent_dials: 3,
+            dht_bootstrap_min_buckets: 1,
+            peer_filter: None,
+            peer_reconnect_window: Duration::from_secs(5 * 60), // 5 min
+            gossip_dedup_cache_size: 10_000,
+            gossip_dedup_cache_ttl: Duration::from_secs(2 * 60), // 2 min
+        }
+    }
+
+    /// Checks the configuration for settings that contradict each other.
+    /// Returns all inconsistencies that were found.
+    pub fn validate(&self) -> Result<(), Vec<ConfigError>> {
+        let mut errors = vec![];
+
+        if self.enable_relay_client {
+            if self.relay_peers.is_empty() {
+                errors.push(ConfigError::NoRelayPeers);
+            }
+            for address in &self.relay_peers {
+                if !address.iter().any(|p| matches!(p, Protocol::P2p(_))) {
+                    errors.push(ConfigError::RelayPeerWithoutPeerId(address.clone()));
+                }
+            }
+        }
+
+        if !self.enable_quic || self.only_secure_ws_connections {
+            for address in &self.listen_addresses {
+                if address
+                    .iter()
+                    .any(|p| matches!(p, Protocol::Quic | Protocol::QuicV1))
+                {
+                    errors.push(ConfigError::QuicDisabled(address.clone()));
+                }
+            }
+        }
+
+        if self.connection_backoff_base > self.connection_backoff_max {
+            errors.push(ConfigError::InvalidConnectionBackoff {
+                base: self.connection_backoff_base,
+                max: self.connection_backoff_max,
+            });
         }
+
+        if self.max_concurrent_dials == 0 {
+            errors.push(ConfigError::NoConcurrentDials);
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use libp2p::multiaddr::multiaddr;
+    use nimiq_test_log::test;
+
+    use super::*;
+
+    fn config() -> Config {
+        let keypair = Keypair::generate_ed25519();
+        let peer_contact =
+            PeerContact::new(vec![], keypair.public(), Services::all(), None).unwrap();
+        Config::new(
+            keypair,
+            peer_contact,
+            vec![],
+            Default::default(),
+            true,
+            Services::all(),
+            None,
+            3,
+            true,
+            false,
+            true,
+            NonZeroU8::new(1).unwrap(),
+        )
+    }
+
+    #[test]
+    fn it_accepts_the_default_config() {
+        assert_eq!(config().validate(), Ok(()));
+    }
+
+    #[test]
+    fn it_reports_all_inconsistencies() {
+        let quic_address = multiaddr!(Ip4([127, 0, 0, 1]), Udp(8443u16), QuicV1);
+        let relay_address = multiaddr!(Ip4([127, 0, 0, 1]), Tcp(8443u16));
+
+        let mut config = config();
+        config.listen_addresses = vec![quic_address.clone()];
+        config.enable_relay_client = true;
+        config.connection_backoff_base = Duration::from_secs(10);
+        config.connection_backoff_max = Duration::from_secs(1);
+        assert_eq!(
+            config.validate(),
+            Err(vec![
+                ConfigError::NoRelayPeers,
+                ConfigError::QuicDisabled(quic_address),
+                ConfigError::InvalidConnectionBackoff {
+                    base: Duration::from_secs(10),
+                    max: Duration::from_secs(1),
+                },
+            ])
+        );
+
+        config.relay_peers = vec![
+            relay_address.clone(),
+            relay_address.clone().with(Protocol::P2p(PeerId::random())),
+        ];
+        config.enable_quic = true;
+        config.connection_backoff_max = Duration::from_secs(10);
+        assert_eq!(
+            config.validate(),
+            Err(vec![ConfigError::RelayPeerWithoutPeerId(relay_address)])
+        );
     }
 }
@@ -24,11 +24,17 @@ use nimiq_network_interface::{network::CloseReason, peer_info::Services};
 use nimiq_time::{interval, sleep_until, Interval};
 use nimiq_utils::WakerExt as _;
 use parking_lot::RwLock;
-use rand::{seq::IteratorRandom, thread_rng};
+use rand::{seq::IteratorRandom, thread_rng, Rng};
 use void::Void;
 
-use super::Error;
-use crate::discovery::{handler, peer_contacts::PeerContactBook};
+use super::{
+    peer_score::{PeerScore, PeerScoreEvent},
+    Error,
+};
+use crate::{
+    discovery::{handler, peer_contacts::PeerContactBook},
+    PeerFilter,
+};
 
 /// Current state of connections and peers for connection limits
 #[derive(Clone, Debug)]
@@ -62,6 +68,10 @@ struct Config {
     retry_down_after: Duration,
     /// Interval duration for peer connections housekeeping
     housekeeping_interval: Duration,
+    /// Initial delay before re-dialing after a failed dial attempt
+    backoff_base: Duration,
+    /// Maximum delay before re-dialing after repeated failed dial attempts
+    backoff_max: Duration,
 }
 
 /// Connection Peer information
@@ -85,10 +95,23 @@ impl Default for Config {
             dialing_count_max: 3,
             retry_down_after: Duration::from_secs(60 * 10), // 10 minutes
             housekeeping_interval: Duration::from_secs(60 * 2), // 2 minutes
+            backoff_base: Duration::from_secs(1),
+            backoff_max: Duration::from_secs(60 * 5), // 5 minutes
         }
     }
 }
 
+/// Dial backoff state of a connection ID
+#[derive(Clone, Debug)]
+struct Backoff {
+    /// Number of consecutive failed dial attempts
+    attempts: u32,
+    /// Current (jittered) backoff delay
+    delay: Duration,
+    /// Instant before which no new dial attempt should be made
+    retry_at: Instant,
+}
+
 /// State of all of the connections the network has, like
 /// connected peers, peers being dialed, peers with failed dial attempts
 /// peers that are down or banned.
@@ -99,11 +122,12 @@ struct ConnectionState<T> {
     connected: BTreeMap<T, Option<Services>>,
     /// Set of connection IDs marked as banned.
     banned: BTreeSet<T>,
-    /// List of subsequent banned peers with their unban deadlines in ascending order.
+    /// Temporarily banned connection IDs with their unban deadlines in ascending order.
+    /// Permanently banned connection IDs have no entry.
     unban_deadlines: VecDeque<(T, Instant)>,
-    /// Deadline for first peer that can be unbanned.
-    unban_timeout: Option<(T, BoxFuture<'static, ()>)>,
-    /// The time that needs to pass to unban a banned peer.
+    /// Timer for the first deadline in `unban_deadlines`.
+    unban_timeout: Option<BoxFuture<'static, ()>>,
+    /// The time that needs to pass to unban a peer banned for misbehavior.
     ban_time: Duration,
     /// Set of connection IDs mark as failed.
     failed: BTreeMap<T, usize>,
@@ -115,6 +139,12 @@ struct ConnectionState<T> {
     max_failures: usize,
     /// Time after which a connection ID would be removed from IDs marked as down.
     retry_down_after: Duration,
+    /// Dial backoff state per connection ID with failed dial attempts.
+    backoff: BTreeMap<T, Backoff>,
+    /// Initial backoff delay after a failed dial attempt.
+    backoff_base: Duration,
+    /// Maximum backoff delay after repeated failed dial attempts.
+    backoff_max: Duration,
     /// Desired number of connections. When the number of connections is below this number,
     /// the `housekeeping` will retry the down nodes after 1s instead of `retry_down_after`.
     desired_connections: usize,
@@ -128,25 +158,23 @@ impl<T: Clone + Ord + Unpin> Future for ConnectionState<T> {
     type Output = ();
 
     fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
-        while let Some((id, sleep)) = self.unban_timeout.as_mut() {
-            if sleep.poll_unpin(cx).is_ready() {
+        while let Some(sleep) = self.unban_timeout.as_mut() {
+            if sleep.poll_unpin(cx).is_pending() {
+                break;
+            }
+
+            // Unban all peers whose deadline has been reached and schedule a sleep until
+            // the next peer can be unbanned.
+            let now = Instant::now();
+            while let Some((id, deadline)) = self.unban_deadlines.front() {
+                if *deadline > now {
+                    break;
+                }
                 let id = id.clone();
                 self.banned.remove(&id);
-                self.unban_timeout = None;
-
-                // While we are unbanning, try to unban more peers if their deadline also have been reached.
-                // Otherwise schedule a sleep until for the first peer that can be unbanned.
-                while let Some((next_id, deadline)) = self.unban_deadlines.pop_front() {
-                    if Instant::now() >= deadline {
-                        self.banned.remove(&next_id);
-                    } else {
-                        self.unban_timeout = Some((next_id, Box::pin(sleep_until(deadline))));
-                        break;
-                    }
-                }
-            } else {
-                break;
+                self.unban_deadlines.pop_front();
             }
+            self.schedule_unban_timeout();
         }
 
         if self.unban_timeout.is_none() {
@@ -164,6 +192,8 @@ impl<T: Clone + Ord> ConnectionState<T> {
         desired_connections: usize,
         required_services: Services,
         ban_time: Duration,
+        backoff_base: Duration,
+        backoff_max: Duration,
     ) -> Self {
         Self {
             dialing: BTreeSet::new(),
@@ -176,6 +206,9 @@ impl<T: Clone + Ord> ConnectionState<T> {
             down: BTreeMap::new(),
             max_failures,
             retry_down_after,
+            backoff: BTreeMap::new(),
+            backoff_base,
+            backoff_max,
             desired_connections,
             required_services,
             waker: None,
@@ -194,6 +227,7 @@ impl<T: Clone + Ord> ConnectionState<T> {
         self.dialing.remove(&id);
         self.failed.remove(&id);
         self.down.remove(&id);
+        self.backoff.remove(&id);
         self.connected.insert(id, services);
     }
 
@@ -203,22 +237,63 @@ impl<T: Clone + Ord> ConnectionState<T> {
         self.connected.remove(&id);
     }
 
-    /// Marks a connection ID as banned. The connection ID will be also removed
-    /// from the IDs marked as down or failed.
+    /// Marks a connection ID as banned for the default ban time. The connection ID will be
+    /// also removed from the IDs marked as down or failed.
     fn mark_banned(&mut self, id: T) {
+        self.mark_banned_for(id, Some(self.ban_time));
+    }
+
+    /// Marks a connection ID as banned for the given duration, or permanently if the duration
+    /// is `None`. A previous ban of the connection ID is replaced.
+    fn mark_banned_for(&mut self, id: T, duration: Option<Duration>) {
         self.failed.remove(&id);
         self.down.remove(&id);
         self.banned.insert(id.clone());
 
-        let unban_deadline = Instant::now() + self.ban_time;
-        // If no peer is scheduled to be unbanned, put is as the first-to-be unbanned peer.
-        // Otherwise queue the peer such that it will be scheduled later when the peer is the first in line.
-        if self.unban_timeout.is_none() {
-            self.unban_timeout = Some((id, Box::pin(sleep_until(unban_deadline))));
-            self.waker.wake();
-        } else {
-            self.unban_deadlines.push_back((id.clone(), unban_deadline));
+        self.unban_deadlines
+            .retain(|(banned_id, _)| *banned_id != id);
+        if let Some(duration) = duration {
+            let unban_deadline = Instant::now() + duration;
+            let index = self
+                .unban_deadlines
+                .partition_point(|(_, deadline)| *deadline <= unban_deadline);
+            self.unban_deadlines.insert(index, (id, unban_deadline));
         }
+        self.schedule_unban_timeout();
+        self.waker.wake();
+    }
+
+    /// Lifts the ban of a connection ID. Returns whether the connection ID was banned.
+    fn unban(&mut self, id: &T) -> bool {
+        self.unban_deadlines
+            .retain(|(banned_id, _)| banned_id != id);
+        self.schedule_unban_timeout();
+        self.waker.wake();
+        self.banned.remove(id)
+    }
+
+    /// Returns the banned connection IDs with their unban deadlines. Permanently banned
+    /// connection IDs have no deadline.
+    fn banned(&self) -> Vec<(T, Option<Instant>)> {
+        self.banned
+            .iter()
+            .map(|id| {
+                let deadline = self
+                    .unban_deadlines
+                    .iter()
+                    .find(|(banned_id, _)| banned_id == id)
+                    .map(|(_, deadline)| *deadline);
+                (id.clone(), deadline)
+            })
+            .collect()
+    }
+
+    /// Schedules the unban timer for the first unban deadline.
+    fn schedule_unban_timeout(&mut self) {
+        self.unban_timeout = self
+            .unban_deadlines
+            .front()
+            .map(|(_, deadline)| Box::pin(sleep_until(*deadline)) as BoxFuture<'static, ()>);
     }
 
     /// Returns whether a connection ID is banned
@@ -240,6 +315,10 @@ impl<T: Clone + Ord> ConnectionState<T> {
             return;
         }
 
+        if dialing {
+            self.increase_backoff(id.clone());
+        }
+
         // Peer is already marked as down. There is no point in incrementing the
         // number of failed attempts
         if self.down.contains_key(&id) {
@@ -256,6 +335,37 @@ impl<T: Clone + Ord> ConnectionState<T> {
         }
     }
 
+    /// Increases the dial backoff of a connection ID after a failed dial attempt.
+    ///
+    /// The delay grows exponentially from `backoff_base` with every consecutive failure
+    /// and is capped at `backoff_max`. A random jitter of up to half of the delay is
+    /// subtracted, such that peers failing at the same time are not re-dialed at once.
+    fn increase_backoff(&mut self, id: T) {
+        let attempts = self
+            .backoff
+            .get(&id)
+            .map_or(1, |backoff| backoff.attempts.saturating_add(1));
+        let delay = self
+            .backoff_base
+            .saturating_mul(2u32.saturating_pow(attempts - 1))
+            .min(self.backoff_max);
+        let delay = delay / 2 + delay.mul_f64(thread_rng().gen_range(0.0..=0.5));
+
+        self.backoff.insert(
+            id,
+            Backoff {
+                attempts,
+                delay,
+                retry_at: Instant::now() + delay,
+            },
+        );
+    }
+
+    /// Returns the current dial backoff delay of a connection ID, if any.
+    fn backoff_delay(&self, id: &T) -> Option<Duration> {
+        self.backoff.get(id).map(|backoff| backoff.delay)
+    }
+
     /// Marks a connection ID as down
     ///
     /// If the connection was into the failed set, it will be removed from such
@@ -271,6 +381,10 @@ impl<T: Clone + Ord> ConnectionState<T> {
             && !self.connected.contains_key(id)
             && !self.down.contains_key(id)
             && !self.banned.contains(id)
+            && self
+                .backoff
+                .get(id)
+                .map_or(true, |backoff| backoff.retry_at <= Instant::now())
     }
 
     /// Returns the number of connections being dialed
@@ -309,6 +423,12 @@ impl<T: Clone + Ord> ConnectionState<T> {
         };
         self.down
             .retain(|_, down_since| down_since.elapsed() < retry_down_after);
+
+        // Forget the backoff of connection IDs that haven't failed again for a while.
+        let now = Instant::now();
+        let backoff_max = self.backoff_max;
+        self.backoff
+            .retain(|_, backoff| backoff.retry_at + backoff_max > now);
     }
 }
 
@@ -350,6 +470,15 @@ pub struct Behaviour {
     /// The set of services that this peer requires.
     required_services: Services,
 
+    /// Set of peers that are never evicted to make room for other peers.
+    protected_peers: HashSet<PeerId>,
+
+    /// Optional filter for the peers we are allowed to connect to
+    peer_filter: Option<PeerFilter>,
+
+    /// Scores of the connected peers. Peers without an entry have the default score.
+    peer_scores: HashMap<PeerId, PeerScore>,
+
     /// Connection state per Peer ID
     peer_ids: ConnectionState<PeerId>,
 
@@ -362,6 +491,12 @@ pub struct Behaviour {
     /// Tells whether the connection pool behaviour is active or not
     active: bool,
 
+    /// Tells whether the network is shutting down. If so, no new connections are accepted.
+    shutting_down: bool,
+
+    /// Tells whether the discovery is paused. If so, no new peers or seeds are dialed.
+    discovery_paused: bool,
+
     /// Counters per connection limits
     limits: Limits,
 
@@ -382,6 +517,11 @@ impl Behaviour {
         seeds: Vec<Multiaddr>,
         required_services: Services,
         desired_peer_count: usize,
+        protected_peers: HashSet<PeerId>,
+        backoff_base: Duration,
+        backoff_max: Duration,
+        peer_filter: Option<PeerFilter>,
+        max_concurrent_dials: usize,
     ) -> Self {
         let limits = Limits {
             ip_count: HashMap::new(),
@@ -390,6 +530,9 @@ impl Behaviour {
         };
         let config = Config {
             desired_peer_count,
+            backoff_base,
+            backoff_max,
+            dialing_count_max: max_concurrent_dials,
             ..Default::default()
         };
         let housekeeping_timer = interval(config.housekeeping_interval);
@@ -399,12 +542,17 @@ impl Behaviour {
             own_peer_id,
             seeds,
             required_services,
+            protected_peers,
+            peer_filter,
+            peer_scores: HashMap::new(),
             peer_ids: ConnectionState::new(
                 2,
                 config.retry_down_after,
                 desired_peer_count,
                 required_services,
                 Duration::from_secs(60 * 10), // 10 minutes
+                config.backoff_base,
+                config.backoff_max,
             ),
             addresses: ConnectionState::new(
                 4,
@@ -412,9 +560,13 @@ impl Behaviour {
                 desired_peer_count,
                 required_services,
                 Duration::from_secs(60 * 10), // 10 minutes
+                config.backoff_base,
+                config.backoff_max,
             ),
             actions: VecDeque::new(),
             active: false,
+            shutting_down: false,
+            discovery_paused: false,
             limits,
             config,
             waker: None,
@@ -469,6 +621,7 @@ impl Behaviour {
         // Note: when counting dialing IDs we have to account for peer IDs and
         // addresses (seeds may only be in the `addresses` set).
         if self.active
+            && !self.discovery_paused
             && self.peer_ids.num_connected(true) < self.config.desired_peer_count
             && self.peer_ids.num_dialing() + self.addresses.num_dialing()
                 < self.config.dialing_count_max
@@ -496,6 +649,67 @@ impl Behaviour {
         self.waker.wake();
     }
 
+    /// Returns the current dial backoff delay of a peer, if its last dial attempts failed.
+    pub fn backoff_delay(&self, peer_id: &PeerId) -> Option<Duration> {
+        self.peer_ids.backoff_delay(peer_id)
+    }
+
+    /// Bans a peer for the given duration, or permanently if the duration is `None`, and closes
+    /// all connections to it. Banned peers are neither dialed nor accepted until the ban expires.
+    pub fn ban_peer(&mut self, peer_id: PeerId, duration: Option<Duration>) {
+        self.peer_ids.mark_banned_for(peer_id, duration);
+        debug!(%peer_id, ?duration, "Banned peer");
+
+        self.actions.push_back(ToSwarm::CloseConnection {
+            peer_id,
+            connection: CloseConnection::All,
+        });
+        self.waker.wake();
+    }
+
+    /// Lifts the ban of a peer. Returns whether the peer was banned.
+    pub fn unban_peer(&mut self, peer_id: &PeerId) -> bool {
+        let was_banned = self.peer_ids.unban(peer_id);
+        if was_banned {
+            debug!(%peer_id, "Unbanned peer");
+        }
+        was_banned
+    }
+
+    /// Returns the banned peers with the remaining time of their ban. Permanently banned
+    /// peers have no remaining time.
+    pub fn banned_peers(&self) -> Vec<(PeerId, Option<Duration>)> {
+        let now = Instant::now();
+        self.peer_ids
+            .banned()
+            .into_iter()
+            .map(|(peer_id, deadline)| {
+                (
+                    peer_id,
+                    deadline.map(|deadline| deadline.saturating_duration_since(now)),
+                )
+            })
+            .collect()
+    }
+
+    /// Updates the score of a connected peer. Events of peers that are not connected are ignored.
+    pub(crate) fn report_peer(&mut self, peer_id: PeerId, event: PeerScoreEvent) {
+        if self.peer_ids.connected.contains_key(&peer_id) {
+            self.peer_scores.entry(peer_id).or_default().apply(event);
+        }
+    }
+
+    /// Returns the score of a peer, or `None` if the peer is not connected.
+    pub fn peer_score(&self, peer_id: &PeerId) -> Option<i32> {
+        self.peer_ids.connected.contains_key(peer_id).then(|| {
+            self.peer_scores
+                .get(peer_id)
+                .copied()
+                .unwrap_or_default()
+                .value()
+        })
+    }
+
     /// Tells the behaviour to start connecting to other peers.
     pub fn start_connecting(&mut self) {
         self.active = true;
@@ -509,6 +723,23 @@ impl Behaviour {
         self.active = false;
     }
 
+    /// Pauses or resumes dialing peers and seeds to maintain the desired peer count.
+    /// Existing connections and explicitly requested dials are not affected.
+    pub fn set_discovery_paused(&mut self, paused: bool) {
+        self.discovery_paused = paused;
+        if !paused {
+            self.maintain_peers();
+            self.waker.wake();
+        }
+    }
+
+    /// Tells the behaviour to stop connecting to other peers and to deny any
+    /// new inbound or outbound connection, as the network is shutting down.
+    pub fn shutdown(&mut self) {
+        self.stop_connecting();
+        self.shutting_down = true;
+    }
+
     /// Closes a peer connection with a reason
     ///
     /// This will take actions depending on the close reason. For instance:
@@ -531,7 +762,8 @@ impl Behaviour {
     fn choose_peers_to_dial(&self) -> Vec<PeerId> {
         let num_peers = usize::min(
             self.config.desired_peer_count - self.peer_ids.num_connected(true),
-            self.config.dialing_count_max - self.peer_ids.num_dialing(),
+            self.config.dialing_count_max
+                - (self.peer_ids.num_dialing() + self.addresses.num_dialing()),
         );
         let contacts = self.contacts.read();
         let own_contact = contacts.get_own_contact();
@@ -543,6 +775,7 @@ impl Behaviour {
                 let peer_id = contact.peer_id();
                 if peer_id != own_peer_id
                     && self.peer_ids.can_dial(peer_id)
+                    && self.is_allowed(peer_id)
                     && contact.addresses().count() > 0
                 {
                     Some(*peer_id)
@@ -571,6 +804,7 @@ impl Behaviour {
                 let peer_id = contact.peer_id();
                 if peer_id != own_peer_id
                     && self.peer_ids.can_dial(peer_id)
+                    && self.is_allowed(peer_id)
                     && contact.addresses().count() > 0
                 {
                     Some(*peer_id)
@@ -581,6 +815,35 @@ impl Behaviour {
             .choose_multiple(&mut thread_rng(), num_peers)
     }
 
+    /// Returns whether the peer filter allows connections with the given peer.
+    fn is_allowed(&self, peer_id: &PeerId) -> bool {
+        self.peer_filter
+            .as_ref()
+            .map_or(true, |filter| filter.allows(peer_id))
+    }
+
+    /// Chooses a connected peer that can be evicted to make room for a new connection.
+    /// One of the lowest-scoring peers is chosen. Protected peers are never chosen.
+    fn choose_peer_to_evict(&self) -> Option<PeerId> {
+        let candidates: Vec<(PeerId, i32)> = self
+            .peer_ids
+            .connected
+            .keys()
+            .filter(|peer_id| !self.protected_peers.contains(peer_id))
+            .map(|peer_id| {
+                let score = self.peer_scores.get(peer_id).copied().unwrap_or_default();
+                (*peer_id, score.value())
+            })
+            .collect();
+
+        let lowest_score = candidates.iter().map(|(_, score)| *score).min()?;
+        candidates
+            .into_iter()
+            .filter(|(_, score)| *score == lowest_score)
+            .map(|(peer_id, _)| peer_id)
+            .choose(&mut thread_rng())
+    }
+
     fn choose_seeds_to_dial(&self) -> Vec<Multiaddr> {
         // We prefer to connect to non-seed peers. Thus, we only choose any seeds here if we're
         // not already dialing any peers and at most one seed at a time.
@@ -742,6 +1005,7 @@ impl Behaviour {
 
         self.addresses.mark_closed(address.clone());
         self.peer_ids.mark_closed(*peer_id);
+        self.peer_scores.remove(peer_id);
 
         // If the connection was closed for any reason, don't dial the peer again.
         self.peer_ids.mark_down(*peer_id);
@@ -874,11 +1138,25 @@ impl NetworkBehaviour for Behaviour {
         _addresses: &[Multiaddr],
         _effective_role: Endpoint,
     ) -> Result<Vec<Multiaddr>, ConnectionDenied> {
+        if self.shutting_down {
+            return Err(ConnectionDenied::new(Error::ShuttingDown));
+        }
+
         let peer_id = match maybe_peer {
             None => return Ok(vec![]),
             Some(peer) => peer,
         };
 
+        if self.peer_ids.is_banned(peer_id) {
+            debug!(%peer_id, "Denying dial, peer is banned");
+            return Err(ConnectionDenied::new(Error::BannedPeer));
+        }
+
+        if !self.is_allowed(&peer_id) {
+            debug!(%peer_id, "Denying dial, peer is rejected by the peer filter");
+            return Err(ConnectionDenied::new(Error::PeerFiltered));
+        }
+
         Ok(self
             .contacts
             .read()
@@ -892,6 +1170,11 @@ impl NetworkBehaviour for Behaviour {
         _local_addr: &Multiaddr,
         remote_addr: &Multiaddr,
     ) -> Result<(), ConnectionDenied> {
+        if self.shutting_down {
+            debug!(%remote_addr, "Denying connection, network is shutting down");
+            return Err(ConnectionDenied::new(Error::ShuttingDown));
+        }
+
         if let Some(outer_protocol_address) = handler::outer_protocol_address(remote_addr) {
             if self.addresses.is_banned(outer_protocol_address) {
                 debug!(%remote_addr, "Address is banned");
@@ -934,8 +1217,12 @@ impl NetworkBehaviour for Behaviour {
             }
         }
 
-        // Check for the maximum peer count limit
-        if self.config.peer_count_max < self.limits.peer_count.saturating_add(1) {
+        // Check for the maximum peer count limit. Protected peers can only be identified by
+        // their peer ID, so if there are any, the check is deferred until the connection is
+        // established.
+        if self.protected_peers.is_empty()
+            && self.config.peer_count_max < self.limits.peer_count.saturating_add(1)
+        {
             debug!(
                 connections = self.limits.peer_count,
                 "Max peer connections limit reached"
@@ -960,17 +1247,66 @@ impl NetworkBehaviour for Behaviour {
             return Err(ConnectionDenied::new(Error::BannedPeer));
         }
 
+        if !self.is_allowed(&peer) {
+            debug!(peer_id=%peer, "Peer is rejected by the peer filter");
+            return Err(ConnectionDenied::new(Error::PeerFiltered));
+        }
+
+        // Check for the maximum peer count limit if it couldn't be checked while the connection
+        // was pending, i.e. if there are protected peers.
+        if self.config.peer_count_max < self.limits.peer_count.saturating_add(1) {
+            if !self.protected_peers.contains(&peer) {
+                debug!(
+                    connections = self.limits.peer_count,
+                    "Max peer connections limit reached"
+                );
+                return Err(ConnectionDenied::new(Error::MaxPeerConnectionsReached));
+            }
+
+            // Make room for the protected peer by evicting an unprotected one.
+            if let Some(evicted_peer_id) = self.choose_peer_to_evict() {
+                debug!(
+                    peer_id = %peer,
+                    %evicted_peer_id,
+                    "Max peer connections limit reached, evicting peer to admit protected peer"
+                );
+                self.actions.push_back(ToSwarm::CloseConnection {
+                    peer_id: evicted_peer_id,
+                    connection: CloseConnection::All,
+                });
+                self.waker.wake();
+            } else {
+                warn!(
+                    peer_id = %peer,
+                    connections = self.limits.peer_count,
+                    "Max peer connections limit reached and all connected peers are protected"
+                );
+            }
+        }
+
         Ok(dummy::ConnectionHandler)
     }
 
     fn handle_established_outbound_connection(
         &mut self,
         _connection_id: ConnectionId,
-        _peer: PeerId,
+        peer: PeerId,
         _addr: &Multiaddr,
         _role_override: Endpoint,
         _port_use: PortUse,
     ) -> Result<THandler<Self>, ConnectionDenied> {
+        // Dials to addresses don't know the peer ID in advance, so the ban and the filter
+        // need to be checked here as well.
+        if self.peer_ids.is_banned(peer) {
+            debug!(peer_id=%peer, "Peer is banned");
+            return Err(ConnectionDenied::new(Error::BannedPeer));
+        }
+
+        if !self.is_allowed(&peer) {
+            debug!(peer_id=%peer, "Peer is rejected by the peer filter");
+            return Err(ConnectionDenied::new(Error::PeerFiltered));
+        }
+
         Ok(dummy::ConnectionHandler)
     }
 
@@ -999,16 +1335,77 @@ impl NetworkBehaviour for Behaviour {
 
 #[cfg(test)]
 mod tests {
-    use std::task::Context;
+    use std::{collections::HashSet, sync::Arc, task::Context};
 
     use futures::{task::noop_waker, FutureExt};
     use instant::Duration;
-    use libp2p::PeerId;
+    use libp2p::{identity::Keypair, swarm::ToSwarm, PeerId};
     use nimiq_network_interface::peer_info::Services;
     use nimiq_test_log::test;
     use nimiq_time::sleep;
+    use parking_lot::RwLock;
+
+    use super::Behaviour;
+    use crate::{
+        connection_pool::behaviour::ConnectionState,
+        discovery::peer_contacts::{PeerContact, PeerContactBook, SignedPeerContact},
+    };
+
+    fn peer_contact(n: usize) -> SignedPeerContact {
+        let keypair = Keypair::generate_ed25519();
+        let mut peer_contact = PeerContact::new(
+            [format!("/dns/test{}.local/tcp/443/wss", n).parse().unwrap()],
+            keypair.public(),
+            Services::FULL_BLOCKS,
+            None,
+        )
+        .unwrap();
+        peer_contact.set_current_time();
+        peer_contact.sign(&keypair)
+    }
+
+    fn num_dials(behaviour: &Behaviour) -> usize {
+        behaviour
+            .actions
+            .iter()
+            .filter(|action| matches!(action, ToSwarm::Dial { .. }))
+            .count()
+    }
+
+    #[test(tokio::test)]
+    async fn no_dials_while_discovery_is_paused() {
+        let own_contact = peer_contact(0);
+        let own_peer_id = own_contact.peer_id();
+        let contacts = Arc::new(RwLock::new(PeerContactBook::new(
+            own_contact,
+            false,
+            true,
+            true,
+        )));
+        contacts.write().insert(peer_contact(1));
+
+        let mut behaviour = Behaviour::new(
+            contacts,
+            own_peer_id,
+            vec!["/dns/seed.local/tcp/443/wss".parse().unwrap()],
+            Services::empty(),
+            4,
+            HashSet::new(),
+            Duration::from_secs(1),
+            Duration::from_secs(10),
+            None,
+            4,
+        );
+
+        behaviour.set_discovery_paused(true);
+        behaviour.start_connecting();
+        behaviour.maintain_peers();
+        assert_eq!(num_dials(&behaviour), 0);
 
-    use crate::connection_pool::behaviour::ConnectionState;
+        // Resuming dials the known peer. The seed isn't dialed while a peer is being dialed.
+        behaviour.set_discovery_paused(false);
+        assert_eq!(num_dials(&behaviour), 1);
+    }
 
     #[test(tokio::test)]
     async fn unban_peers_after_timeout() {
@@ -1018,6 +1415,8 @@ mod tests {
             1,
             Services::empty(),
             Duration::from_secs(2), // Ban time: 2 seconds
+            Duration::from_secs(1),
+            Duration::from_secs(60),
         );
 
         let waker = noop_waker();
@@ -1052,4 +1451,79 @@ mod tests {
         // p2 and p3 should both be unbanned
         assert!(cs.banned.is_empty());
     }
+
+    #[test(tokio::test)]
+    async fn bans_with_custom_durations() {
+        let mut cs = ConnectionState::new(
+            30,
+            Duration::from_secs(30),
+            1,
+            Services::empty(),
+            Duration::from_secs(60),
+            Duration::from_secs(1),
+            Duration::from_secs(60),
+        );
+
+        let waker = noop_waker();
+        let cx = &mut Context::from_waker(&waker);
+
+        let p1 = PeerId::random();
+        let p2 = PeerId::random();
+        let p3 = PeerId::random();
+
+        cs.mark_banned_for(p1, Some(Duration::from_secs(2)));
+        cs.mark_banned_for(p2, Some(Duration::from_secs(1)));
+        cs.mark_banned_for(p3, None);
+        assert!(!cs.can_dial(&p3));
+
+        let banned = cs.banned();
+        assert_eq!(banned.len(), 3);
+        assert!(banned.contains(&(p3, None)));
+
+        sleep(Duration::from_millis(1500)).await;
+        let _ = cs.poll_unpin(cx);
+
+        // The shorter ban expires first, even though it was issued later.
+        assert!(cs.is_banned(p1));
+        assert!(!cs.is_banned(p2));
+
+        assert!(cs.unban(&p1));
+        assert!(!cs.unban(&p2));
+        assert!(cs.is_banned(p3));
+        assert!(!cs.is_banned(p1));
+    }
+
+    #[test]
+    fn backoff_grows_and_resets() {
+        let mut cs = ConnectionState::new(
+            30,
+            Duration::from_secs(30),
+            1,
+            Services::empty(),
+            Duration::from_secs(2),
+            Duration::from_secs(1),  // Backoff base: 1 second
+            Duration::from_secs(10), // Backoff max: 10 seconds
+        );
+
+        let p1 = PeerId::random();
+
+        let mut max_delays = vec![];
+        for _ in 0..6 {
+            cs.mark_dialing(p1);
+            cs.mark_failed(p1);
+            max_delays.push(cs.backoff_delay(&p1).unwrap());
+            assert!(!cs.can_dial(&p1));
+        }
+
+        // Delays are jittered down to at most half of the exponential delay, capped at the max.
+        let expected = [1, 2, 4, 8, 10, 10];
+        for (delay, expected) in max_delays.iter().zip(expected) {
+            let expected = Duration::from_secs(expected);
+            assert!(*delay <= expected);
+            assert!(*delay >= expected / 2);
+        }
+
+        cs.mark_connected(p1, None);
+        assert_eq!(cs.backoff_delay(&p1), None);
+    }
 }
@@ -1,5 +1,7 @@
 pub mod behaviour;
+mod peer_score;
 pub use behaviour::Behaviour;
+pub(crate) use peer_score::PeerScoreEvent;
 use thiserror::Error;
 
 /// Connection Pool errors
@@ -24,4 +26,12 @@ pub enum Error {
     ///Maximum peers connections per IP has been reached
     #[error("Maximum peers connections per IP has been reached")]
     MaxPeerPerIPConnectionsReached,
+
+    /// The peer is rejected by the configured peer filter
+    #[error("Peer is rejected by the peer filter")]
+    PeerFiltered,
+
+    /// The network is shutting down and doesn't accept new connections
+    #[error("The network is shutting down")]
+    ShuttingDown,
 }
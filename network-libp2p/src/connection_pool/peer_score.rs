@@ -0,0 +1,85 @@
+use std::time::Duration;
+
+/// Events that change the score of a connected peer.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum PeerScoreEvent {
+    /// The peer answered one of our requests.
+    RequestSucceeded,
+    /// One of our requests to the peer failed, e.g. because it timed out.
+    RequestFailed,
+    /// The peer exceeded the rate limit of a request type.
+    RateLimitExceeded,
+    /// The peer relayed a message that was rejected as invalid.
+    InvalidMessage,
+    /// The peer sent data that doesn't follow the protocol, e.g. an unparsable request.
+    ProtocolViolation,
+    /// A ping to the peer completed with the given round trip time.
+    Latency(Duration),
+}
+
+/// Score of a connected peer, accumulated from the events observed while connected to it.
+///
+/// The score starts at zero and is clamped to `[MIN, MAX]`, such that a long history of good
+/// behavior can't outweigh a burst of misbehavior and vice versa.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub(crate) struct PeerScore(i32);
+
+impl PeerScore {
+    pub(crate) const MIN: i32 = -1000;
+    pub(crate) const MAX: i32 = 1000;
+
+    /// Round trip times up to this duration increase the score.
+    const LOW_LATENCY: Duration = Duration::from_millis(200);
+    /// Round trip times of at least this duration decrease the score.
+    const HIGH_LATENCY: Duration = Duration::from_secs(1);
+
+    pub(crate) fn value(&self) -> i32 {
+        self.0
+    }
+
+    /// Updates the score according to the given event.
+    pub(crate) fn apply(&mut self, event: PeerScoreEvent) {
+        let delta = match event {
+            PeerScoreEvent::RequestSucceeded => 1,
+            PeerScoreEvent::RequestFailed => -5,
+            PeerScoreEvent::RateLimitExceeded => -20,
+            PeerScoreEvent::InvalidMessage | PeerScoreEvent::ProtocolViolation => -50,
+            PeerScoreEvent::Latency(rtt) if rtt <= Self::LOW_LATENCY => 1,
+            PeerScoreEvent::Latency(rtt) if rtt >= Self::HIGH_LATENCY => -2,
+            PeerScoreEvent::Latency(_) => 0,
+        };
+        self.0 = (self.0 + delta).clamp(Self::MIN, Self::MAX);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use nimiq_test_log::test;
+
+    use super::*;
+
+    #[test]
+    fn it_accumulates_and_clamps_scores() {
+        let mut score = PeerScore::default();
+        assert_eq!(score.value(), 0);
+
+        score.apply(PeerScoreEvent::RequestSucceeded);
+        score.apply(PeerScoreEvent::Latency(Duration::from_millis(50)));
+        score.apply(PeerScoreEvent::Latency(Duration::from_millis(500)));
+        assert_eq!(score.value(), 2);
+
+        score.apply(PeerScoreEvent::RequestFailed);
+        score.apply(PeerScoreEvent::Latency(Duration::from_secs(2)));
+        assert_eq!(score.value(), -5);
+
+        for _ in 0..100 {
+            score.apply(PeerScoreEvent::InvalidMessage);
+        }
+        assert_eq!(score.value(), PeerScore::MIN);
+
+        for _ in 0..10_000 {
+            score.apply(PeerScoreEvent::RequestSucceeded);
+        }
+        assert_eq!(score.value(), PeerScore::MAX);
+    }
+}
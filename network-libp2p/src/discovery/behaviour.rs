@@ -1,6 +1,10 @@
 use std::{
     collections::{HashSet, VecDeque},
-    sync::Arc,
+    mem,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
     task::{Context, Poll},
     time::Duration,
 };
@@ -21,7 +25,7 @@ use nimiq_time::{interval, Interval};
 use parking_lot::RwLock;
 
 use super::{
-    handler::{Handler, HandlerOutEvent},
+    handler::{Error as HandlerError, Handler, HandlerOutEvent},
     peer_contacts::{PeerContact, PeerContactBook},
 };
 
@@ -54,6 +58,10 @@ pub struct Config {
 
     /// Only secure websocket connections
     pub only_secure_ws_connections: bool,
+
+    /// Maximum time the handshake with a peer may take. Connections to peers that don't complete
+    /// the handshake in time are closed.
+    pub handshake_timeout: Duration,
 }
 
 impl Config {
@@ -72,6 +80,7 @@ impl Config {
             house_keeping_interval: Duration::from_secs(60),
             keep_alive: true,
             only_secure_ws_connections,
+            handshake_timeout: Duration::from_secs(10),
         }
     }
 }
@@ -84,6 +93,15 @@ pub enum Event {
         peer_contact: PeerContact,
     },
     Update,
+    /// The peer didn't complete the handshake within the configured handshake timeout.
+    HandshakeTimeout {
+        peer_id: PeerId,
+    },
+    /// The peer doesn't support our version of the discovery protocol.
+    IncompatiblePeer {
+        peer_id: PeerId,
+        our_version: &'static str,
+    },
 }
 
 type DiscoveryToSwarm = ToSwarm<Event, ()>;
@@ -115,6 +133,13 @@ pub struct Behaviour {
 
     /// Timer to do house-keeping in the peer address book.
     house_keeping_timer: Interval,
+
+    /// Whether discovery is paused. This flag is shared with all connection handlers.
+    paused: Arc<AtomicBool>,
+
+    /// Our own addresses as observed by our peers. They are reported to the swarm as external
+    /// address candidates again when rediscovering our external addresses.
+    observed_addresses: HashSet<Multiaddr>,
 }
 
 impl Behaviour {
@@ -139,9 +164,27 @@ impl Behaviour {
             peer_contact_book,
             events,
             house_keeping_timer,
+            paused: Arc::new(AtomicBool::new(false)),
+            observed_addresses: HashSet::new(),
         }
     }
 
+    /// Pauses the discovery. Existing connections are kept, but no peer contacts are exchanged
+    /// with connected peers until the discovery is resumed.
+    pub fn pause(&self) {
+        self.paused.store(true, Ordering::Relaxed);
+    }
+
+    /// Resumes a paused discovery.
+    pub fn resume(&self) {
+        self.paused.store(false, Ordering::Relaxed);
+    }
+
+    /// Returns whether the discovery is paused.
+    pub fn is_paused(&self) -> bool {
+        self.paused.load(Ordering::Relaxed)
+    }
+
     /// Adds addresses into our own contact within the peer contact book
     pub fn add_own_addresses(&self, addresses: Vec<Multiaddr>) {
         self.peer_contact_book
@@ -149,6 +192,42 @@ impl Behaviour {
             .add_own_addresses(addresses, &self.keypair)
     }
 
+    /// Records an address of ours as observed by a peer, such that it is reported as an external
+    /// address candidate again when rediscovering our addresses.
+    pub fn add_observed_address(&mut self, address: Multiaddr) {
+        self.observed_addresses.insert(address);
+    }
+
+    /// Replaces the addresses of our own contact by the given listen addresses and reports
+    /// the addresses observed by our peers to the swarm as external address candidates again.
+    /// Returns the addresses that were removed from our own contact and the observed addresses,
+    /// such that they can be probed again.
+    pub fn rediscover_own_addresses(&mut self, listen_addresses: Vec<Multiaddr>) -> Vec<Multiaddr> {
+        let own_addresses: Vec<Multiaddr> = {
+            let mut peer_contact_book = self.peer_contact_book.write();
+            let own_addresses: Vec<Multiaddr> = peer_contact_book
+                .get_own_contact()
+                .addresses()
+                .cloned()
+                .collect();
+            peer_contact_book.remove_own_addresses(own_addresses.clone(), &self.keypair);
+            peer_contact_book.add_own_addresses(listen_addresses.clone(), &self.keypair);
+            own_addresses
+                .into_iter()
+                .filter(|address| !listen_addresses.contains(address))
+                .collect()
+        };
+
+        for address in &self.observed_addresses {
+            self.events
+                .push_back(ToSwarm::NewExternalAddrCandidate(address.clone()));
+        }
+
+        let mut candidates = own_addresses;
+        candidates.extend(mem::take(&mut self.observed_addresses));
+        candidates
+    }
+
     /// Returns whether an address in `Multiaddr` format is a dialable websocket address
     pub fn is_address_dialable(&self, address: &Multiaddr) -> bool {
         self.peer_contact_book.read().is_address_dialable(address)
@@ -177,6 +256,7 @@ impl NetworkBehaviour for Behaviour {
             self.keypair.clone(),
             self.peer_contact_book(),
             remote_addr.clone(),
+            Arc::clone(&self.paused),
         ))
     }
 
@@ -194,6 +274,7 @@ impl NetworkBehaviour for Behaviour {
             self.keypair.clone(),
             self.peer_contact_book(),
             addr.clone(),
+            Arc::clone(&self.paused),
         ))
     }
 
@@ -284,14 +365,33 @@ impl NetworkBehaviour for Behaviour {
                 }
             }
             HandlerOutEvent::ObservedAddress { observed_address } => {
+                self.add_observed_address(observed_address.clone());
                 self.events
                     .push_back(ToSwarm::NewExternalAddrCandidate(observed_address));
             }
             HandlerOutEvent::Update => self.events.push_back(ToSwarm::GenerateEvent(Event::Update)),
-            HandlerOutEvent::Error(_) => self.events.push_back(ToSwarm::CloseConnection {
-                peer_id,
-                connection: CloseConnection::All,
-            }),
+            HandlerOutEvent::Error(error) => {
+                match error {
+                    HandlerError::HandshakeTimeout { state } => {
+                        debug!(%peer_id, ?state, "Discovery handshake timed out");
+                        self.events
+                            .push_back(ToSwarm::GenerateEvent(Event::HandshakeTimeout { peer_id }));
+                    }
+                    HandlerError::IncompatibleProtocol { our_version } => {
+                        debug!(%peer_id, our_version, "Peer doesn't support our discovery protocol");
+                        self.events
+                            .push_back(ToSwarm::GenerateEvent(Event::IncompatiblePeer {
+                                peer_id,
+                                our_version,
+                            }));
+                    }
+                    _ => {}
+                }
+                self.events.push_back(ToSwarm::CloseConnection {
+                    peer_id,
+                    connection: CloseConnection::All,
+                })
+            }
         }
     }
 }
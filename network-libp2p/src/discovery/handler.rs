@@ -1,6 +1,9 @@
 use std::{
     pin::Pin,
-    sync::Arc,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
     task::{Context, Poll, Waker},
     time::Duration,
 };
@@ -15,7 +18,7 @@ use libp2p::{
         handler::{
             ConnectionEvent, DialUpgradeError, FullyNegotiatedInbound, FullyNegotiatedOutbound,
         },
-        ConnectionHandler, ConnectionHandlerEvent, Stream, SubstreamProtocol,
+        ConnectionHandler, ConnectionHandlerEvent, Stream, StreamUpgradeError, SubstreamProtocol,
     },
     Multiaddr, PeerId,
 };
@@ -34,6 +37,7 @@ use super::{
     peer_contacts::{PeerContactBook, SignedPeerContact},
     protocol::{ChallengeNonce, DiscoveryMessage, DiscoveryProtocol},
 };
+use crate::DISCOVERY_PROTOCOL;
 
 #[derive(Debug)]
 pub enum HandlerOutEvent {
@@ -65,8 +69,11 @@ pub enum Error {
         message: DiscoveryMessage,
     },
 
-    #[error("Timeout reached for {state:?}: peer might not be responding")]
-    StateTransitionTimeout { state: HandlerState },
+    #[error("Peer doesn't support our discovery protocol version {our_version}")]
+    IncompatibleProtocol { our_version: &'static str },
+
+    #[error("Handshake timed out in {state:?}: peer might not be responding")]
+    HandshakeTimeout { state: HandlerState },
 
     #[error("Mismatch for genesis hash: Expected {expected}, but received {received}")]
     GenesisHashMismatch {
@@ -163,16 +170,22 @@ pub struct Handler {
 
     /// Waker used when opening a substream.
     waker: Option<Waker>,
+
+    /// An error that occurred outside of `poll` and still needs to be reported.
+    pending_error: Option<Error>,
+
+    /// Whether discovery is paused. While paused, no peer contacts are exchanged with the peer.
+    paused: Arc<AtomicBool>,
 }
 
 impl Handler {
-    const STATE_TRANSITION_TIMEOUT: Duration = Duration::from_millis(3000);
     pub fn new(
         peer_id: PeerId,
         config: Config,
         keypair: Keypair,
         peer_contact_book: Arc<RwLock<PeerContactBook>>,
         peer_address: Multiaddr,
+        paused: Arc<AtomicBool>,
     ) -> Self {
         if let Some(peer_contact) = peer_contact_book.write().get(&peer_id) {
             if let Some(outer_protocol_address) = outer_protocol_address(&peer_address) {
@@ -196,6 +209,8 @@ impl Handler {
             inbound: None,
             outbound: None,
             waker: None,
+            pending_error: None,
+            paused,
         }
     }
 
@@ -241,7 +256,6 @@ impl Handler {
     fn check_initialized(&mut self) {
         if self.inbound.is_some() && self.outbound.is_some() {
             self.state = HandlerState::SendHandshake;
-            self.state_timeout = Some(Delay::new(Self::STATE_TRANSITION_TIMEOUT));
 
             self.waker
                 .take()
@@ -311,6 +325,19 @@ impl ConnectionHandler for Handler {
                 self.outbound = Some(protocol);
                 self.check_initialized();
             }
+            ConnectionEvent::DialUpgradeError(DialUpgradeError {
+                error: StreamUpgradeError::NegotiationFailed,
+                ..
+            }) => {
+                // The peer doesn't speak our version of the discovery protocol, so the handshake
+                // can never succeed.
+                self.pending_error = Some(Error::IncompatibleProtocol {
+                    our_version: DISCOVERY_PROTOCOL,
+                });
+                if let Some(waker) = &self.waker {
+                    waker.wake_by_ref();
+                }
+            }
             ConnectionEvent::DialUpgradeError(DialUpgradeError { error, .. }) => {
                 error!(%error, "inject_dial_upgrade_error");
             }
@@ -329,11 +356,17 @@ impl ConnectionHandler for Handler {
         cx: &mut Context,
     ) -> Poll<ConnectionHandlerEvent<Self::OutboundProtocol, (), HandlerOutEvent>> {
         loop {
-            // Check if we hit the state transition timeout
+            if let Some(error) = self.pending_error.take() {
+                return Poll::Ready(ConnectionHandlerEvent::NotifyBehaviour(
+                    HandlerOutEvent::Error(error),
+                ));
+            }
+
+            // Check if we hit the handshake timeout
             if let Some(ref mut state_timeout) = self.state_timeout {
                 if state_timeout.poll_unpin(cx).is_ready() {
                     return Poll::Ready(ConnectionHandlerEvent::NotifyBehaviour(
-                        HandlerOutEvent::Error(Error::StateTransitionTimeout { state: self.state }),
+                        HandlerOutEvent::Error(Error::HandshakeTimeout { state: self.state }),
                     ));
                 }
             }
@@ -360,8 +393,9 @@ impl ConnectionHandler for Handler {
             match self.state {
                 HandlerState::Init => {
                     // Request outbound substream
+                    // The whole handshake needs to complete within the handshake timeout.
                     self.state = HandlerState::OpenSubstream;
-                    self.state_timeout = Some(Delay::new(Self::STATE_TRANSITION_TIMEOUT));
+                    self.state_timeout = Some(Delay::new(self.config.handshake_timeout));
 
                     return Poll::Ready(ConnectionHandlerEvent::OutboundSubstreamRequest {
                         protocol: SubstreamProtocol::new(DiscoveryProtocol, ()),
@@ -396,7 +430,6 @@ impl ConnectionHandler for Handler {
                     }
 
                     self.state = HandlerState::ReceiveHandshake;
-                    self.state_timeout = Some(Delay::new(Self::STATE_TRANSITION_TIMEOUT));
                 }
 
                 HandlerState::ReceiveHandshake => {
@@ -461,8 +494,6 @@ impl ConnectionHandler for Handler {
                                     }
 
                                     self.state = HandlerState::ReceiveHandshakeAck;
-                                    self.state_timeout =
-                                        Some(Delay::new(Self::STATE_TRANSITION_TIMEOUT));
 
                                     return Poll::Ready(ConnectionHandlerEvent::NotifyBehaviour(
                                         HandlerOutEvent::ObservedAddress { observed_address },
@@ -573,11 +604,13 @@ impl ConnectionHandler for Handler {
                                     peer_contact_book.insert(peer_contact.clone());
 
                                     // Insert the peer's contacts (filtered) into my contact book
-                                    peer_contact_book.insert_all_filtered(
-                                        peer_contacts,
-                                        self.config.required_services,
-                                        self.config.only_secure_ws_connections,
-                                    );
+                                    if !self.paused.load(Ordering::Relaxed) {
+                                        peer_contact_book.insert_all_filtered(
+                                            peer_contacts,
+                                            self.config.required_services,
+                                            self.config.only_secure_ws_connections,
+                                        );
+                                    }
 
                                     drop(peer_contact_book);
 
@@ -678,6 +711,11 @@ impl ConnectionHandler for Handler {
                                         }
                                     }
 
+                                    // Ignore updates while discovery is paused.
+                                    if self.paused.load(Ordering::Relaxed) {
+                                        continue;
+                                    }
+
                                     // Insert the new peer contacts into the peer contact book.
                                     self.peer_contact_book.write().insert_all_filtered(
                                         peer_contacts,
@@ -717,6 +755,10 @@ impl ConnectionHandler for Handler {
                     if let Some(timer) = self.periodic_update_interval.as_mut() {
                         match timer.poll_next_unpin(cx) {
                             Poll::Ready(Some(_instant)) => {
+                                if self.paused.load(Ordering::Relaxed) {
+                                    continue;
+                                }
+
                                 let peer_contacts = {
                                     let peer_contact_book = &self.peer_contact_book.read();
                                     let mut peer_contacts = self.get_peer_contacts(
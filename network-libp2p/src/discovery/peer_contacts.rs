@@ -597,6 +597,21 @@ impl PeerContactBook {
         if self.memory_transport {
             return true;
         }
+        // QUIC addresses are based on UDP/IP and may only have a trailing `P2p` protocol.
+        let mut quic = address.iter();
+        if let (Some(ip), Some(Protocol::Udp(_)), Some(Protocol::QuicV1)) =
+            (quic.next(), quic.next(), quic.next())
+        {
+            let is_loopback = match ip {
+                Protocol::Ip4(ip) => ip.is_loopback(),
+                Protocol::Ip6(ip) => ip.is_loopback(),
+                _ => return false,
+            };
+            return !self.only_secure_addresses
+                && (self.allow_loopback_addresses || !is_loopback)
+                && quic.all(|protocol| matches!(protocol, Protocol::P2p(_)));
+        }
+
         // Otherwise check for an appropriate WS address
         let mut protocols = address.iter();
         let mut ip = protocols.next();
@@ -0,0 +1,103 @@
+use std::time::Duration;
+
+use instant::Instant;
+use libp2p::gossipsub::MessageId;
+use linked_hash_map::LinkedHashMap;
+
+/// Bounded cache of recently seen gossipsub message IDs, used to suppress duplicate messages
+/// before they are dispatched to the application.
+///
+/// Gossipsub only remembers message IDs for a short time. Messages that keep circulating in
+/// a dense topology can therefore be delivered again after that. This cache remembers up to
+/// `capacity` message IDs for `ttl` each, evicting the oldest ones first.
+#[derive(Debug, Default)]
+pub(crate) struct MessageDedupCache {
+    capacity: usize,
+    ttl: Duration,
+    /// Seen message IDs and when they were first seen, in insertion order.
+    seen: LinkedHashMap<MessageId, Instant>,
+}
+
+impl MessageDedupCache {
+    /// Creates a new cache. A capacity of zero disables the deduplication.
+    pub(crate) fn new(capacity: usize, ttl: Duration) -> Self {
+        Self {
+            capacity,
+            ttl,
+            seen: LinkedHashMap::new(),
+        }
+    }
+
+    /// Records the message ID and returns whether a message with the same ID was already
+    /// seen within the TTL.
+    pub(crate) fn check_duplicate(&mut self, message_id: &MessageId, now: Instant) -> bool {
+        if self.capacity == 0 {
+            return false;
+        }
+
+        // Entries are never refreshed, so the oldest entries are at the front.
+        while let Some((_, &seen_at)) = self.seen.front() {
+            if now.saturating_duration_since(seen_at) < self.ttl {
+                break;
+            }
+            self.seen.pop_front();
+        }
+
+        if self.seen.contains_key(message_id) {
+            return true;
+        }
+
+        self.seen.insert(message_id.clone(), now);
+        if self.seen.len() > self.capacity {
+            self.seen.pop_front();
+        }
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use nimiq_test_log::test;
+
+    use super::*;
+
+    fn message_id(n: u8) -> MessageId {
+        MessageId::from(vec![n])
+    }
+
+    #[test]
+    fn it_suppresses_duplicates_within_ttl() {
+        let mut cache = MessageDedupCache::new(10, Duration::from_secs(10));
+        let start = Instant::now();
+
+        assert!(!cache.check_duplicate(&message_id(1), start));
+        assert!(cache.check_duplicate(&message_id(1), start + Duration::from_secs(5)));
+        assert!(!cache.check_duplicate(&message_id(2), start + Duration::from_secs(5)));
+
+        // The first entry expired.
+        assert!(!cache.check_duplicate(&message_id(1), start + Duration::from_secs(10)));
+        assert!(cache.check_duplicate(&message_id(2), start + Duration::from_secs(10)));
+    }
+
+    #[test]
+    fn it_evicts_the_oldest_entries() {
+        let mut cache = MessageDedupCache::new(2, Duration::from_secs(10));
+        let now = Instant::now();
+
+        assert!(!cache.check_duplicate(&message_id(1), now));
+        assert!(!cache.check_duplicate(&message_id(2), now));
+        assert!(!cache.check_duplicate(&message_id(3), now));
+
+        assert!(!cache.check_duplicate(&message_id(1), now));
+        assert!(cache.check_duplicate(&message_id(3), now));
+    }
+
+    #[test]
+    fn it_can_be_disabled() {
+        let mut cache = MessageDedupCache::new(0, Duration::from_secs(10));
+        let now = Instant::now();
+
+        assert!(!cache.check_duplicate(&message_id(1), now));
+        assert!(!cache.check_duplicate(&message_id(1), now));
+    }
+}
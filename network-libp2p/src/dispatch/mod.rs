@@ -1 +1,2 @@
 pub mod codecs;
+pub(crate) mod dedup;
@@ -10,6 +10,9 @@ pub enum NetworkError {
     #[error("Failed to send action to swarm task")]
     Send,
 
+    #[error("The swarm task can't take any more actions at the moment")]
+    WouldBlock,
+
     #[error("Network action was cancelled")]
     Cancelled,
 
@@ -28,6 +31,9 @@ pub enum NetworkError {
     #[error("DHT PutRecord error: {0:?}")]
     DhtPutRecord(libp2p::kad::PutRecordError),
 
+    #[error("DHT AddProvider error: {0:?}")]
+    DhtAddProvider(libp2p::kad::AddProviderError),
+
     #[error("Gossipsub Publish error: {0:?}")]
     GossipsubPublish(libp2p::gossipsub::PublishError),
 
@@ -53,6 +59,12 @@ pub enum NetworkError {
 
     #[error("Peer contact error: {0}")]
     PeerContactError(#[from] PeerContactError),
+
+    #[error("Failed to listen on any of the provided addresses")]
+    ListenFailed,
+
+    #[error("Peer {0} is rejected by the peer filter")]
+    PeerFiltered(libp2p::PeerId),
 }
 
 impl<T> From<tokio::sync::mpsc::error::SendError<T>> for NetworkError {
@@ -85,6 +97,12 @@ impl From<libp2p::kad::PutRecordError> for NetworkError {
     }
 }
 
+impl From<libp2p::kad::AddProviderError> for NetworkError {
+    fn from(e: libp2p::kad::AddProviderError) -> Self {
+        Self::DhtAddProvider(e)
+    }
+}
+
 impl From<libp2p::gossipsub::PublishError> for NetworkError {
     fn from(e: libp2p::gossipsub::PublishError) -> Self {
         Self::GossipsubPublish(e)
@@ -1,6 +1,7 @@
 #[macro_use]
 extern crate log;
 
+mod bandwidth;
 mod behaviour;
 mod config;
 mod connection_pool;
@@ -12,14 +13,23 @@ mod network;
 mod network_metrics;
 mod network_types;
 mod only_secure_ws_transport;
+mod pem;
 mod rate_limiting;
 mod swarm;
+#[cfg(feature = "testing")]
+pub mod testing;
+#[cfg(not(target_family = "wasm"))]
+mod tls;
 mod utils;
 
 pub const DISCOVERY_PROTOCOL: &str = "/nimiq/discovery/0.0.1";
 pub const DHT_PROTOCOL: &str = "/nimiq/kad/0.0.1";
+pub const REQRES_PROTOCOL: &str = "/nimiq/reqres/0.0.1";
 
-pub use config::{Config, TlsConfig};
+use std::fmt;
+
+pub use bandwidth::BandwidthStats;
+pub use config::{Config, ConfigError, PeerFilter, RelayLimits, TlsConfig};
 pub use error::NetworkError;
 pub use libp2p::{
     self,
@@ -28,21 +38,89 @@ pub use libp2p::{
     PeerId,
 };
 pub use network::Network;
+pub use network_types::{
+    ConnectionDirection, DhtRoutingChange, DhtRoutingEvent, PeerConnectionInfo,
+};
+pub use pem::PemError;
+pub use rate_limiting::RateLimitConfig;
 use serde::{
-    de::Error, ser::Error as SerializationError, Deserialize, Deserializer, Serialize, Serializer,
+    de::{Error, SeqAccess, Visitor},
+    ser::{Error as SerializationError, SerializeTuple},
+    Deserialize, Deserializer, Serialize, Serializer,
 };
+#[cfg(not(target_family = "wasm"))]
+pub use tls::TlsError;
+
+/// Serialization discriminant of Ed25519 keypairs.
+const KEY_TYPE_ED25519: u8 = 0;
+/// Serialization discriminant of Secp256k1 keypairs.
+const KEY_TYPE_SECP256K1: u8 = 1;
+/// Size of the legacy serialization of keypairs, see [`Libp2pKeyPair::from_legacy_bytes`].
+/// The current serializations of all key types are at least this long.
+const LEGACY_KEYPAIR_SIZE: usize = 64;
 
 /// Wrapper to libp2p Keypair identity that implements SerDe Serialize/Deserialize
 #[derive(Clone, Debug)]
 pub struct Libp2pKeyPair(pub Keypair);
 
+impl Libp2pKeyPair {
+    /// Encodes the keypair as a PKCS#8 PEM `PRIVATE KEY` block.
+    /// Only Ed25519 keypairs are supported.
+    pub fn to_pem(&self) -> Result<String, PemError> {
+        let keypair = self
+            .0
+            .clone()
+            .try_into_ed25519()
+            .map_err(|_| PemError::UnsupportedKeyType)?;
+        Ok(pem::encode_ed25519(&keypair))
+    }
+
+    /// Decodes a keypair from a PKCS#8 PEM `PRIVATE KEY` block.
+    /// Blocks containing keys other than Ed25519 are rejected.
+    pub fn from_pem(pem: &str) -> Result<Self, PemError> {
+        let keypair = pem::decode_ed25519(pem)?;
+        Ok(Libp2pKeyPair(Keypair::from(keypair)))
+    }
+
+    /// Derives an Ed25519 keypair from the given seed, which is used as the secret key.
+    /// The same seed always results in the same keypair and thus the same `PeerId`.
+    pub fn from_seed(seed: [u8; 32]) -> Self {
+        let keypair =
+            Keypair::ed25519_from_bytes(seed).expect("Any 32 bytes are a valid Ed25519 secret key");
+        Libp2pKeyPair(keypair)
+    }
+
+    /// Decodes an Ed25519 keypair from the legacy serialization, which consists of the raw
+    /// 64-byte keypair without a key type discriminant.
+    pub fn from_legacy_bytes(bytes: &[u8]) -> Result<Self, libp2p::identity::DecodingError> {
+        let mut bytes = bytes.to_vec();
+        let keypair = libp2p::identity::ed25519::Keypair::try_from_bytes(&mut bytes)?;
+        Ok(Libp2pKeyPair(Keypair::from(keypair)))
+    }
+}
+
 impl Serialize for Libp2pKeyPair {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
         S: Serializer,
     {
-        if let Ok(pk) = self.0.clone().try_into_ed25519() {
-            nimiq_serde::FixedSizeByteArray::from(pk.to_bytes()).serialize(serializer)
+        if let Ok(keypair) = self.0.clone().try_into_ed25519() {
+            let mut tuple = serializer.serialize_tuple(2)?;
+            tuple.serialize_element(&KEY_TYPE_ED25519)?;
+            tuple.serialize_element(&nimiq_serde::FixedSizeByteArray::from(keypair.to_bytes()))?;
+            tuple.end()
+        } else if let Ok(keypair) = self.0.clone().try_into_secp256k1() {
+            // The public key is included such that the serialization is at least as long as the
+            // legacy one, see the `Deserialize` implementation.
+            let mut tuple = serializer.serialize_tuple(3)?;
+            tuple.serialize_element(&KEY_TYPE_SECP256K1)?;
+            tuple.serialize_element(&nimiq_serde::FixedSizeByteArray::from(
+                keypair.secret().to_bytes(),
+            ))?;
+            tuple.serialize_element(&nimiq_serde::FixedSizeByteArray::from(
+                keypair.public().to_bytes(),
+            ))?;
+            tuple.end()
         } else {
             Err(S::Error::custom("Unsupported key type"))
         }
@@ -54,12 +132,165 @@ impl<'de> Deserialize<'de> for Libp2pKeyPair {
     where
         D: Deserializer<'de>,
     {
-        let mut hex_encoded: [u8; 64] =
-            nimiq_serde::FixedSizeByteArray::deserialize(deserializer)?.into_inner();
+        struct KeyPairVisitor;
 
-        let keypair = libp2p::identity::ed25519::Keypair::try_from_bytes(&mut hex_encoded)
-            .map_err(|_| D::Error::custom("Invalid value"))?;
+        impl<'de> Visitor<'de> for KeyPairVisitor {
+            type Value = Libp2pKeyPair;
 
-        Ok(Libp2pKeyPair(Keypair::from(keypair)))
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str(
+                    "a key type discriminant followed by the key bytes or a legacy Ed25519 keypair",
+                )
+            }
+
+            fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+            where
+                A: SeqAccess<'de>,
+            {
+                // The legacy serialization is the raw Ed25519 keypair, while the current one
+                // starts with a key type discriminant. Both are at least `LEGACY_KEYPAIR_SIZE`
+                // bytes long, so the first `LEGACY_KEYPAIR_SIZE` bytes are read in any case.
+                // They are a legacy keypair if its public key matches its secret key.
+                let prefix: [u8; LEGACY_KEYPAIR_SIZE] = seq
+                    .next_element::<nimiq_serde::FixedSizeByteArray<LEGACY_KEYPAIR_SIZE>>()?
+                    .ok_or_else(|| A::Error::invalid_length(0, &self))?
+                    .into_inner();
+                if let Ok(keypair) = Libp2pKeyPair::from_legacy_bytes(&prefix) {
+                    return Ok(keypair);
+                }
+
+                let keypair = match prefix[0] {
+                    KEY_TYPE_ED25519 => {
+                        let last_byte: u8 = seq
+                            .next_element()?
+                            .ok_or_else(|| A::Error::invalid_length(1, &self))?;
+                        let mut bytes = [0u8; 64];
+                        bytes[..63].copy_from_slice(&prefix[1..]);
+                        bytes[63] = last_byte;
+                        let keypair =
+                            libp2p::identity::ed25519::Keypair::try_from_bytes(&mut bytes)
+                                .map_err(|_| A::Error::custom("Invalid Ed25519 key"))?;
+                        Keypair::from(keypair)
+                    }
+                    KEY_TYPE_SECP256K1 => {
+                        let last_bytes: [u8; 2] = seq
+                            .next_element::<nimiq_serde::FixedSizeByteArray<2>>()?
+                            .ok_or_else(|| A::Error::invalid_length(1, &self))?
+                            .into_inner();
+                        let mut secret_bytes = [0u8; 32];
+                        secret_bytes.copy_from_slice(&prefix[1..33]);
+                        let mut public_bytes = [0u8; 33];
+                        public_bytes[..31].copy_from_slice(&prefix[33..]);
+                        public_bytes[31..].copy_from_slice(&last_bytes);
+
+                        let secret = libp2p::identity::secp256k1::SecretKey::try_from_bytes(
+                            &mut secret_bytes,
+                        )
+                        .map_err(|_| A::Error::custom("Invalid Secp256k1 key"))?;
+                        let keypair = libp2p::identity::secp256k1::Keypair::from(secret);
+                        if keypair.public().to_bytes() != public_bytes {
+                            return Err(A::Error::custom(
+                                "Secp256k1 public key doesn't match the secret key",
+                            ));
+                        }
+                        Keypair::from(keypair)
+                    }
+                    key_type => {
+                        return Err(A::Error::custom(format!(
+                            "Unknown key type discriminant: {}",
+                            key_type
+                        )))
+                    }
+                };
+
+                Ok(Libp2pKeyPair(keypair))
+            }
+        }
+
+        deserializer.deserialize_tuple(2, KeyPairVisitor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use libp2p::identity::Keypair;
+    use nimiq_serde::{Deserialize, Serialize};
+    use nimiq_test_log::test;
+
+    use super::Libp2pKeyPair;
+
+    #[test]
+    fn it_serializes_ed25519_keys() {
+        let keypair = Libp2pKeyPair(Keypair::generate_ed25519());
+        let serialized = keypair.serialize_to_vec();
+        assert_eq!(serialized.len(), 65);
+
+        let deserialized = Libp2pKeyPair::deserialize_from_vec(&serialized).unwrap();
+        assert_eq!(deserialized.0.public(), keypair.0.public());
+    }
+
+    #[test]
+    fn it_serializes_secp256k1_keys() {
+        let keypair = Libp2pKeyPair(Keypair::generate_secp256k1());
+        let serialized = keypair.serialize_to_vec();
+        assert_eq!(serialized.len(), 66);
+
+        let deserialized = Libp2pKeyPair::deserialize_from_vec(&serialized).unwrap();
+        assert_eq!(deserialized.0.public(), keypair.0.public());
+    }
+
+    #[test]
+    fn it_rejects_unknown_key_types() {
+        let mut serialized = Libp2pKeyPair(Keypair::generate_ed25519()).serialize_to_vec();
+        serialized[0] = 0xff;
+
+        assert!(Libp2pKeyPair::deserialize_from_vec(&serialized).is_err());
+    }
+
+    #[test]
+    fn it_derives_keys_from_seeds() {
+        let keypair = Libp2pKeyPair::from_seed([1; 32]);
+        assert_eq!(
+            keypair.0.public(),
+            Libp2pKeyPair::from_seed([1; 32]).0.public()
+        );
+        assert_ne!(
+            keypair.0.public(),
+            Libp2pKeyPair::from_seed([2; 32]).0.public()
+        );
+
+        let deserialized =
+            Libp2pKeyPair::deserialize_from_vec(&keypair.serialize_to_vec()).unwrap();
+        assert_eq!(deserialized.0.public(), keypair.0.public());
+    }
+
+    #[test]
+    fn it_decodes_legacy_ed25519_keys() {
+        let keypair = Keypair::generate_ed25519();
+        let legacy_bytes = keypair.clone().try_into_ed25519().unwrap().to_bytes();
+
+        let decoded = Libp2pKeyPair::from_legacy_bytes(&legacy_bytes).unwrap();
+        assert_eq!(decoded.0.public(), keypair.public());
+    }
+
+    #[test]
+    fn it_deserializes_legacy_ed25519_keys() {
+        // The first byte of a legacy key can be mistaken for a key type discriminant.
+        for seed in [[0; 32], [1; 32], [2; 32]] {
+            let keypair = Libp2pKeyPair::from_seed(seed);
+            let legacy_bytes = keypair.0.clone().try_into_ed25519().unwrap().to_bytes();
+
+            let deserialized = Libp2pKeyPair::deserialize_from_vec(&legacy_bytes).unwrap();
+            assert_eq!(deserialized.0.public(), keypair.0.public());
+        }
+    }
+
+    #[test]
+    fn it_rejects_mismatching_secp256k1_public_keys() {
+        let mut serialized = Libp2pKeyPair(Keypair::generate_secp256k1()).serialize_to_vec();
+        let other = Libp2pKeyPair(Keypair::generate_secp256k1()).serialize_to_vec();
+        serialized[33..].copy_from_slice(&other[33..]);
+
+        assert!(Libp2pKeyPair::deserialize_from_vec(&serialized).is_err());
     }
 }
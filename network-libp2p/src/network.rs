@@ -10,9 +10,12 @@ use std::{
 use async_trait::async_trait;
 use bytes::Bytes;
 use futures::{future::BoxFuture, ready, stream::BoxStream, Stream, StreamExt};
+use instant::Instant;
 use libp2p::{
-    gossipsub, request_response::InboundRequestId, swarm::NetworkInfo, Multiaddr, PeerId, Swarm,
+    gossipsub, kad::RecordKey, request_response::InboundRequestId, swarm::NetworkInfo, Multiaddr,
+    PeerId, Swarm,
 };
+use linked_hash_map::LinkedHashMap;
 use nimiq_network_interface::{
     network::{
         CloseReason, MsgAcceptance, Network as NetworkInterface, NetworkEvent, SubscribeEvents,
@@ -21,31 +24,81 @@ use nimiq_network_interface::{
     peer_info::{PeerInfo, Services},
     request::{
         InboundRequestError, Message, OutboundRequestError, Request, RequestCommon, RequestError,
-        RequestSerialize, RequestType,
+        RequestPriority, RequestSerialize, RequestType,
     },
 };
 use nimiq_serde::{Deserialize, Serialize};
-use nimiq_time::{interval, timeout};
+use nimiq_time::{interval, sleep, timeout};
 use nimiq_utils::{
     spawn,
     tagged_signing::{TaggedKeyPair, TaggedSignable, TaggedSigned},
 };
-use parking_lot::RwLock;
+use parking_lot::{Mutex, RwLock};
 use tokio::sync::{broadcast, mpsc, oneshot};
-use tokio_stream::wrappers::{BroadcastStream, ReceiverStream};
+use tokio_stream::wrappers::{
+    errors::BroadcastStreamRecvError, BroadcastStream, ReceiverStream, UnboundedReceiverStream,
+};
 
 #[cfg(feature = "metrics")]
 use crate::network_metrics::NetworkMetrics;
 use crate::{
+    bandwidth::{BandwidthStats, BandwidthTracker},
     discovery::peer_contacts::PeerContactBook,
-    network_types::{GossipsubId, NetworkAction, ValidateMessage},
-    rate_limiting::RequestRateLimitData,
+    dispatch::dedup::MessageDedupCache,
+    network_types::{
+        DhtRoutingEvent, GossipsubId, NetworkAction, PeerConnectionInfo, ValidateMessage,
+    },
+    rate_limiting::{RateLimitConfig, RequestRateLimitData},
     swarm::{new_swarm, swarm_task},
     Config, NetworkError,
 };
 
 const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
 
+/// Maximum number of recently disconnected peers remembered to detect reconnections.
+const MAX_RECENTLY_DISCONNECTED_PEERS: usize = 1024;
+
+type ReconnectHook = Arc<dyn Fn(PeerId) + Send + Sync>;
+
+/// Keeps track of recently disconnected peers in order to notify the registered hooks
+/// when one of them reconnects within the reconnect window.
+pub(crate) struct ReconnectTracker {
+    /// Recently disconnected peers and the time of their disconnection, least recent first.
+    recently_disconnected: LinkedHashMap<PeerId, Instant>,
+    /// Maximum time between disconnection and connection for it to count as a reconnection.
+    window: Duration,
+    /// Hooks called on reconnection.
+    hooks: Vec<ReconnectHook>,
+}
+
+impl ReconnectTracker {
+    pub(crate) fn new(window: Duration) -> Self {
+        Self {
+            recently_disconnected: LinkedHashMap::new(),
+            window,
+            hooks: vec![],
+        }
+    }
+
+    /// Remembers that a peer disconnected, evicting the least recently disconnected peer
+    /// if too many peers are remembered.
+    pub(crate) fn peer_left(&mut self, peer_id: PeerId) {
+        self.recently_disconnected.insert(peer_id, Instant::now());
+        while self.recently_disconnected.len() > MAX_RECENTLY_DISCONNECTED_PEERS {
+            self.recently_disconnected.pop_front();
+        }
+    }
+
+    /// Returns the hooks that need to be called for the newly connected peer. These are
+    /// only returned if the peer disconnected within the reconnect window.
+    pub(crate) fn peer_joined(&mut self, peer_id: &PeerId) -> Vec<ReconnectHook> {
+        match self.recently_disconnected.remove(peer_id) {
+            Some(disconnected_at) if disconnected_at.elapsed() <= self.window => self.hooks.clone(),
+            _ => vec![],
+        }
+    }
+}
+
 pub struct Network {
     /// The local ID that is used to identify our peer
     local_peer_id: PeerId,
@@ -55,8 +108,14 @@ pub struct Network {
     connected_peers: Arc<RwLock<HashMap<PeerId, PeerInfo>>>,
     /// Stream used to send event messages
     events_tx: broadcast::Sender<NetworkEvent<PeerId>>,
+    /// Stream used to send DHT routing table events
+    dht_routing_tx: broadcast::Sender<DhtRoutingEvent>,
     /// Stream used to send action messages
     action_tx: mpsc::Sender<NetworkAction>,
+    /// Streams used to send requests and responses of high and low priority. Actions sent
+    /// through `action_tx` are treated as normal priority.
+    high_priority_action_tx: mpsc::Sender<NetworkAction>,
+    low_priority_action_tx: mpsc::Sender<NetworkAction>,
     /// Stream used to send validation messages
     validate_tx: mpsc::UnboundedSender<ValidateMessage<PeerId>>,
     /// Metrics used for data analysis
@@ -66,6 +125,12 @@ pub struct Network {
     required_services: Services,
     /// Reference to PeerContactBook, used to satisfy rpc requests for it.
     contacts: Arc<RwLock<PeerContactBook>>,
+    /// Rate limits per request type name overriding the request type defaults.
+    rate_limits: HashMap<String, RateLimitConfig>,
+    /// Tracker of recently disconnected peers, shared with the swarm task.
+    reconnect_tracker: Arc<Mutex<ReconnectTracker>>,
+    /// Counters of the bandwidth used by the connections, shared with the transport.
+    bandwidth: Arc<BandwidthTracker>,
 }
 
 impl Network {
@@ -90,23 +155,43 @@ impl Network {
             ..Default::default()
         };
         let dht_quorum = config.dht_quorum;
+        let dht_bootstrap_min_buckets = config.dht_bootstrap_min_buckets;
+        let gossip_dedup_cache = MessageDedupCache::new(
+            config.gossip_dedup_cache_size,
+            config.gossip_dedup_cache_ttl,
+        );
+        let rate_limits = config.rate_limits.clone();
+        let listen_addresses = config.listen_addresses.clone();
+        let relay_peers = if config.enable_relay_client {
+            config.relay_peers.clone()
+        } else {
+            vec![]
+        };
         // Only force the server mode if we are doing a memory transport.
         // Otherwise expect the regular flow: DHT will get in server mode once a confirmed address is obtained using Autonat.
         // In memory transport we don't have a mechanism that sets the DHT in server mode such as confirming an address
         // with Autonat. This is because Autonat v1 only works with IP addresses.
         let force_dht_server_mode = config.memory_transport;
+        let reconnect_tracker = Arc::new(Mutex::new(ReconnectTracker::new(
+            config.peer_reconnect_window,
+        )));
+        let bandwidth = Arc::new(BandwidthTracker::new());
         let swarm = new_swarm(
             config,
             Arc::clone(&contacts),
             params.clone(),
             force_dht_server_mode,
+            Arc::clone(&bandwidth),
         );
 
         let local_peer_id = *Swarm::local_peer_id(&swarm);
         let connected_peers = Arc::new(RwLock::new(HashMap::new()));
 
         let (events_tx, _) = broadcast::channel(64);
+        let (dht_routing_tx, _) = broadcast::channel(64);
         let (action_tx, action_rx) = mpsc::channel(64);
+        let (high_priority_action_tx, high_priority_action_rx) = mpsc::channel(64);
+        let (low_priority_action_tx, low_priority_action_rx) = mpsc::channel(64);
         let (validate_tx, validate_rx) = mpsc::unbounded_channel();
 
         let update_scores = interval(params.decay_interval);
@@ -117,28 +202,49 @@ impl Network {
         spawn(Box::pin(swarm_task(
             swarm,
             events_tx.clone(),
+            dht_routing_tx.clone(),
             action_rx,
+            high_priority_action_rx,
+            low_priority_action_rx,
             validate_rx,
             Arc::clone(&connected_peers),
+            Arc::clone(&reconnect_tracker),
             update_scores,
             Arc::clone(&contacts),
             force_dht_server_mode,
             dht_quorum,
+            dht_bootstrap_min_buckets,
+            gossip_dedup_cache,
+            relay_peers,
             #[cfg(feature = "metrics")]
             metrics.clone(),
         )));
 
-        Self {
+        let network = Self {
             contacts,
             local_peer_id,
             connected_peers,
             events_tx,
+            dht_routing_tx,
             action_tx,
+            high_priority_action_tx,
+            low_priority_action_tx,
             validate_tx,
             #[cfg(feature = "metrics")]
             metrics,
             required_services,
+            rate_limits,
+            reconnect_tracker,
+            bandwidth,
+        };
+
+        if !listen_addresses.is_empty() {
+            if let Err(error) = network.listen_on(listen_addresses).await {
+                error!(%error, "Failed to listen on the configured addresses");
+            }
         }
+
+        network
     }
 
     pub fn local_peer_id(&self) -> &PeerId {
@@ -151,7 +257,9 @@ impl Network {
         self.contacts.read().known_peers()
     }
 
-    /// Gets the network information
+    /// Gets a fresh snapshot of the swarm's network information, i.e. the number of connected
+    /// peers and the connection counters. The snapshot is taken by the swarm task when it
+    /// processes the request, so this can be polled at any time, e.g. by health endpoints.
     pub async fn network_info(&self) -> Result<NetworkInfo, NetworkError> {
         let (output_tx, output_rx) = oneshot::channel();
 
@@ -162,17 +270,195 @@ impl Network {
         Ok(output_rx.await?)
     }
 
-    /// Tells the network to listen on a specific address received in a
+    /// Tells the network to listen on the given addresses received in a
     /// `Multiaddr` format.
-    pub async fn listen_on(&self, listen_addresses: Vec<Multiaddr>) {
-        if let Err(error) = self
-            .action_tx
+    /// Addresses that fail to bind are logged and skipped. An error is only
+    /// returned if none of the given addresses could be listened on.
+    pub async fn listen_on(&self, listen_addresses: Vec<Multiaddr>) -> Result<(), NetworkError> {
+        let (output_tx, output_rx) = oneshot::channel();
+
+        self.action_tx
             .clone()
-            .send(NetworkAction::ListenOn { listen_addresses })
-            .await
-        {
-            error!(%error, "Failed to send NetworkAction::ListenOnAddress");
-        }
+            .send(NetworkAction::ListenOn {
+                listen_addresses,
+                output: output_tx,
+            })
+            .await?;
+        output_rx.await?
+    }
+
+    /// Gets the addresses the network is actually listening on
+    pub async fn listen_addresses(&self) -> Result<Vec<Multiaddr>, NetworkError> {
+        let (output_tx, output_rx) = oneshot::channel();
+
+        self.action_tx
+            .clone()
+            .send(NetworkAction::ListenAddresses { output: output_tx })
+            .await?;
+        Ok(output_rx.await?)
+    }
+
+    /// Gets the current dial backoff delay of a peer. This is `None` unless the
+    /// last dial attempts to the peer failed. Intended for debugging purposes.
+    pub async fn connection_backoff(
+        &self,
+        peer_id: PeerId,
+    ) -> Result<Option<Duration>, NetworkError> {
+        let (output_tx, output_rx) = oneshot::channel();
+
+        self.action_tx
+            .clone()
+            .send(NetworkAction::ConnectionBackoff {
+                peer_id,
+                output: output_tx,
+            })
+            .await?;
+        Ok(output_rx.await?)
+    }
+
+    /// Gets information about the connection to a peer, such as since when it is
+    /// connected, who initiated the connection and which protocols the peer has been
+    /// observed to speak. Returns `None` if the peer is not connected.
+    pub async fn peer_connection_info(
+        &self,
+        peer_id: PeerId,
+    ) -> Result<Option<PeerConnectionInfo>, NetworkError> {
+        let (output_tx, output_rx) = oneshot::channel();
+        self.action_tx
+            .clone()
+            .send(NetworkAction::PeerConnectionInfo {
+                peer_id,
+                output: output_tx,
+            })
+            .await?;
+        Ok(output_rx.await?)
+    }
+
+    /// Gets the bandwidth used by the connections to a peer. Returns `None` if the peer is
+    /// not connected.
+    pub fn bandwidth(&self, peer_id: &PeerId) -> Option<BandwidthStats> {
+        self.bandwidth.peer_stats(peer_id)
+    }
+
+    /// Gets the bandwidth used by all connections since the network was started.
+    pub fn total_bandwidth(&self) -> BandwidthStats {
+        self.bandwidth.total_stats()
+    }
+
+    /// Gets the (versioned) protocols that have been successfully negotiated with a peer,
+    /// e.g. `/nimiq/discovery/0.0.1`, sorted by name. Returns an empty list if the peer is not
+    /// connected.
+    pub async fn peer_protocols(&self, peer_id: PeerId) -> Result<Vec<String>, NetworkError> {
+        let mut protocols: Vec<String> = self
+            .peer_connection_info(peer_id)
+            .await?
+            .map(|info| info.protocols.iter().map(ToString::to_string).collect())
+            .unwrap_or_default();
+        protocols.sort();
+        Ok(protocols)
+    }
+
+    /// Looks up the peers providing the given key in the DHT. The returned stream yields the
+    /// providers as they are discovered and ends once the lookup has finished or
+    /// `timeout_duration` has elapsed. Dropping the stream aborts the lookup.
+    pub async fn get_providers(
+        &self,
+        key: RecordKey,
+        timeout_duration: Duration,
+    ) -> Result<impl Stream<Item = PeerId>, NetworkError> {
+        let (output_tx, output_rx) = mpsc::unbounded_channel();
+
+        self.action_tx
+            .clone()
+            .send(NetworkAction::DhtGetProviders {
+                key,
+                output: output_tx,
+            })
+            .await?;
+
+        Ok(UnboundedReceiverStream::new(output_rx).take_until(sleep(timeout_duration)))
+    }
+
+    /// Announces us in the DHT as a provider of the given key. The announcement is repeated
+    /// periodically, see `Config::dht_provider_publication_interval`, until `stop_providing`
+    /// is called. Resolves once the initial announcement has finished.
+    pub async fn start_providing(&self, key: RecordKey) -> Result<(), NetworkError> {
+        let (output_tx, output_rx) = oneshot::channel();
+
+        self.action_tx
+            .clone()
+            .send(NetworkAction::DhtStartProviding {
+                key,
+                output: output_tx,
+            })
+            .await?;
+        output_rx.await?
+    }
+
+    /// Stops announcing us in the DHT as a provider of the given key. Provider records already
+    /// stored by other peers remain until they expire.
+    pub async fn stop_providing(&self, key: RecordKey) -> Result<(), NetworkError> {
+        self.action_tx
+            .clone()
+            .send(NetworkAction::DhtStopProviding { key })
+            .await?;
+        Ok(())
+    }
+
+    /// Gets the score of a connected peer, or `None` if the peer is not connected.
+    ///
+    /// The score is accumulated from the peer's behavior while connected, e.g. answered and
+    /// failed requests, rate limit violations, invalid messages and ping latencies. When the
+    /// connection pool needs to evict a peer, it chooses one of the lowest-scoring peers.
+    pub async fn peer_score(&self, peer_id: PeerId) -> Result<Option<i32>, NetworkError> {
+        let (output_tx, output_rx) = oneshot::channel();
+        self.action_tx
+            .clone()
+            .send(NetworkAction::PeerScore {
+                peer_id,
+                output: output_tx,
+            })
+            .await?;
+        Ok(output_rx.await?)
+    }
+
+    /// Bans a peer for the given duration, or permanently if the duration is `None`.
+    /// The peer is disconnected immediately and neither dialed nor accepted until the ban
+    /// expires or is lifted with `unban_peer`. A previous ban of the peer is replaced.
+    pub async fn ban_peer(
+        &self,
+        peer_id: PeerId,
+        duration: Option<Duration>,
+    ) -> Result<(), NetworkError> {
+        self.action_tx
+            .clone()
+            .send(NetworkAction::BanPeer { peer_id, duration })
+            .await?;
+        Ok(())
+    }
+
+    /// Lifts the ban of a peer. Returns whether the peer was banned.
+    pub async fn unban_peer(&self, peer_id: PeerId) -> Result<bool, NetworkError> {
+        let (output_tx, output_rx) = oneshot::channel();
+        self.action_tx
+            .clone()
+            .send(NetworkAction::UnbanPeer {
+                peer_id,
+                output: output_tx,
+            })
+            .await?;
+        Ok(output_rx.await?)
+    }
+
+    /// Gets the banned peers together with the remaining time of their ban. Permanently
+    /// banned peers have no remaining time.
+    pub async fn banned_peers(&self) -> Result<Vec<(PeerId, Option<Duration>)>, NetworkError> {
+        let (output_tx, output_rx) = oneshot::channel();
+        self.action_tx
+            .clone()
+            .send(NetworkAction::BannedPeers { output: output_tx })
+            .await?;
+        Ok(output_rx.await?)
     }
 
     /// Tells the network to start connecting to any available peer or seed
@@ -192,10 +478,117 @@ impl Network {
         }
     }
 
+    /// Pauses the peer discovery. Existing connections are kept, but no peer contacts are
+    /// exchanged with connected peers until `resume_discovery` is called.
+    pub async fn pause_discovery(&self) -> Result<(), NetworkError> {
+        self.action_tx
+            .clone()
+            .send(NetworkAction::SetDiscoveryPaused { paused: true })
+            .await?;
+        Ok(())
+    }
+
+    /// Resumes the peer discovery after it was paused with `pause_discovery`.
+    pub async fn resume_discovery(&self) -> Result<(), NetworkError> {
+        self.action_tx
+            .clone()
+            .send(NetworkAction::SetDiscoveryPaused { paused: false })
+            .await?;
+        Ok(())
+    }
+
+    /// Returns whether the peer discovery is paused.
+    pub async fn discovery_paused(&self) -> Result<bool, NetworkError> {
+        let (output_tx, output_rx) = oneshot::channel();
+        self.action_tx
+            .clone()
+            .send(NetworkAction::DiscoveryPaused { output: output_tx })
+            .await?;
+        Ok(output_rx.await?)
+    }
+
+    /// Forgets the external addresses of this node and immediately probes our addresses with
+    /// AutoNAT again. This is useful after a change of the public IP address, which would
+    /// otherwise only be picked up once AutoNAT re-probes on its own.
+    pub async fn rediscover_external_address(&self) -> Result<(), NetworkError> {
+        self.action_tx
+            .clone()
+            .send(NetworkAction::RediscoverExternalAddress)
+            .await?;
+        Ok(())
+    }
+
+    /// Gracefully shuts down the network.
+    ///
+    /// New inbound and outbound connections are denied, in-flight requests and pending
+    /// responses are given the chance to complete and afterwards all connections are closed.
+    /// The returned future resolves once this has finished or `timeout_duration` has elapsed.
+    /// Dropping the future doesn't abort the shutdown once it has been initiated.
+    pub fn shutdown(&self, timeout_duration: Duration) -> impl Future<Output = ()> {
+        let action_tx = self.action_tx.clone();
+
+        async move {
+            let shutdown = async move {
+                let (output_tx, output_rx) = oneshot::channel();
+                if action_tx
+                    .send(NetworkAction::Shutdown { output: output_tx })
+                    .await
+                    .is_err()
+                {
+                    // The swarm task already terminated.
+                    return;
+                }
+                output_rx.await.ok();
+            };
+
+            if timeout(timeout_duration, shutdown).await.is_err() {
+                debug!(timeout = ?timeout_duration, "Network shutdown timed out");
+            }
+        }
+    }
+
+    /// Subscribes to the changes of the DHT routing table. An event is emitted whenever a peer
+    /// is inserted into a bucket, its entry is updated or it is evicted from its bucket.
+    pub fn subscribe_dht_routing_events(
+        &self,
+    ) -> BoxStream<'static, Result<DhtRoutingEvent, BroadcastStreamRecvError>> {
+        Box::pin(BroadcastStream::new(self.dht_routing_tx.subscribe()))
+    }
+
+    /// Registers a hook that is called whenever a peer that disconnected less than
+    /// `Config::peer_reconnect_window` ago connects again.
+    ///
+    /// The hook is called from within the network task and thus should return quickly.
+    pub fn on_peer_reconnect(&self, hook: Box<dyn Fn(PeerId) + Send + Sync>) {
+        self.reconnect_tracker.lock().hooks.push(Arc::from(hook));
+    }
+
+    /// Returns the stream used to send actions of the given priority to the swarm task.
+    fn action_tx_for(&self, priority: RequestPriority) -> &mpsc::Sender<NetworkAction> {
+        match priority {
+            RequestPriority::High => &self.high_priority_action_tx,
+            RequestPriority::Normal => &self.action_tx,
+            RequestPriority::Low => &self.low_priority_action_tx,
+        }
+    }
+
+    /// Sends a request to a peer and waits at most `timeout_duration` for its response.
+    /// The response is matched to the request by its request ID, so responses to requests that
+    /// already timed out are dropped instead of being delivered to a later request.
+    pub async fn request_with_timeout<Req: Request>(
+        &self,
+        request: Req,
+        peer_id: PeerId,
+        timeout_duration: Duration,
+    ) -> Result<Req::Response, RequestError> {
+        self.request_impl(request, peer_id, timeout_duration).await
+    }
+
     async fn request_impl<Req: RequestCommon>(
         &self,
         request: Req,
         peer_id: PeerId,
+        timeout_duration: Duration,
     ) -> Result<Req::Response, RequestError> {
         let (output_tx, output_rx) = oneshot::channel();
         let (response_tx, response_rx) = oneshot::channel();
@@ -207,7 +600,13 @@ impl Network {
             output: output_tx,
         };
 
-        if self.action_tx.clone().send(action).await.is_err() {
+        if self
+            .action_tx_for(Req::PRIORITY)
+            .clone()
+            .send(action)
+            .await
+            .is_err()
+        {
             return Err(OutboundRequestError::SendError.into());
         }
 
@@ -222,7 +621,7 @@ impl Network {
             "Request sent",
         );
 
-        let Ok(result) = timeout(REQUEST_TIMEOUT, response_rx).await else {
+        let Ok(result) = timeout(timeout_duration, response_rx).await else {
             debug!(
                 r#type = Req::type_name::<Req>(),
                 %request_id,
@@ -312,6 +711,8 @@ impl Network {
         }
 
         let action_tx = self.action_tx.clone();
+        let request_rate_limit_data =
+            RequestRateLimitData::new::<Req>(self.rate_limits.get(Req::type_name::<Req>()));
         ReceiveStream::WaitingForRegister(Box::pin(async move {
             // TODO Make buffer size configurable
             let (tx, rx) = mpsc::channel(1024);
@@ -320,7 +721,7 @@ impl Network {
                 .send(NetworkAction::ReceiveRequests {
                     type_id: RequestType::from_request::<Req>(),
                     output: tx,
-                    request_rate_limit_data: RequestRateLimitData::new::<Req>(),
+                    request_rate_limit_data,
                 })
                 .await
                 .expect("Sending action to network task failed.");
@@ -422,6 +823,44 @@ impl Network {
         output_rx.await?
     }
 
+    /// Publishes an item on a topic like `publish`, but fails with `NetworkError::WouldBlock`
+    /// instead of waiting if the swarm task can't take any more actions at the moment.
+    pub async fn try_publish<T>(&self, item: <T as Topic>::Item) -> Result<(), NetworkError>
+    where
+        T: Topic + Sync,
+    {
+        let (action, output_rx) = Self::publish_action::<T>(T::NAME.to_string(), item);
+
+        self.action_tx
+            .clone()
+            .try_send(action)
+            .map_err(|error| match error {
+                mpsc::error::TrySendError::Full(_) => NetworkError::WouldBlock,
+                mpsc::error::TrySendError::Closed(_) => NetworkError::Send,
+            })?;
+
+        self.await_published::<T>(output_rx).await
+    }
+
+    /// Publishes an item on a topic like `publish`, but waits at most `timeout_duration` for
+    /// the swarm task to accept it. Fails with `NetworkError::WouldBlock` if it didn't.
+    pub async fn publish_with_timeout<T>(
+        &self,
+        item: <T as Topic>::Item,
+        timeout_duration: Duration,
+    ) -> Result<(), NetworkError>
+    where
+        T: Topic + Sync,
+    {
+        let (action, output_rx) = Self::publish_action::<T>(T::NAME.to_string(), item);
+
+        timeout(timeout_duration, self.action_tx.clone().send(action))
+            .await
+            .map_err(|_| NetworkError::WouldBlock)??;
+
+        self.await_published::<T>(output_rx).await
+    }
+
     async fn publish_with_name<T>(
         &self,
         topic_name: String,
@@ -430,17 +869,38 @@ impl Network {
     where
         T: Topic + Sync,
     {
-        let (output_tx, output_rx) = oneshot::channel();
+        let (action, output_rx) = Self::publish_action::<T>(topic_name, item);
 
-        self.action_tx
-            .clone()
-            .send(NetworkAction::Publish {
-                topic_name,
-                data: item.serialize_to_vec(),
-                output: output_tx,
-            })
-            .await?;
+        self.action_tx.clone().send(action).await?;
 
+        self.await_published::<T>(output_rx).await
+    }
+
+    fn publish_action<T>(
+        topic_name: String,
+        item: <T as Topic>::Item,
+    ) -> (NetworkAction, oneshot::Receiver<Result<(), NetworkError>>)
+    where
+        T: Topic + Sync,
+    {
+        let (output_tx, output_rx) = oneshot::channel();
+        let action = NetworkAction::Publish {
+            topic_name,
+            data: item.serialize_to_vec(),
+            output: output_tx,
+        };
+        (action, output_rx)
+    }
+
+    // The topic is only needed to record the metrics.
+    #[cfg_attr(not(feature = "metrics"), allow(clippy::extra_unused_type_parameters))]
+    async fn await_published<T>(
+        &self,
+        output_rx: oneshot::Receiver<Result<(), NetworkError>>,
+    ) -> Result<(), NetworkError>
+    where
+        T: Topic + Sync,
+    {
         output_rx.await??;
 
         #[cfg(feature = "metrics")]
@@ -689,7 +1149,7 @@ impl NetworkInterface for Network {
     }
 
     async fn message<M: Message>(&self, message: M, peer_id: PeerId) -> Result<(), RequestError> {
-        self.request_impl(message, peer_id).await
+        self.request_impl(message, peer_id, REQUEST_TIMEOUT).await
     }
 
     async fn request<Req: Request>(
@@ -697,7 +1157,7 @@ impl NetworkInterface for Network {
         request: Req,
         peer_id: PeerId,
     ) -> Result<Req::Response, RequestError> {
-        self.request_impl(request, peer_id).await
+        self.request_impl(request, peer_id, REQUEST_TIMEOUT).await
     }
 
     fn receive_messages<M: Message>(&self) -> BoxStream<'static, (M, PeerId)> {
@@ -724,7 +1184,7 @@ impl NetworkInterface for Network {
         let response: Result<Req::Response, InboundRequestError> = Ok(response);
         let ser_response = response.serialize_to_vec();
 
-        self.action_tx
+        self.action_tx_for(Req::PRIORITY)
             .clone()
             .send(NetworkAction::SendResponse {
                 request_id,
@@ -3,13 +3,17 @@ use std::time::Duration;
 use libp2p::gossipsub::TopicHash;
 use prometheus_client::{
     encoding::EncodeLabelSet,
-    metrics::{counter::Counter, family::Family, histogram::Histogram},
+    metrics::{counter::Counter, family::Family, gauge::Gauge, histogram::Histogram},
     registry::Registry,
 };
 
 pub struct NetworkMetrics {
     gossipsub_messages_received: Family<TopicLabels, Counter>,
     gossipsub_messages_published: Family<TopicLabels, Counter>,
+    gossipsub_messages_dropped: Family<TopicLabels, Counter>,
+    gossipsub_messages_suppressed: Family<TopicLabels, Counter>,
+    gossipsub_channel_depth: Family<TopicLabels, Gauge>,
+    relay_reservations: Gauge,
     response_times: Histogram,
 }
 
@@ -18,11 +22,30 @@ struct TopicLabels {
     topic: String,
 }
 
+impl TopicLabels {
+    /// Labels for a topic received from the network. Unknown topics are grouped
+    /// together to keep the label cardinality bounded.
+    fn from_received(topic: &TopicHash) -> Self {
+        let topic = topic.to_string();
+        if ["blocks", "transactions", "tendermint-proposal"].contains(&&*topic) {
+            TopicLabels { topic }
+        } else {
+            TopicLabels {
+                topic: "unknown".into(),
+            }
+        }
+    }
+}
+
 impl Default for NetworkMetrics {
     fn default() -> Self {
         NetworkMetrics {
             gossipsub_messages_received: Default::default(),
             gossipsub_messages_published: Default::default(),
+            gossipsub_messages_dropped: Default::default(),
+            gossipsub_messages_suppressed: Default::default(),
+            gossipsub_channel_depth: Default::default(),
+            relay_reservations: Default::default(),
             response_times: Histogram::new([0.01, 0.02, 0.05, 0.1, 0.2, 0.5, 1.0, 2.0].into_iter()),
         }
     }
@@ -42,6 +65,30 @@ impl NetworkMetrics {
             self.gossipsub_messages_published.clone(),
         );
 
+        registry.register(
+            "gossipsub_messages_dropped",
+            "Number of received gossipsub messages dropped because the topic's channel was full",
+            self.gossipsub_messages_dropped.clone(),
+        );
+
+        registry.register(
+            "gossipsub_messages_suppressed",
+            "Number of received gossipsub messages suppressed as duplicates",
+            self.gossipsub_messages_suppressed.clone(),
+        );
+
+        registry.register(
+            "gossipsub_channel_depth",
+            "Number of gossipsub messages queued in the topic's channel",
+            self.gossipsub_channel_depth.clone(),
+        );
+
+        registry.register(
+            "relay_reservations",
+            "Number of active reservations on our relay server",
+            self.relay_reservations.clone(),
+        );
+
         registry.register(
             "request_durations",
             "Time between requests and responses",
@@ -50,19 +97,27 @@ impl NetworkMetrics {
     }
 
     pub(crate) fn note_received_pubsub_message(&self, topic: &TopicHash) {
-        if ["blocks", "transactions", "tendermint-proposal"].contains(&&*topic.to_string()) {
-            self.gossipsub_messages_received
-                .get_or_create(&TopicLabels {
-                    topic: topic.to_string(),
-                })
-                .inc();
-        } else {
-            self.gossipsub_messages_received
-                .get_or_create(&TopicLabels {
-                    topic: "unknown".into(),
-                })
-                .inc();
-        }
+        self.gossipsub_messages_received
+            .get_or_create(&TopicLabels::from_received(topic))
+            .inc();
+    }
+
+    pub(crate) fn note_dropped_pubsub_message(&self, topic: &TopicHash) {
+        self.gossipsub_messages_dropped
+            .get_or_create(&TopicLabels::from_received(topic))
+            .inc();
+    }
+
+    pub(crate) fn note_suppressed_pubsub_message(&self, topic: &TopicHash) {
+        self.gossipsub_messages_suppressed
+            .get_or_create(&TopicLabels::from_received(topic))
+            .inc();
+    }
+
+    pub(crate) fn note_pubsub_channel_depth(&self, topic: &TopicHash, depth: usize) {
+        self.gossipsub_channel_depth
+            .get_or_create(&TopicLabels::from_received(topic))
+            .set(depth as i64);
     }
 
     pub(crate) fn note_published_pubsub_message(&self, topic_str: &str) {
@@ -73,6 +128,10 @@ impl NetworkMetrics {
             .inc();
     }
 
+    pub(crate) fn note_relay_reservations(&self, num_reservations: usize) {
+        self.relay_reservations.set(num_reservations as i64);
+    }
+
     pub(crate) fn note_response_time(&self, duration: Duration) {
         self.response_times.observe(duration.as_secs_f64());
     }
@@ -1,14 +1,16 @@
-use std::collections::HashMap;
+use std::{
+    collections::{HashMap, HashSet},
+    time::Duration,
+};
 
 use bytes::Bytes;
-#[cfg(feature = "metrics")]
 use instant::Instant;
 use libp2p::{
     gossipsub,
-    kad::{QueryId, Record},
+    kad::{QueryId, Record, RecordKey},
     request_response::{InboundRequestId, OutboundRequestId, ResponseChannel},
-    swarm::NetworkInfo,
-    Multiaddr, PeerId,
+    swarm::{ConnectionId, NetworkInfo},
+    Multiaddr, PeerId, StreamProtocol,
 };
 use nimiq_bls::KeyPair;
 use nimiq_network_interface::{
@@ -23,7 +25,10 @@ use thiserror::Error;
 use tokio::sync::{mpsc, oneshot};
 
 use crate::{
-    dispatch::codecs::{IncomingRequest, OutgoingResponse},
+    dispatch::{
+        codecs::{IncomingRequest, OutgoingResponse},
+        dedup::MessageDedupCache,
+    },
     rate_limiting::RequestRateLimitData,
     NetworkError,
 };
@@ -47,6 +52,17 @@ pub(crate) enum NetworkAction {
         value: Vec<u8>,
         output: oneshot::Sender<Result<(), NetworkError>>,
     },
+    DhtGetProviders {
+        key: RecordKey,
+        output: mpsc::UnboundedSender<PeerId>,
+    },
+    DhtStartProviding {
+        key: RecordKey,
+        output: oneshot::Sender<Result<(), NetworkError>>,
+    },
+    DhtStopProviding {
+        key: RecordKey,
+    },
     Subscribe {
         topic_name: String,
         buffer_size: usize,
@@ -88,6 +104,10 @@ pub(crate) enum NetworkAction {
     },
     ListenOn {
         listen_addresses: Vec<Multiaddr>,
+        output: oneshot::Sender<Result<(), NetworkError>>,
+    },
+    ListenAddresses {
+        output: oneshot::Sender<Vec<Multiaddr>>,
     },
     ConnectPeersByServices {
         services: Services,
@@ -95,10 +115,43 @@ pub(crate) enum NetworkAction {
         output: oneshot::Sender<Vec<PeerId>>,
     },
     StartConnecting,
+    SetDiscoveryPaused {
+        paused: bool,
+    },
+    DiscoveryPaused {
+        output: oneshot::Sender<bool>,
+    },
+    RediscoverExternalAddress,
     DisconnectPeer {
         peer_id: PeerId,
         reason: CloseReason,
     },
+    Shutdown {
+        output: oneshot::Sender<()>,
+    },
+    ConnectionBackoff {
+        peer_id: PeerId,
+        output: oneshot::Sender<Option<Duration>>,
+    },
+    PeerConnectionInfo {
+        peer_id: PeerId,
+        output: oneshot::Sender<Option<PeerConnectionInfo>>,
+    },
+    PeerScore {
+        peer_id: PeerId,
+        output: oneshot::Sender<Option<i32>>,
+    },
+    BanPeer {
+        peer_id: PeerId,
+        duration: Option<Duration>,
+    },
+    UnbanPeer {
+        peer_id: PeerId,
+        output: oneshot::Sender<bool>,
+    },
+    BannedPeers {
+        output: oneshot::Sender<Vec<(PeerId, Option<Duration>)>>,
+    },
 }
 
 pub(crate) struct ValidateMessage<P: Clone> {
@@ -136,6 +189,18 @@ pub(crate) enum DhtBootStrapState {
     Completed,
 }
 
+/// Graceful shutdown state
+#[derive(Default, PartialEq)]
+pub(crate) enum ShutdownState {
+    /// The network is running normally
+    #[default]
+    Running,
+    /// No new connections are accepted, waiting for in-flight requests to finish
+    Draining,
+    /// All connections are being closed
+    Disconnecting,
+}
+
 /// Enum over all of the possible DHT records values
 #[derive(Clone, PartialEq)]
 pub(crate) enum DhtRecord {
@@ -208,6 +273,60 @@ impl TryFrom<&Record> for DhtRecord {
     }
 }
 
+/// Direction of a connection to a peer
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ConnectionDirection {
+    /// The peer dialed us
+    Inbound,
+    /// We dialed the peer
+    Outbound,
+}
+
+/// Change of an entry of the DHT routing table
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DhtRoutingChange {
+    /// The peer was added to a bucket
+    Inserted,
+    /// The addresses or the status of the peer's entry were updated
+    Updated,
+    /// The peer was removed from its bucket, either to make room for another peer or
+    /// because it disconnected
+    Evicted,
+}
+
+/// Event emitted when the DHT routing table changes
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DhtRoutingEvent {
+    /// The peer whose entry changed
+    pub peer_id: PeerId,
+    /// Index of the bucket of the peer's entry, i.e. the base 2 logarithm of the distance
+    /// between the peer and us
+    pub bucket: u32,
+    /// Kind of change
+    pub change: DhtRoutingChange,
+}
+
+/// Information about the connection to a peer
+#[derive(Clone, Debug)]
+pub struct PeerConnectionInfo {
+    /// Time at which the first connection to the peer was established
+    pub connected_since: Instant,
+    /// Direction of the first connection to the peer
+    pub direction: ConnectionDirection,
+    /// Protocols the peer has been observed to speak over the connection
+    pub protocols: HashSet<StreamProtocol>,
+}
+
+impl PeerConnectionInfo {
+    pub(crate) fn new(direction: ConnectionDirection) -> Self {
+        Self {
+            connected_since: Instant::now(),
+            direction,
+            protocols: HashSet::new(),
+        }
+    }
+}
+
 /// DHT results obtained for a specific query ID
 pub(crate) struct DhtResults {
     /// Number of records obtained
@@ -226,6 +345,10 @@ pub(crate) struct TaskState {
     pub(crate) dht_gets: HashMap<QueryId, oneshot::Sender<Result<Vec<u8>, NetworkError>>>,
     /// Get results for DHT (kad) get operation
     pub(crate) dht_get_results: HashMap<QueryId, DhtResults>,
+    /// Senders for DHT (kad) start providing operations
+    pub(crate) dht_start_providing: HashMap<QueryId, oneshot::Sender<Result<(), NetworkError>>>,
+    /// Senders for the providers found by DHT (kad) get providers operations
+    pub(crate) dht_get_providers: HashMap<QueryId, mpsc::UnboundedSender<PeerId>>,
     /// Senders per Gossibsub topic
     pub(crate) gossip_topics: HashMap<
         gossipsub::TopicHash,
@@ -238,6 +361,18 @@ pub(crate) struct TaskState {
     pub(crate) dht_bootstrap_state: DhtBootStrapState,
     /// DHT (kad) is in server mode
     pub(crate) dht_server_mode: bool,
+    /// Number of populated DHT (kad) buckets required to consider the DHT bootstrapped
+    pub(crate) dht_bootstrap_min_buckets: usize,
+    /// Recently seen gossipsub message IDs, to suppress duplicate messages
+    pub(crate) gossip_dedup_cache: MessageDedupCache,
+    /// Addresses of the relay peers that peers are dialed through if a direct dial fails
+    pub(crate) relay_peers: Vec<Multiaddr>,
+    /// Peers that are currently being dialed through the relay peers, with the ID of the dial
+    pub(crate) relayed_dials: HashMap<PeerId, ConnectionId>,
+    /// Peers holding a reservation on our relay server
+    pub(crate) relay_reservations: HashSet<PeerId>,
+    /// `NetworkEvent::DhtBootstrapped` has been emitted
+    pub(crate) dht_bootstrapped_notified: bool,
     /// Senders per `OutboundRequestId` for request-response
     pub(crate) requests: HashMap<OutboundRequestId, oneshot::Sender<Result<Bytes, RequestError>>>,
     /// Time spent per `OutboundRequestId` for request-response
@@ -256,6 +391,12 @@ pub(crate) struct TaskState {
     >,
     /// DHT quorum value
     pub(crate) dht_quorum: u8,
+    /// Graceful shutdown state
+    pub(crate) shutdown_state: ShutdownState,
+    /// Senders to notify once the graceful shutdown has completed
+    pub(crate) shutdown_waiters: Vec<oneshot::Sender<()>>,
+    /// Connection information per connected peer
+    pub(crate) peer_connections: HashMap<PeerId, PeerConnectionInfo>,
 }
 
 #[derive(Clone, Debug)]
@@ -8,6 +8,15 @@ use instant::Instant;
 use libp2p::PeerId;
 use nimiq_network_interface::request::{RequestCommon, RequestType};
 
+/// Rate limit configuration that overrides the default limits defined by a request type.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RateLimitConfig {
+    /// Maximum requests allowed within the time window.
+    pub max_requests: u32,
+    /// The range/window of time.
+    pub time_window: Duration,
+}
+
 /// The rate limiting request metadata that will be passed on between the network and the swarm.
 /// This is not sent through the wire.
 #[derive(Debug, PartialEq)]
@@ -16,13 +25,22 @@ pub(crate) struct RequestRateLimitData {
     pub(crate) max_requests: u32,
     ///  The range/window of time of this request type.
     pub(crate) time_window: Duration,
+    /// The name of this request type.
+    pub(crate) type_name: &'static str,
 }
 
 impl RequestRateLimitData {
-    pub(crate) fn new<Req: RequestCommon>() -> Self {
+    /// Creates the rate limiting data for the given request type.
+    /// If an override is given, it takes precedence over the limits defined by the request type.
+    pub(crate) fn new<Req: RequestCommon>(rate_limit_override: Option<&RateLimitConfig>) -> Self {
+        let (max_requests, time_window) = match rate_limit_override {
+            Some(config) => (config.max_requests, config.time_window),
+            None => (Req::MAX_REQUESTS, Req::TIME_WINDOW),
+        };
         Self {
-            max_requests: Req::MAX_REQUESTS,
-            time_window: Req::TIME_WINDOW,
+            max_requests,
+            time_window,
+            type_name: Req::type_name::<Req>(),
         }
     }
 }
@@ -1,8 +1,13 @@
-use std::{collections::HashMap, num::NonZeroU8, sync::Arc};
+use std::{
+    collections::{HashMap, HashSet},
+    num::NonZeroU8,
+    sync::Arc,
+};
 
 use futures::StreamExt;
-#[cfg(feature = "metrics")]
 use instant::Instant;
+#[cfg(not(target_family = "wasm"))]
+use libp2p::quic;
 #[cfg(all(target_family = "wasm", not(feature = "tokio-websocket")))]
 use libp2p::websocket_websys;
 use libp2p::{
@@ -12,23 +17,27 @@ use libp2p::{
         muxing::StreamMuxerBox,
         transport::{Boxed, MemoryTransport},
     },
-    gossipsub,
+    gossipsub, identify,
     identity::Keypair,
-    kad::{self, store::RecordStore, GetRecordOk, InboundRequest, QueryResult, Quorum, Record},
-    noise,
+    kad::{
+        self, store::RecordStore, GetProvidersOk, GetRecordOk, InboundRequest, QueryResult, Quorum,
+        Record,
+    },
+    multiaddr::Protocol,
+    noise, ping, relay,
     request_response::{self},
     swarm::{
         dial_opts::{DialOpts, PeerCondition},
-        SwarmEvent,
+        DialError, SwarmEvent,
     },
-    yamux, PeerId, Swarm, SwarmBuilder, Transport,
+    yamux, Multiaddr, PeerId, StreamProtocol, Swarm, SwarmBuilder, Transport,
 };
 #[cfg(feature = "tokio-websocket")]
 use libp2p::{dns, tcp, websocket};
 use log::Instrument;
 use nimiq_bls::{CompressedPublicKey, KeyPair};
 use nimiq_network_interface::{
-    network::{CloseReason, NetworkEvent},
+    network::{CloseReason, NetworkEvent, Reachability},
     peer_info::PeerInfo,
     request::{peek_type, InboundRequestError, OutboundRequestError, RequestError},
 };
@@ -36,19 +45,25 @@ use nimiq_serde::{Deserialize, Serialize};
 use nimiq_time::Interval;
 use nimiq_utils::tagged_signing::{TaggedSignable, TaggedSigned};
 use nimiq_validator_network::validator_record::ValidatorRecord;
-use parking_lot::RwLock;
+use parking_lot::{Mutex, RwLock};
 use tokio::sync::{broadcast, mpsc};
 
 #[cfg(feature = "metrics")]
 use crate::network_metrics::NetworkMetrics;
 use crate::{
+    bandwidth::BandwidthTracker,
     behaviour,
+    connection_pool::{self, PeerScoreEvent},
     discovery::{behaviour::Event, peer_contacts::PeerContactBook},
+    dispatch::dedup::MessageDedupCache,
+    network::ReconnectTracker,
     network_types::{
-        DhtBootStrapState, DhtRecord, DhtResults, NetworkAction, TaskState, ValidateMessage,
+        ConnectionDirection, DhtBootStrapState, DhtRecord, DhtResults, DhtRoutingChange,
+        DhtRoutingEvent, NetworkAction, PeerConnectionInfo, ShutdownState, TaskState,
+        ValidateMessage,
     },
     rate_limiting::RateLimits,
-    Config, NetworkError, TlsConfig,
+    Config, NetworkError, TlsConfig, DHT_PROTOCOL, DISCOVERY_PROTOCOL, REQRES_PROTOCOL,
 };
 
 type NimiqSwarm = Swarm<behaviour::Behaviour>;
@@ -58,18 +73,37 @@ pub(crate) fn new_swarm(
     contacts: Arc<RwLock<PeerContactBook>>,
     peer_score_params: gossipsub::PeerScoreParams,
     force_dht_server_mode: bool,
+    bandwidth: Arc<BandwidthTracker>,
 ) -> Swarm<behaviour::Behaviour> {
     let keypair = config.keypair.clone();
+    let (relay_transport, relay_client) = if config.enable_relay_client {
+        let (transport, behaviour) = relay::client::new(keypair.public().to_peer_id());
+        (Some(transport), Some(behaviour))
+    } else {
+        (None, None)
+    };
+
     let transport = new_transport(
         &keypair,
         config.memory_transport,
         config.only_secure_ws_connections,
+        config.enable_quic,
+        relay_transport,
         config.tls.as_ref(),
     )
     .unwrap();
-
-    let behaviour =
-        behaviour::Behaviour::new(config, contacts, peer_score_params, force_dht_server_mode);
+    let transport = Transport::map(transport, move |(peer_id, muxer), _| {
+        (peer_id, bandwidth.track(peer_id, muxer))
+    })
+    .boxed();
+
+    let behaviour = behaviour::Behaviour::new(
+        config,
+        contacts,
+        peer_score_params,
+        force_dht_server_mode,
+        relay_client,
+    );
 
     // TODO add proper config
     #[cfg(not(target_family = "wasm"))]
@@ -94,21 +128,41 @@ pub(crate) fn new_swarm(
 pub(crate) async fn swarm_task(
     mut swarm: NimiqSwarm,
     events_tx: broadcast::Sender<NetworkEvent<PeerId>>,
-    mut action_rx: mpsc::Receiver<NetworkAction>,
+    dht_routing_tx: broadcast::Sender<DhtRoutingEvent>,
+    action_rx: mpsc::Receiver<NetworkAction>,
+    high_priority_action_rx: mpsc::Receiver<NetworkAction>,
+    low_priority_action_rx: mpsc::Receiver<NetworkAction>,
     mut validate_rx: mpsc::UnboundedReceiver<ValidateMessage<PeerId>>,
     connected_peers: Arc<RwLock<HashMap<PeerId, PeerInfo>>>,
+    reconnect_tracker: Arc<Mutex<ReconnectTracker>>,
     mut update_scores: Interval,
     contacts: Arc<RwLock<PeerContactBook>>,
     force_dht_server_mode: bool,
     dht_quorum: NonZeroU8,
+    dht_bootstrap_min_buckets: usize,
+    gossip_dedup_cache: MessageDedupCache,
+    relay_peers: Vec<Multiaddr>,
     #[cfg(feature = "metrics")] metrics: Arc<NetworkMetrics>,
 ) {
+    // Reserve a slot on the relay peers, such that others can reach us through them.
+    for relay_address in &relay_peers {
+        let circuit_address = relay_address.clone().with(Protocol::P2pCircuit);
+        if let Err(error) = swarm.listen_on(circuit_address.clone()) {
+            warn!(address = %circuit_address, %error, "Failed to listen on relay");
+        }
+    }
+
     let mut task_state = TaskState {
         dht_server_mode: force_dht_server_mode,
         dht_quorum: dht_quorum.into(),
+        dht_bootstrap_min_buckets,
+        gossip_dedup_cache,
+        relay_peers,
         ..Default::default()
     };
     let mut rate_limiting = RateLimits::default();
+    let mut action_lanes =
+        ActionLanes::new(high_priority_action_rx, action_rx, low_priority_action_rx);
 
     let peer_id = Swarm::local_peer_id(&swarm);
     let task_span = trace_span!("swarm task", peer_id=?peer_id);
@@ -119,6 +173,12 @@ pub(crate) async fn swarm_task(
                 validate_msg = validate_rx.recv() => {
                     if let Some(validate_msg) = validate_msg {
                         let topic = validate_msg.topic;
+                        if matches!(validate_msg.acceptance, gossipsub::MessageAcceptance::Reject) {
+                            swarm
+                                .behaviour_mut()
+                                .pool
+                                .report_peer(validate_msg.pubsub_id.propagation_source, PeerScoreEvent::InvalidMessage);
+                        }
                         let result: Result<bool, gossipsub::PublishError> = swarm
                             .behaviour_mut()
                             .gossipsub
@@ -137,15 +197,15 @@ pub(crate) async fn swarm_task(
                 },
                 event = swarm.next() => {
                     if let Some(event) = event {
-                        handle_event(event, &events_tx, &mut swarm, &mut task_state, &connected_peers, &mut rate_limiting, #[cfg( feature = "metrics")] &metrics);
+                        handle_event(event, &events_tx, &dht_routing_tx, &mut swarm, &mut task_state, &connected_peers, &reconnect_tracker, &mut rate_limiting, #[cfg( feature = "metrics")] &metrics);
                     }
                 },
-                action = action_rx.recv() => {
+                action = action_lanes.next() => {
                     if let Some(action) = action {
                         perform_action(action, &mut swarm, &mut task_state);
                     }
                     else {
-                        // `action_rx.next()` will return `None` if all senders (i.e. the `Network` object) are dropped.
+                        // `ActionLanes::next()` will return `None` if all senders (i.e. the `Network` object) are dropped.
                         break;
                     }
                 },
@@ -153,13 +213,128 @@ pub(crate) async fn swarm_task(
                     swarm.behaviour().update_scores(Arc::clone(&contacts));
                 },
             };
+
+            if task_state.shutdown_state != ShutdownState::Running {
+                advance_shutdown(&mut swarm, &mut task_state, &mut action_lanes);
+            }
         }
     }
     .instrument(task_span)
     .await
 }
 
+/// Maximum number of actions of the higher priority lanes that are received in a row while
+/// actions of the low priority lane are pending.
+const MAX_HIGH_PRIORITY_STREAK: usize = 16;
+
+/// The lanes the swarm task receives its actions from.
+struct ActionLanes {
+    high_priority_action_rx: mpsc::Receiver<NetworkAction>,
+    action_rx: mpsc::Receiver<NetworkAction>,
+    low_priority_action_rx: mpsc::Receiver<NetworkAction>,
+    /// Number of actions received from the higher priority lanes since the last action of the
+    /// low priority lane.
+    high_priority_streak: usize,
+}
+
+impl ActionLanes {
+    fn new(
+        high_priority_action_rx: mpsc::Receiver<NetworkAction>,
+        action_rx: mpsc::Receiver<NetworkAction>,
+        low_priority_action_rx: mpsc::Receiver<NetworkAction>,
+    ) -> Self {
+        Self {
+            high_priority_action_rx,
+            action_rx,
+            low_priority_action_rx,
+            high_priority_streak: 0,
+        }
+    }
+
+    /// Receives the next action. Actions of a higher priority lane are received before the ones
+    /// of lower priority lanes, except that a pending low priority action is received after at
+    /// most `MAX_HIGH_PRIORITY_STREAK` actions of the other lanes, such that the low priority
+    /// lane isn't starved. Returns `None` once all lanes are closed.
+    async fn next(&mut self) -> Option<NetworkAction> {
+        if self.high_priority_streak >= MAX_HIGH_PRIORITY_STREAK {
+            if let Ok(action) = self.low_priority_action_rx.try_recv() {
+                self.high_priority_streak = 0;
+                return Some(action);
+            }
+        }
+
+        tokio::select! {
+            biased;
+            Some(action) = self.high_priority_action_rx.recv() => {
+                self.high_priority_streak = self.high_priority_streak.saturating_add(1);
+                Some(action)
+            },
+            Some(action) = self.action_rx.recv() => {
+                self.high_priority_streak = self.high_priority_streak.saturating_add(1);
+                Some(action)
+            },
+            Some(action) = self.low_priority_action_rx.recv() => {
+                self.high_priority_streak = 0;
+                Some(action)
+            },
+            else => None,
+        }
+    }
+
+    /// Receives an action that is already queued, without waiting for one. Actions of a higher
+    /// priority lane are received before the ones of lower priority lanes.
+    fn try_next(&mut self) -> Option<NetworkAction> {
+        self.high_priority_action_rx
+            .try_recv()
+            .or_else(|_| self.action_rx.try_recv())
+            .or_else(|_| self.low_priority_action_rx.try_recv())
+            .ok()
+    }
+}
+
 fn new_transport(
+    keypair: &Keypair,
+    memory_transport: bool,
+    only_secure_ws_connections: bool,
+    enable_quic: bool,
+    relay_transport: Option<relay::client::Transport>,
+    tls: Option<&TlsConfig>,
+) -> std::io::Result<Boxed<(PeerId, StreamMuxerBox)>> {
+    let mut transport =
+        new_stream_transport(keypair, memory_transport, only_secure_ws_connections, tls)?;
+
+    // Relayed connections are upgraded like the other stream transports.
+    if let Some(relay_transport) = relay_transport {
+        let relay_transport = relay_transport
+            .upgrade(core::upgrade::Version::V1)
+            .authenticate(noise::Config::new(keypair).unwrap())
+            .multiplex(yamux::Config::default())
+            .map(|(peer_id, muxer), _| (peer_id, StreamMuxerBox::new(muxer)));
+        transport = relay_transport
+            .or_transport(transport)
+            .map(|either, _| either.into_inner())
+            .boxed();
+    }
+
+    // QUIC comes with its own encryption and multiplexing, so it's not upgraded like the other
+    // transports. It can't be used if only secure WebSocket connections are allowed.
+    #[cfg(not(target_family = "wasm"))]
+    if enable_quic && !only_secure_ws_connections {
+        let quic = quic::tokio::Transport::new(quic::Config::new(keypair))
+            .map(|(peer_id, muxer), _| (peer_id, StreamMuxerBox::new(muxer)));
+        return Ok(quic
+            .or_transport(transport)
+            .map(|either, _| either.into_inner())
+            .boxed());
+    }
+
+    #[cfg(target_family = "wasm")]
+    let _ = enable_quic; // silence unused variable warning
+
+    Ok(transport)
+}
+
+fn new_stream_transport(
     keypair: &Keypair,
     memory_transport: bool,
     only_secure_ws_connections: bool,
@@ -260,9 +435,11 @@ fn new_transport(
 fn handle_event(
     event: SwarmEvent<behaviour::BehaviourEvent>,
     events_tx: &broadcast::Sender<NetworkEvent<PeerId>>,
+    dht_routing_tx: &broadcast::Sender<DhtRoutingEvent>,
     swarm: &mut NimiqSwarm,
     state: &mut TaskState,
     connected_peers: &RwLock<HashMap<PeerId, PeerInfo>>,
+    reconnect_tracker: &Mutex<ReconnectTracker>,
     rate_limiting: &mut RateLimits,
     #[cfg(feature = "metrics")] metrics: &Arc<NetworkMetrics>,
 ) {
@@ -285,6 +462,16 @@ fn handle_event(
                 "Connection established",
             );
 
+            state.relayed_dials.remove(&peer_id);
+
+            state.peer_connections.entry(peer_id).or_insert_with(|| {
+                PeerConnectionInfo::new(if endpoint.is_dialer() {
+                    ConnectionDirection::Outbound
+                } else {
+                    ConnectionDirection::Inbound
+                })
+            });
+
             if let Some(dial_errors) = concurrent_dial_errors {
                 for (addr, error) in dial_errors {
                     trace!(
@@ -342,12 +529,27 @@ fn handle_event(
             // Remove Peer
             if num_established == 0 {
                 connected_peers.write().remove(&peer_id);
-                swarm.behaviour_mut().remove_peer(peer_id);
+                state.peer_connections.remove(&peer_id);
+                // The relay server drops the reservations of disconnected peers.
+                if state.relay_reservations.remove(&peer_id) {
+                    #[cfg(feature = "metrics")]
+                    metrics.note_relay_reservations(state.relay_reservations.len());
+                }
+                if swarm.behaviour_mut().remove_peer(peer_id) {
+                    emit_dht_routing_event(
+                        swarm,
+                        dht_routing_tx,
+                        peer_id,
+                        DhtRoutingChange::Evicted,
+                    );
+                }
 
                 // Removes or marks to remove the respective rate limits.
                 // Also cleans up the expired rate limits pending to delete.
                 rate_limiting.remove_rate_limits(peer_id);
 
+                reconnect_tracker.lock().peer_left(peer_id);
+
                 let _ = events_tx.send(NetworkEvent::PeerLeft(peer_id));
             }
         }
@@ -379,6 +581,21 @@ fn handle_event(
             );
         }
 
+        SwarmEvent::OutgoingConnectionError {
+            connection_id,
+            peer_id: Some(peer_id),
+            error,
+        } => {
+            if state.relayed_dials.get(&peer_id) == Some(&connection_id) {
+                // The dial through the relays failed as well. Forget about it such that the peer
+                // can be dialed through the relays again the next time a direct dial fails.
+                debug!(%peer_id, %error, "Failed to dial peer through relays");
+                state.relayed_dials.remove(&peer_id);
+            } else if let DialError::Transport(_) = error {
+                dial_via_relays(swarm, state, peer_id);
+            }
+        }
+
         SwarmEvent::Dialing {
             peer_id: Some(peer_id),
             connection_id: _,
@@ -392,6 +609,11 @@ fn handle_event(
             address,
         } => {
             debug!(%address, "New listen address");
+            // A relay server is publicly reachable, so its listen addresses are confirmed as
+            // external addresses right away. They are handed out with the relay reservations.
+            if swarm.behaviour().relay_server.is_enabled() {
+                swarm.add_external_address(address.clone());
+            }
             swarm
                 .behaviour_mut()
                 .discovery
@@ -413,6 +635,20 @@ fn handle_event(
                             log::warn!("Couldn't detect a public reachable address. Validator network operations won't be possible");
                             log::warn!("You may need to find a relay to enable validator network operations");
                         }
+
+                        let old = to_reachability(&old);
+                        let address = match &new {
+                            autonat::NatStatus::Public(address) => Some(address.clone()),
+                            _ => None,
+                        };
+                        let new = to_reachability(&new);
+                        if old != new {
+                            let _ = events_tx.send(NetworkEvent::ReachabilityChanged {
+                                old,
+                                new,
+                                address,
+                            });
+                        }
                     }
                 },
                 behaviour::BehaviourEvent::ConnectionLimits(_) => {}
@@ -537,6 +773,39 @@ fn handle_event(
                                         warn!(query_id = ?id, "PutRecord query result for unknown query ID");
                                     }
                                 }
+                                QueryResult::StartProviding(result) => {
+                                    // Re-announcements are not tracked, only the initial one.
+                                    if let Some(output) = state.dht_start_providing.remove(&id) {
+                                        if output
+                                            .send(result.map(|_| ()).map_err(Into::into))
+                                            .is_err()
+                                        {
+                                            error!(query_id = ?id, error = "receiver hung up", "could not send start providing query result to channel");
+                                        }
+                                    }
+                                }
+                                QueryResult::GetProviders(Ok(GetProvidersOk::FoundProviders {
+                                    providers,
+                                    ..
+                                })) => {
+                                    if let Some(output) = state.dht_get_providers.get(&id) {
+                                        if providers
+                                            .into_iter()
+                                            .any(|provider| output.send(provider).is_err())
+                                        {
+                                            // Nobody is interested in the providers anymore.
+                                            state.dht_get_providers.remove(&id);
+                                            if let Some(mut query) =
+                                                swarm.behaviour_mut().dht.query_mut(&id)
+                                            {
+                                                query.finish();
+                                            }
+                                        }
+                                    }
+                                }
+                                QueryResult::GetProviders(Err(error)) => {
+                                    debug!(query_id = ?id, %error, "GetProviders query failed");
+                                }
                                 QueryResult::Bootstrap(result) => match result {
                                     Ok(result) => {
                                         if result.num_remaining == 0 {
@@ -546,12 +815,18 @@ fn handle_event(
                                             if state.dht_server_mode {
                                                 let _ = events_tx.send(NetworkEvent::DhtReady);
                                             }
+                                            check_dht_bootstrapped(swarm, state, events_tx);
                                         }
                                     }
                                     Err(e) => error!(error = %e, "DHT bootstrap error"),
                                 },
                                 _ => {}
                             }
+
+                            // Dropping the sender ends the stream of providers.
+                            if step.last {
+                                state.dht_get_providers.remove(&id);
+                            }
                         }
                         kad::Event::InboundRequest {
                             request:
@@ -584,6 +859,30 @@ fn handle_event(
                                 );
                             }
                         }
+                        kad::Event::RoutingUpdated {
+                            peer,
+                            is_new_peer,
+                            old_peer,
+                            ..
+                        } => {
+                            if let Some(old_peer) = old_peer {
+                                emit_dht_routing_event(
+                                    swarm,
+                                    dht_routing_tx,
+                                    old_peer,
+                                    DhtRoutingChange::Evicted,
+                                );
+                            }
+                            let change = if is_new_peer {
+                                DhtRoutingChange::Inserted
+                            } else {
+                                DhtRoutingChange::Updated
+                            };
+                            emit_dht_routing_event(swarm, dht_routing_tx, peer, change);
+
+                            note_peer_protocol(state, peer, StreamProtocol::new(DHT_PROTOCOL));
+                            check_dht_bootstrapped(swarm, state, events_tx);
+                        }
                         kad::Event::ModeChanged { new_mode } => {
                             debug!(%new_mode, "DHT mode changed");
                             if new_mode == kad::Mode::Server {
@@ -604,6 +903,12 @@ fn handle_event(
                             peer_address,
                             peer_contact,
                         } => {
+                            note_peer_protocol(
+                                state,
+                                peer_id,
+                                StreamProtocol::new(DISCOVERY_PROTOCOL),
+                            );
+
                             let peer_info =
                                 PeerInfo::new(peer_address.clone(), peer_contact.services);
                             if connected_peers
@@ -615,6 +920,14 @@ fn handle_event(
                                 let _ =
                                     events_tx.send(NetworkEvent::PeerJoined(peer_id, peer_info));
 
+                                // The lock must not be held while calling the hooks since they
+                                // might register further hooks.
+                                let reconnect_hooks =
+                                    reconnect_tracker.lock().peer_joined(&peer_id);
+                                for hook in reconnect_hooks {
+                                    hook(peer_id);
+                                }
+
                                 if swarm.behaviour().is_address_dialable(&peer_address) {
                                     swarm
                                         .behaviour_mut()
@@ -634,6 +947,19 @@ fn handle_event(
                             }
                         }
                         Event::Update => {}
+                        Event::HandshakeTimeout { peer_id } => {
+                            let _ = events_tx.send(NetworkEvent::HandshakeTimeout(peer_id));
+                        }
+                        Event::IncompatiblePeer {
+                            peer_id,
+                            our_version,
+                        } => {
+                            info!(%peer_id, our_version, "Peer speaks an incompatible protocol version");
+                            let _ = events_tx.send(NetworkEvent::IncompatiblePeer {
+                                peer: peer_id,
+                                our_version: our_version.to_string(),
+                            });
+                        }
                     }
                 }
                 behaviour::BehaviourEvent::Gossipsub(event) => match event {
@@ -643,7 +969,26 @@ fn handle_event(
                         message,
                     } => {
                         let topic = message.topic.clone();
-                        if let Some(topic_info) = state.gossip_topics.get_mut(&topic) {
+                        if state
+                            .gossip_dedup_cache
+                            .check_duplicate(&message_id, Instant::now())
+                        {
+                            trace!(%message_id, %topic, "Suppressing duplicate gossipsub message");
+                            // We already handled this message, so there is no need to propagate it again.
+                            if let Err(error) = swarm
+                                .behaviour_mut()
+                                .gossipsub
+                                .report_message_validation_result(
+                                    &message_id,
+                                    &propagation_source,
+                                    gossipsub::MessageAcceptance::Ignore,
+                                )
+                            {
+                                error!(%message_id, %error, "Failed to report message validation result");
+                            }
+                            #[cfg(feature = "metrics")]
+                            metrics.note_suppressed_pubsub_message(&topic);
+                        } else if let Some(topic_info) = state.gossip_topics.get_mut(&topic) {
                             let (output, validate) = topic_info;
                             if !*validate {
                                 if let Err(error) = swarm
@@ -662,12 +1007,21 @@ fn handle_event(
                             if let Err(error) =
                                 output.try_send((message, message_id, propagation_source))
                             {
+                                #[cfg(feature = "metrics")]
+                                if matches!(error, mpsc::error::TrySendError::Full(_)) {
+                                    metrics.note_dropped_pubsub_message(&topic);
+                                }
                                 error!(
                                     %topic,
                                     %error,
                                     "Failed to dispatch gossipsub message",
                                 )
                             }
+                            #[cfg(feature = "metrics")]
+                            metrics.note_pubsub_channel_depth(
+                                &topic,
+                                output.max_capacity() - output.capacity(),
+                            );
                         } else {
                             warn!(topic = %message.topic, "unknown topic hash");
                         }
@@ -695,10 +1049,49 @@ fn handle_event(
                         }
                         Ok(duration) => {
                             trace!(?duration, peer_id = %event.peer, "Ping completed");
+                            note_peer_protocol(state, event.peer, ping::PROTOCOL_NAME);
+                            swarm
+                                .behaviour_mut()
+                                .pool
+                                .report_peer(event.peer, PeerScoreEvent::Latency(duration));
                         }
                     };
                 }
                 behaviour::BehaviourEvent::Pool(event) => match event {},
+                behaviour::BehaviourEvent::Identify(event) => {
+                    if let identify::Event::Received { peer_id, info, .. } = event {
+                        debug!(
+                            %peer_id,
+                            agent_version = %info.agent_version,
+                            protocol_version = %info.protocol_version,
+                            "Received identify info",
+                        );
+                        swarm
+                            .behaviour_mut()
+                            .discovery
+                            .add_observed_address(info.observed_addr);
+                        note_peer_protocol(state, peer_id, identify::PROTOCOL_NAME);
+                        for protocol in info.protocols {
+                            note_peer_protocol(state, peer_id, protocol);
+                        }
+                    }
+                }
+                behaviour::BehaviourEvent::RelayClient(event) => {
+                    debug!(?event, "Relay client event");
+                }
+                behaviour::BehaviourEvent::RelayServer(event) => {
+                    match event {
+                        relay::Event::ReservationReqAccepted { src_peer_id, .. } => {
+                            state.relay_reservations.insert(src_peer_id);
+                        }
+                        relay::Event::ReservationTimedOut { src_peer_id } => {
+                            state.relay_reservations.remove(&src_peer_id);
+                        }
+                        event => debug!(?event, "Relay server event"),
+                    }
+                    #[cfg(feature = "metrics")]
+                    metrics.note_relay_reservations(state.relay_reservations.len());
+                }
                 behaviour::BehaviourEvent::RequestResponse(event) => match event {
                     request_response::Event::Message {
                         peer: peer_id,
@@ -709,6 +1102,12 @@ fn handle_event(
                             request,
                             channel,
                         } => {
+                            note_peer_protocol(
+                                state,
+                                peer_id,
+                                StreamProtocol::new(REQRES_PROTOCOL),
+                            );
+
                             // We might get empty requests (None) because of our codec implementation
                             if let Some(request) = request {
                                 if let Ok(type_id) = peek_type(&request) {
@@ -727,12 +1126,17 @@ fn handle_event(
                                         ) {
                                             debug!(
                                                 %type_id,
+                                                r#type = request_rate_limit_data.type_name,
                                                 %request_id,
                                                 %peer_id,
                                                 max_requests = %request_rate_limit_data.max_requests,
                                                 time_window = ?request_rate_limit_data.time_window,
                                                 "Denied request - exceeded max requests rate",
                                             );
+                                            swarm.behaviour_mut().pool.report_peer(
+                                                peer_id,
+                                                PeerScoreEvent::RateLimitExceeded,
+                                            );
                                             let response: Result<(), InboundRequestError> =
                                                 Err(InboundRequestError::ExceedsRateLimit);
                                             if swarm
@@ -746,6 +1150,7 @@ fn handle_event(
                                             {
                                                 error!(
                                                     %type_id,
+                                                    r#type = request_rate_limit_data.type_name,
                                                     %request_id,
                                                     %peer_id,
                                                     "Could not send rate limit error response"
@@ -821,6 +1226,10 @@ fn handle_event(
                                         %peer_id,
                                         "Could not parse request type",
                                     );
+                                    swarm
+                                        .behaviour_mut()
+                                        .pool
+                                        .report_peer(peer_id, PeerScoreEvent::ProtocolViolation);
                                 }
                             }
                         }
@@ -828,6 +1237,19 @@ fn handle_event(
                             request_id,
                             response,
                         } => {
+                            note_peer_protocol(
+                                state,
+                                peer_id,
+                                StreamProtocol::new(REQRES_PROTOCOL),
+                            );
+
+                            let score_event = if response.is_some() {
+                                PeerScoreEvent::RequestSucceeded
+                            } else {
+                                PeerScoreEvent::RequestFailed
+                            };
+                            swarm.behaviour_mut().pool.report_peer(peer_id, score_event);
+
                             if let Some(channel) = state.requests.remove(&request_id) {
                                 // We might get empty responses (None) because of the implementation of our codecs.
                                 let response = response
@@ -864,6 +1286,10 @@ fn handle_event(
                             %error,
                             "Failed to send request to peer",
                         );
+                        swarm
+                            .behaviour_mut()
+                            .pool
+                            .report_peer(peer_id, PeerScoreEvent::RequestFailed);
                         if let Some(channel) = state.requests.remove(&request_id) {
                             // The request initiator might no longer exist, so silently ignore
                             // any errors while delivering the response.
@@ -902,7 +1328,17 @@ fn perform_action(action: NetworkAction, swarm: &mut NimiqSwarm, state: &mut Tas
             let dial_opts = DialOpts::peer_id(peer_id)
                 .condition(PeerCondition::Disconnected)
                 .build();
-            let result = swarm.dial(dial_opts).map_err(Into::into);
+            let result = swarm.dial(dial_opts).map_err(|error| match &error {
+                DialError::Denied { cause }
+                    if matches!(
+                        cause.downcast_ref::<connection_pool::Error>(),
+                        Some(connection_pool::Error::PeerFiltered)
+                    ) =>
+                {
+                    NetworkError::PeerFiltered(peer_id)
+                }
+                _ => error.into(),
+            });
 
             // The initiator might no longer exist, so we silently ignore any errors here.
             output.send(result).ok();
@@ -916,6 +1352,24 @@ fn perform_action(action: NetworkAction, swarm: &mut NimiqSwarm, state: &mut Tas
             let query_id = swarm.behaviour_mut().dht.get_record(key.into());
             state.dht_gets.insert(query_id, output);
         }
+        NetworkAction::DhtGetProviders { key, output } => {
+            let query_id = swarm.behaviour_mut().dht.get_providers(key);
+            state.dht_get_providers.insert(query_id, output);
+        }
+        NetworkAction::DhtStartProviding { key, output } => {
+            match swarm.behaviour_mut().dht.start_providing(key) {
+                Ok(query_id) => {
+                    // Remember the operation to resolve when we receive a `QueryResult::StartProviding`
+                    state.dht_start_providing.insert(query_id, output);
+                }
+                Err(e) => {
+                    output.send(Err(e.into())).ok();
+                }
+            }
+        }
+        NetworkAction::DhtStopProviding { key } => {
+            swarm.behaviour_mut().dht.stop_providing(&key);
+        }
         NetworkAction::DhtPut { key, value, output } => {
             let local_peer_id = Swarm::local_peer_id(swarm);
 
@@ -1088,15 +1542,80 @@ fn perform_action(action: NetworkAction, swarm: &mut NimiqSwarm, state: &mut Tas
             // The request initiator might no longer exist, so we silently ignore any errors here.
             output.send(result).ok();
         }
-        NetworkAction::ListenOn { listen_addresses } => {
+        NetworkAction::ListenOn {
+            listen_addresses,
+            output,
+        } => {
+            let mut result = if listen_addresses.is_empty() {
+                Ok(())
+            } else {
+                Err(NetworkError::ListenFailed)
+            };
             for listen_address in listen_addresses {
-                Swarm::listen_on(swarm, listen_address)
-                    .expect("Failed to listen on provided address");
+                match Swarm::listen_on(swarm, listen_address.clone()) {
+                    Ok(_) => {
+                        debug!(address = %listen_address, "Listening on address");
+                        result = Ok(());
+                    }
+                    Err(error) => {
+                        error!(address = %listen_address, %error, "Failed to listen on address");
+                    }
+                }
             }
+
+            // The initiator might no longer exist, so we silently ignore any errors here.
+            output.send(result).ok();
+        }
+        NetworkAction::ListenAddresses { output } => {
+            output.send(swarm.listeners().cloned().collect()).ok();
         }
         NetworkAction::StartConnecting => {
             swarm.behaviour_mut().pool.start_connecting();
         }
+        NetworkAction::SetDiscoveryPaused { paused } => {
+            let behaviour = swarm.behaviour_mut();
+            if paused {
+                behaviour.discovery.pause();
+            } else {
+                behaviour.discovery.resume();
+            }
+            behaviour.pool.set_discovery_paused(paused);
+        }
+        NetworkAction::DiscoveryPaused { output } => {
+            output.send(swarm.behaviour().discovery.is_paused()).ok();
+        }
+        NetworkAction::RediscoverExternalAddress => {
+            // Forget the confirmed external addresses and the addresses advertised in our own
+            // contact, and probe them again along with our listen addresses and the addresses
+            // observed by our peers. Addresses that are still reachable are confirmed again by
+            // AutoNAT.
+            let external_addresses: Vec<Multiaddr> = swarm.external_addresses().cloned().collect();
+            for address in &external_addresses {
+                swarm.remove_external_address(address);
+            }
+            let listen_addresses: Vec<Multiaddr> = swarm.listeners().cloned().collect();
+            let candidates = swarm
+                .behaviour_mut()
+                .discovery
+                .rediscover_own_addresses(listen_addresses.clone());
+
+            debug!(
+                num_external_addresses = external_addresses.len(),
+                num_candidates = candidates.len(),
+                "Rediscovering external address"
+            );
+            let mut probed = HashSet::new();
+            let autonat = &mut swarm.behaviour_mut().autonat;
+            for address in external_addresses
+                .into_iter()
+                .chain(listen_addresses)
+                .chain(candidates)
+            {
+                if probed.insert(address.clone()) {
+                    autonat.probe_address(address);
+                }
+            }
+        }
         NetworkAction::ConnectPeersByServices {
             services,
             num_peers,
@@ -1122,6 +1641,164 @@ fn perform_action(action: NetworkAction, swarm: &mut NimiqSwarm, state: &mut Tas
         NetworkAction::DisconnectPeer { peer_id, reason } => {
             swarm.behaviour_mut().pool.close_connection(peer_id, reason)
         }
+        NetworkAction::Shutdown { output } => {
+            if state.shutdown_state == ShutdownState::Running {
+                debug!("Shutting down network");
+                swarm.behaviour_mut().pool.shutdown();
+                state.shutdown_state = ShutdownState::Draining;
+            }
+            state.shutdown_waiters.push(output);
+        }
+        NetworkAction::ConnectionBackoff { peer_id, output } => {
+            output
+                .send(swarm.behaviour().pool.backoff_delay(&peer_id))
+                .ok();
+        }
+        NetworkAction::PeerConnectionInfo { peer_id, output } => {
+            output
+                .send(state.peer_connections.get(&peer_id).cloned())
+                .ok();
+        }
+        NetworkAction::PeerScore { peer_id, output } => {
+            output
+                .send(swarm.behaviour().pool.peer_score(&peer_id))
+                .ok();
+        }
+        NetworkAction::BanPeer { peer_id, duration } => {
+            swarm.behaviour_mut().pool.ban_peer(peer_id, duration);
+        }
+        NetworkAction::UnbanPeer { peer_id, output } => {
+            output
+                .send(swarm.behaviour_mut().pool.unban_peer(&peer_id))
+                .ok();
+        }
+        NetworkAction::BannedPeers { output } => {
+            output.send(swarm.behaviour().pool.banned_peers()).ok();
+        }
+    }
+}
+
+/// Dials a peer through the relay peers after a direct dial to it failed. Each peer is only
+/// dialed through the relays once until a connection to it is established or the relayed dial
+/// fails.
+fn dial_via_relays(swarm: &mut NimiqSwarm, state: &mut TaskState, peer_id: PeerId) {
+    if state.relay_peers.is_empty() || swarm.is_connected(&peer_id) {
+        return;
+    }
+
+    let is_relay_peer = state.relay_peers.iter().any(|address| {
+        address
+            .iter()
+            .any(|protocol| protocol == Protocol::P2p(peer_id))
+    });
+    if is_relay_peer || state.relayed_dials.contains_key(&peer_id) {
+        return;
+    }
+
+    let addresses = state
+        .relay_peers
+        .iter()
+        .map(|address| {
+            address
+                .clone()
+                .with(Protocol::P2pCircuit)
+                .with(Protocol::P2p(peer_id))
+        })
+        .collect();
+    debug!(%peer_id, "Direct dial failed, dialing peer through relays");
+    let dial_opts = DialOpts::peer_id(peer_id).addresses(addresses).build();
+    let connection_id = dial_opts.connection_id();
+    match swarm.dial(dial_opts) {
+        Ok(()) => {
+            state.relayed_dials.insert(peer_id, connection_id);
+        }
+        Err(error) => debug!(%peer_id, %error, "Failed to dial peer through relays"),
+    }
+}
+
+/// Emits an event for a change of the DHT routing table to the subscribers, if there are any.
+fn emit_dht_routing_event(
+    swarm: &NimiqSwarm,
+    dht_routing_tx: &broadcast::Sender<DhtRoutingEvent>,
+    peer_id: PeerId,
+    change: DhtRoutingChange,
+) {
+    let local_key = kad::KBucketKey::from(*Swarm::local_peer_id(swarm));
+    let Some(bucket) = local_key.distance(&kad::KBucketKey::from(peer_id)).ilog2() else {
+        // Our own peer ID is never part of the routing table.
+        return;
+    };
+
+    trace!(%peer_id, bucket, ?change, "DHT routing table changed");
+    let _ = dht_routing_tx.send(DhtRoutingEvent {
+        peer_id,
+        bucket,
+        change,
+    });
+}
+
+/// Emits `NetworkEvent::DhtBootstrapped` once the first DHT bootstrap has completed and
+/// enough buckets of the routing table are populated. The event is only emitted once.
+fn check_dht_bootstrapped(
+    swarm: &mut NimiqSwarm,
+    state: &mut TaskState,
+    events_tx: &broadcast::Sender<NetworkEvent<PeerId>>,
+) {
+    if state.dht_bootstrapped_notified || state.dht_bootstrap_state != DhtBootStrapState::Completed
+    {
+        return;
+    }
+
+    let populated_buckets = swarm
+        .behaviour_mut()
+        .dht
+        .kbuckets()
+        .filter(|bucket| bucket.num_entries() > 0)
+        .count();
+    if populated_buckets >= state.dht_bootstrap_min_buckets {
+        debug!(populated_buckets, "DHT bootstrapped");
+        state.dht_bootstrapped_notified = true;
+        let _ = events_tx.send(NetworkEvent::DhtBootstrapped);
+    }
+}
+
+/// Records that a connected peer has been observed to speak the given protocol.
+fn note_peer_protocol(state: &mut TaskState, peer_id: PeerId, protocol: StreamProtocol) {
+    if let Some(peer_connection) = state.peer_connections.get_mut(&peer_id) {
+        peer_connection.protocols.insert(protocol);
+    }
+}
+
+/// Advances a graceful shutdown. The actions that are still queued, e.g. gossip publishes,
+/// are performed first. Once there are no more queued actions, in-flight requests or
+/// pending responses, all connections are closed. The shutdown waiters are notified
+/// as soon as no connection is left.
+fn advance_shutdown(swarm: &mut NimiqSwarm, state: &mut TaskState, action_lanes: &mut ActionLanes) {
+    if state.shutdown_state == ShutdownState::Draining {
+        while let Some(action) = action_lanes.try_next() {
+            perform_action(action, swarm, state);
+        }
+
+        if !state.requests.is_empty() || !state.response_channels.is_empty() {
+            return;
+        }
+
+        debug!("Network drained, closing all connections");
+        let peer_ids: Vec<PeerId> = swarm.connected_peers().copied().collect();
+        for peer_id in peer_ids {
+            swarm
+                .behaviour_mut()
+                .pool
+                .close_connection(peer_id, CloseReason::GoingOffline);
+        }
+        state.shutdown_state = ShutdownState::Disconnecting;
+    }
+
+    if swarm.network_info().num_peers() == 0 {
+        for waiter in state.shutdown_waiters.drain(..) {
+            // The initiator might no longer exist, so we silently ignore any errors here.
+            waiter.send(()).ok();
+        }
     }
 }
 
@@ -1161,6 +1838,14 @@ pub(crate) fn verify_record(record: &Record) -> Option<DhtRecord> {
     None
 }
 
+fn to_reachability(status: &autonat::NatStatus) -> Reachability {
+    match status {
+        autonat::NatStatus::Public(_) => Reachability::Public,
+        autonat::NatStatus::Private => Reachability::Private,
+        autonat::NatStatus::Unknown => Reachability::Unknown,
+    }
+}
+
 fn to_response_error(error: OutboundFailure) -> RequestError {
     match error {
         OutboundFailure::ConnectionClosed => {
@@ -1178,3 +1863,100 @@ fn to_response_error(error: OutboundFailure) -> RequestError {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use nimiq_test_log::test;
+    use tokio::sync::mpsc;
+
+    use super::{ActionLanes, MAX_HIGH_PRIORITY_STREAK};
+    use crate::network_types::NetworkAction;
+
+    #[test(tokio::test)]
+    async fn action_lanes_do_not_starve_the_low_priority_lane() {
+        let (high_priority_action_tx, high_priority_action_rx) = mpsc::channel(64);
+        let (action_tx, action_rx) = mpsc::channel(64);
+        let (low_priority_action_tx, low_priority_action_rx) = mpsc::channel(64);
+        let mut lanes =
+            ActionLanes::new(high_priority_action_rx, action_rx, low_priority_action_rx);
+
+        for _ in 0..MAX_HIGH_PRIORITY_STREAK {
+            high_priority_action_tx
+                .send(NetworkAction::StartConnecting)
+                .await
+                .unwrap();
+            action_tx
+                .send(NetworkAction::StartConnecting)
+                .await
+                .unwrap();
+        }
+        low_priority_action_tx
+            .send(NetworkAction::RediscoverExternalAddress)
+            .await
+            .unwrap();
+
+        // The higher priority lanes are preferred until the streak is exhausted.
+        for _ in 0..MAX_HIGH_PRIORITY_STREAK {
+            assert!(matches!(
+                lanes.next().await,
+                Some(NetworkAction::StartConnecting)
+            ));
+        }
+        assert!(matches!(
+            lanes.next().await,
+            Some(NetworkAction::RediscoverExternalAddress)
+        ));
+
+        // Afterwards the remaining higher priority actions are received.
+        for _ in 0..MAX_HIGH_PRIORITY_STREAK {
+            assert!(matches!(
+                lanes.next().await,
+                Some(NetworkAction::StartConnecting)
+            ));
+        }
+
+        drop(high_priority_action_tx);
+        drop(action_tx);
+        drop(low_priority_action_tx);
+        assert!(lanes.next().await.is_none());
+    }
+
+    #[test(tokio::test)]
+    async fn action_lanes_flush_queued_actions() {
+        let (high_priority_action_tx, high_priority_action_rx) = mpsc::channel(64);
+        let (action_tx, action_rx) = mpsc::channel(64);
+        let (low_priority_action_tx, low_priority_action_rx) = mpsc::channel(64);
+        let mut lanes =
+            ActionLanes::new(high_priority_action_rx, action_rx, low_priority_action_rx);
+
+        assert!(lanes.try_next().is_none());
+
+        low_priority_action_tx
+            .send(NetworkAction::RediscoverExternalAddress)
+            .await
+            .unwrap();
+        action_tx
+            .send(NetworkAction::StartConnecting)
+            .await
+            .unwrap();
+        high_priority_action_tx
+            .send(NetworkAction::StartConnecting)
+            .await
+            .unwrap();
+
+        // All queued actions are received, the ones of higher priority lanes first.
+        assert!(matches!(
+            lanes.try_next(),
+            Some(NetworkAction::StartConnecting)
+        ));
+        assert!(matches!(
+            lanes.try_next(),
+            Some(NetworkAction::StartConnecting)
+        ));
+        assert!(matches!(
+            lanes.try_next(),
+            Some(NetworkAction::RediscoverExternalAddress)
+        ));
+        assert!(lanes.try_next().is_none());
+    }
+}
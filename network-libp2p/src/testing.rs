@@ -0,0 +1,103 @@
+//! Helpers to build networks for tests that run entirely in memory.
+
+use std::{num::NonZeroU8, time::Duration};
+
+use libp2p::{identity::Keypair, multiaddr::multiaddr, Multiaddr};
+use nimiq_network_interface::{network::Network as NetworkInterface, peer_info::Services};
+use nimiq_time::{sleep, timeout};
+use rand::{thread_rng, Rng};
+
+use crate::{discovery::peer_contacts::PeerContact, Config, Libp2pKeyPair, Network};
+
+/// Interval in which the peer count of the networks is checked while waiting for the mesh to form.
+const PEER_CHECK_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Time after which [`build_mesh`] gives up waiting for the mesh to form.
+const MESH_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Returns the deterministic Ed25519 keypair of the node with the given index.
+pub fn keypair(index: usize) -> Keypair {
+    let mut seed = [0u8; 32];
+    seed[..8].copy_from_slice(&(index as u64).to_be_bytes());
+    Libp2pKeyPair::from_seed(seed).0
+}
+
+/// Returns a network configuration suitable for tests that uses the memory transport and
+/// listens on the given memory address.
+pub fn config(keypair: Keypair, address: Multiaddr, desired_peer_count: usize) -> Config {
+    let mut peer_contact = PeerContact {
+        addresses: vec![address.clone()],
+        public_key: keypair.public(),
+        services: Services::all(),
+        timestamp: None,
+    };
+    peer_contact.set_current_time();
+
+    let mut config = Config::new(
+        keypair,
+        peer_contact,
+        vec![],
+        Default::default(),
+        true,
+        Services::all(),
+        None,
+        desired_peer_count,
+        true,
+        false,
+        true,
+        NonZeroU8::new(1).unwrap(),
+    );
+    config.listen_addresses = vec![address];
+    config
+}
+
+/// Builds `n` networks that are connected to each other over the memory transport.
+///
+/// The node with index `i` uses the keypair returned by [`keypair`]`(i)`, such that the peer IDs
+/// are the same across test runs. The memory addresses are picked randomly to allow tests to run
+/// concurrently. Returns once every network has all other networks as peers.
+///
+/// Panics if the mesh doesn't form within 30 seconds.
+pub async fn build_mesh(n: usize) -> Vec<Network> {
+    let base_port = thread_rng().gen::<u64>();
+
+    let mut networks = Vec::with_capacity(n);
+    let mut addresses = Vec::with_capacity(n);
+    for index in 0..n {
+        let address = multiaddr![Memory(base_port.wrapping_add(index as u64))];
+        let network = Network::new(config(keypair(index), address.clone(), n)).await;
+        debug!(index, %address, peer_id = %network.get_local_peer_id(), "Created mesh node");
+
+        networks.push(network);
+        addresses.push(address);
+    }
+
+    // Every node dials all nodes that were created before it, which connects each pair once.
+    for (index, network) in networks.iter().enumerate() {
+        for address in &addresses[..index] {
+            network
+                .dial_address(address.clone())
+                .await
+                .expect("Failed to dial mesh node");
+        }
+    }
+
+    let connected = async {
+        for network in &networks {
+            while network.peer_count() < n.saturating_sub(1) {
+                sleep(PEER_CHECK_INTERVAL).await;
+            }
+        }
+    };
+    if timeout(MESH_TIMEOUT, connected).await.is_err() {
+        let missing_peers: Vec<_> = networks
+            .iter()
+            .map(|network| n.saturating_sub(1).saturating_sub(network.peer_count()))
+            .collect();
+        panic!(
+            "Mesh didn't form within {MESH_TIMEOUT:?}, missing peers per node: {missing_peers:?}"
+        );
+    }
+
+    networks
+}
@@ -0,0 +1,196 @@
+//! Loading of TLS configurations for secure WebSocket from PEM files.
+
+use std::{fs, io, path::Path};
+
+use ring::{
+    rand::SystemRandom,
+    signature::{
+        EcdsaKeyPair, Ed25519KeyPair, KeyPair, RsaKeyPair, ECDSA_P256_SHA256_ASN1_SIGNING,
+        ECDSA_P384_SHA384_ASN1_SIGNING,
+    },
+};
+use rustls_pemfile::Item;
+use thiserror::Error;
+
+use crate::TlsConfig;
+
+/// Errors that can occur while loading a TLS configuration
+#[derive(Debug, Error)]
+pub enum TlsError {
+    #[error("I/O error: {0}")]
+    Io(#[from] io::Error),
+
+    #[error("Invalid TLS private key")]
+    InvalidPrivateKey,
+
+    #[error("Invalid TLS certificate(s)")]
+    InvalidCertificate,
+
+    #[error("No TLS certificate found")]
+    MissingCertificate,
+
+    #[error("TLS private key does not match the leaf certificate")]
+    KeyMismatch,
+}
+
+/// A private key as read from a PEM file.
+enum PrivateKey {
+    Pkcs8(Vec<u8>),
+    Pkcs1(Vec<u8>),
+    Sec1(Vec<u8>),
+}
+
+impl PrivateKey {
+    fn into_der(self) -> Vec<u8> {
+        match self {
+            PrivateKey::Pkcs8(der) | PrivateKey::Pkcs1(der) | PrivateKey::Sec1(der) => der,
+        }
+    }
+}
+
+impl TlsConfig {
+    /// Loads a TLS configuration from a PEM file containing the certificate chain (leaf
+    /// certificate first) and a PEM file containing the private key.
+    ///
+    /// The private key is expected in PKCS#8 format, PKCS#1 and SEC1 keys are accepted as well.
+    /// For PKCS#8 (Ed25519, ECDSA P-256/P-384 or RSA) and PKCS#1 keys it is verified that the key
+    /// belongs to the leaf certificate.
+    pub fn from_pem<P: AsRef<Path>, Q: AsRef<Path>>(
+        cert_path: P,
+        key_path: Q,
+    ) -> Result<Self, TlsError> {
+        let certificates = fs::read(cert_path)?;
+        let private_key = fs::read(key_path)?;
+        Self::parse_pem(&certificates, &private_key)
+    }
+
+    fn parse_pem(certificates_pem: &[u8], private_key_pem: &[u8]) -> Result<Self, TlsError> {
+        let private_key = match rustls_pemfile::read_one(&mut &*private_key_pem)? {
+            Some(Item::Pkcs8Key(key)) => PrivateKey::Pkcs8(key.secret_pkcs8_der().to_vec()),
+            Some(Item::Pkcs1Key(key)) => PrivateKey::Pkcs1(key.secret_pkcs1_der().to_vec()),
+            Some(Item::Sec1Key(key)) => PrivateKey::Sec1(key.secret_sec1_der().to_vec()),
+            _ => return Err(TlsError::InvalidPrivateKey),
+        };
+
+        // We could have several certificates in the same file, read them all and build the array
+        // of certificates that the network requires.
+        let certificates = rustls_pemfile::read_all(&mut &*certificates_pem)
+            .map(|item| match item? {
+                Item::X509Certificate(cert) => Ok(cert.to_vec()),
+                _ => Err(TlsError::InvalidCertificate),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let leaf = certificates.first().ok_or(TlsError::MissingCertificate)?;
+        verify_key_matches_certificate(&private_key, leaf)?;
+
+        Ok(TlsConfig {
+            private_key: private_key.into_der(),
+            certificates,
+        })
+    }
+}
+
+/// Checks that the public key of the given private key is the one of the certificate.
+fn verify_key_matches_certificate(
+    private_key: &PrivateKey,
+    certificate: &[u8],
+) -> Result<(), TlsError> {
+    let (_, certificate) = x509_parser::parse_x509_certificate(certificate)
+        .map_err(|_| TlsError::InvalidCertificate)?;
+    let certificate_public_key = &*certificate.public_key().subject_public_key.data;
+
+    let public_key = match private_key {
+        PrivateKey::Pkcs8(der) => pkcs8_public_key(der)?,
+        PrivateKey::Pkcs1(der) => RsaKeyPair::from_der(der)
+            .map_err(|_| TlsError::InvalidPrivateKey)?
+            .public_key()
+            .as_ref()
+            .to_vec(),
+        // Ring is not able to parse SEC1 keys, thus they can't be verified here.
+        PrivateKey::Sec1(_) => return Ok(()),
+    };
+
+    if public_key != certificate_public_key {
+        return Err(TlsError::KeyMismatch);
+    }
+    Ok(())
+}
+
+/// Derives the public key of a PKCS#8 private key in the encoding used within certificates.
+fn pkcs8_public_key(der: &[u8]) -> Result<Vec<u8>, TlsError> {
+    if let Ok(key_pair) = Ed25519KeyPair::from_pkcs8_maybe_unchecked(der) {
+        return Ok(key_pair.public_key().as_ref().to_vec());
+    }
+
+    let rng = SystemRandom::new();
+    for algorithm in [
+        &ECDSA_P256_SHA256_ASN1_SIGNING,
+        &ECDSA_P384_SHA384_ASN1_SIGNING,
+    ] {
+        if let Ok(key_pair) = EcdsaKeyPair::from_pkcs8(algorithm, der, &rng) {
+            return Ok(key_pair.public_key().as_ref().to_vec());
+        }
+    }
+
+    RsaKeyPair::from_pkcs8(der)
+        .map(|key_pair| key_pair.public_key().as_ref().to_vec())
+        .map_err(|_| TlsError::InvalidPrivateKey)
+}
+
+#[cfg(test)]
+mod tests {
+    use nimiq_test_log::test;
+
+    use super::TlsError;
+    use crate::TlsConfig;
+
+    fn generate_certificate() -> (String, String) {
+        let certificate =
+            rcgen::generate_simple_self_signed(vec!["localhost".to_string()]).unwrap();
+        (
+            certificate.serialize_pem().unwrap(),
+            certificate.serialize_private_key_pem(),
+        )
+    }
+
+    #[test]
+    fn it_loads_matching_key_and_certificate() {
+        let (certificate, private_key) = generate_certificate();
+        let config = TlsConfig::parse_pem(certificate.as_bytes(), private_key.as_bytes()).unwrap();
+
+        assert_eq!(config.certificates.len(), 1);
+        assert!(!config.private_key.is_empty());
+    }
+
+    #[test]
+    fn it_accepts_certificate_chains() {
+        let (leaf, private_key) = generate_certificate();
+        let (intermediate, _) = generate_certificate();
+        let chain = format!("{leaf}{intermediate}");
+        let config = TlsConfig::parse_pem(chain.as_bytes(), private_key.as_bytes()).unwrap();
+
+        assert_eq!(config.certificates.len(), 2);
+    }
+
+    #[test]
+    fn it_rejects_mismatching_key() {
+        let (certificate, _) = generate_certificate();
+        let (_, private_key) = generate_certificate();
+
+        assert!(matches!(
+            TlsConfig::parse_pem(certificate.as_bytes(), private_key.as_bytes()),
+            Err(TlsError::KeyMismatch)
+        ));
+    }
+
+    #[test]
+    fn it_rejects_missing_certificate() {
+        let (_, private_key) = generate_certificate();
+
+        assert!(matches!(
+            TlsConfig::parse_pem(b"", private_key.as_bytes()),
+            Err(TlsError::MissingCertificate)
+        ));
+    }
+}
@@ -11,7 +11,7 @@ use libp2p::{
     noise,
     swarm::{
         dial_opts::{DialOpts, PeerCondition},
-        Swarm, SwarmEvent,
+        dummy, Swarm, SwarmEvent,
     },
     yamux, PeerId, SwarmBuilder, Transport,
 };
@@ -35,6 +35,10 @@ struct TestNode {
 
 impl TestNode {
     pub fn new() -> Self {
+        Self::with_handshake_timeout(Duration::from_secs(10))
+    }
+
+    pub fn with_handshake_timeout(handshake_timeout: Duration) -> Self {
         let keypair = Keypair::generate_ed25519();
         let peer_id = PeerId::from(keypair.public());
 
@@ -60,6 +64,7 @@ impl TestNode {
             house_keeping_interval: Duration::from_secs(1),
             keep_alive: true,
             only_secure_ws_connections: false,
+            handshake_timeout,
         };
 
         let peer_contact = PeerContact {
@@ -240,6 +245,83 @@ pub async fn test_dialing_peer_from_contacts() {
     }
 }
 
+#[test(tokio::test)]
+pub async fn test_handshake_timeout() {
+    // The handshake can't complete within a zero timeout.
+    let mut node1 = TestNode::with_handshake_timeout(Duration::ZERO);
+    let node2 = TestNode::new();
+    let peer2_id = node2.peer_id;
+
+    node1.dial(node2.address.clone());
+
+    spawn(async move {
+        node2.swarm.for_each(|_| async {}).await;
+    });
+
+    loop {
+        match node1.swarm.next().await {
+            Some(SwarmEvent::Behaviour(discovery::Event::HandshakeTimeout { peer_id })) => {
+                assert_eq!(peer_id, peer2_id);
+                break;
+            }
+            Some(SwarmEvent::Behaviour(discovery::Event::Established { .. })) => {
+                panic!("Handshake must not complete");
+            }
+            Some(_) => {}
+            None => panic!("Swarm terminated"),
+        }
+    }
+}
+
+#[test(tokio::test)]
+pub async fn test_incompatible_peer() {
+    let mut node1 = TestNode::new();
+
+    // A peer that doesn't speak the discovery protocol at all.
+    let keypair = Keypair::generate_ed25519();
+    let peer2_id = PeerId::from(keypair.public());
+    let address2 = multiaddr![Memory(thread_rng().gen::<u64>())];
+    let transport = MemoryTransport::default()
+        .upgrade(Version::V1)
+        .authenticate(noise::Config::new(&keypair).unwrap())
+        .multiplex(yamux::Config::default())
+        .boxed();
+    let mut swarm2 = SwarmBuilder::with_existing_identity(keypair)
+        .with_tokio()
+        .with_other_transport(|_| transport)
+        .unwrap()
+        .with_behaviour(|_| dummy::Behaviour)
+        .unwrap()
+        // Keep the connection open although the dummy behaviour doesn't use it.
+        .with_swarm_config(|config| config.with_idle_connection_timeout(Duration::from_secs(30)))
+        .build();
+    Swarm::listen_on(&mut swarm2, address2.clone()).unwrap();
+
+    spawn(async move {
+        swarm2.for_each(|_| async {}).await;
+    });
+
+    node1.dial(address2);
+
+    loop {
+        match node1.swarm.next().await {
+            Some(SwarmEvent::Behaviour(discovery::Event::IncompatiblePeer {
+                peer_id,
+                our_version,
+            })) => {
+                assert_eq!(peer_id, peer2_id);
+                assert_eq!(our_version, nimiq_network_libp2p::DISCOVERY_PROTOCOL);
+                break;
+            }
+            Some(SwarmEvent::Behaviour(discovery::Event::HandshakeTimeout { .. })) => {
+                panic!("Incompatible peer must be detected before the handshake times out");
+            }
+            Some(_) => {}
+            None => panic!("Swarm terminated"),
+        }
+    }
+}
+
 #[test]
 fn test_housekeeping() {
     let mut peer_contact_book = PeerContactBook::new(
@@ -298,3 +380,39 @@ fn test_housekeeping() {
         .get(&old_contact.public_key().clone().to_peer_id())
         .is_none());
 }
+
+#[test(tokio::test)]
+async fn test_rediscover_own_addresses() {
+    let mut node = TestNode::new();
+    let stale_address: Multiaddr = "/dns/stale.local/tcp/443/wss".parse().unwrap();
+    let observed_address: Multiaddr = "/ip4/192.0.2.1/tcp/8443/ws".parse().unwrap();
+
+    let behaviour = node.swarm.behaviour_mut();
+    behaviour.add_own_addresses(vec![stale_address.clone()]);
+    behaviour.add_observed_address(observed_address.clone());
+    behaviour.events.clear();
+
+    let candidates = behaviour.rediscover_own_addresses(vec![node.address.clone()]);
+
+    // Only the listen address is left in our own contact.
+    let own_addresses: Vec<Multiaddr> = node
+        .peer_contact_book
+        .read()
+        .get_own_contact()
+        .addresses()
+        .cloned()
+        .collect();
+    assert_eq!(own_addresses, vec![node.address.clone()]);
+
+    // The removed and the observed addresses are probed again.
+    assert_eq!(candidates.len(), 2);
+    assert!(candidates.contains(&stale_address));
+    assert!(candidates.contains(&observed_address));
+
+    // The observed address is reported as an external address candidate again.
+    let behaviour = node.swarm.behaviour_mut();
+    assert!(behaviour.events.iter().any(|event| matches!(
+        event,
+        libp2p::swarm::ToSwarm::NewExternalAddrCandidate(address) if *address == observed_address
+    )));
+}
@@ -4,7 +4,8 @@ use futures::{Stream, StreamExt};
 use libp2p::{
     gossipsub,
     identity::Keypair,
-    multiaddr::{multiaddr, Multiaddr},
+    kad::RecordKey,
+    multiaddr::{multiaddr, Multiaddr, Protocol},
     PeerId,
 };
 use nimiq_bls::KeyPair;
@@ -14,7 +15,8 @@ use nimiq_network_interface::{
 };
 use nimiq_network_libp2p::{
     discovery::{self, peer_contacts::PeerContact},
-    Config, Network,
+    testing, Config, ConnectionDirection, DhtRoutingChange, Network, NetworkError, PeerFilter,
+    RelayLimits, DISCOVERY_PROTOCOL,
 };
 use nimiq_test_log::test;
 use nimiq_test_utils::test_rng::test_rng;
@@ -56,6 +58,7 @@ fn network_config(address: Multiaddr) -> Config {
             house_keeping_interval: Duration::from_secs(60),
             keep_alive: false,
             only_secure_ws_connections: false,
+            handshake_timeout: Duration::from_secs(10),
         },
         kademlia: Default::default(),
         gossipsub,
@@ -65,8 +68,28 @@ fn network_config(address: Multiaddr) -> Config {
         desired_peer_count: 3,
         autonat_allow_non_global_ips: true,
         only_secure_ws_connections: false,
+        enable_quic: false,
+        enable_relay_client: false,
+        relay_peers: vec![],
+        relay_server: None,
         allow_loopback_addresses: true,
+        identify_agent_version: "test".to_string(),
+        identify_protocol_version: "/nimiq/0.0.1".to_string(),
         dht_quorum: NonZeroU8::new(1).unwrap(),
+        dht_record_ttl: None,
+        dht_publication_interval: None,
+        dht_provider_publication_interval: None,
+        protected_peers: Default::default(),
+        rate_limits: Default::default(),
+        listen_addresses: vec![],
+        connection_backoff_base: Duration::from_secs(1),
+        connection_backoff_max: Duration::from_secs(60),
+        max_concurrent_dials: 3,
+        dht_bootstrap_min_buckets: 1,
+        peer_filter: None,
+        peer_reconnect_window: Duration::from_secs(60),
+        gossip_dedup_cache_size: 1000,
+        gossip_dedup_cache_ttl: Duration::from_secs(60),
     }
 }
 
@@ -89,7 +112,7 @@ impl TestNetwork {
         self.next_address += 1;
 
         let net = Network::new(network_config(address.clone())).await;
-        net.listen_on(vec![address.clone()]).await;
+        net.listen_on(vec![address.clone()]).await.unwrap();
 
         log::debug!(address = %address, peer_id = %net.get_local_peer_id(), "Creating node");
 
@@ -117,10 +140,10 @@ async fn create_connected_networks() -> (Network, Network) {
     let addr2 = multiaddr![Memory(rng.gen::<u64>())];
 
     let net1 = Network::new(network_config(addr1.clone())).await;
-    net1.listen_on(vec![addr1.clone()]).await;
+    net1.listen_on(vec![addr1.clone()]).await.unwrap();
 
     let net2 = Network::new(network_config(addr2.clone())).await;
-    net2.listen_on(vec![addr2.clone()]).await;
+    net2.listen_on(vec![addr2.clone()]).await.unwrap();
 
     log::debug!(address = %addr1, peer_id = %net1.get_local_peer_id(), "Network 1");
     log::debug!(address = %addr2, peer_id = %net2.get_local_peer_id(), "Network 2");
@@ -151,10 +174,10 @@ async fn create_double_connected_networks() -> (Network, Network) {
     let addr2 = multiaddr![Memory(rng.gen::<u64>())];
 
     let net1 = Network::new(network_config(addr1.clone())).await;
-    net1.listen_on(vec![addr1.clone()]).await;
+    net1.listen_on(vec![addr1.clone()]).await.unwrap();
 
     let net2 = Network::new(network_config(addr2.clone())).await;
-    net2.listen_on(vec![addr2.clone()]).await;
+    net2.listen_on(vec![addr2.clone()]).await.unwrap();
 
     log::debug!(address = %addr1, peer_id = %net1.get_local_peer_id(), "Network 1");
     log::debug!(address = %addr2, peer_id = %net2.get_local_peer_id(), "Network 2");
@@ -193,7 +216,7 @@ async fn create_network_with_n_peers(n_peers: usize) -> Vec<Network> {
         addresses.push(addr.clone());
 
         let network = Network::new(network_config(addr.clone())).await;
-        network.listen_on(vec![addr.clone()]).await;
+        network.listen_on(vec![addr.clone()]).await.unwrap();
 
         log::debug!(address = %addr, peer_id = %network.get_local_peer_id(), "Network {}", peer);
         let local_peer_id = network.get_local_peer_id();
@@ -224,7 +247,7 @@ async fn create_network_with_n_peers(n_peers: usize) -> Vec<Network> {
                 Ok(NetworkEvent::PeerJoined(peer_id, _)) => {
                     log::info!(%local_peer_id, %peer_id, "Received peer joined event");
                 }
-                Ok(NetworkEvent::DhtReady) => {}
+                Ok(NetworkEvent::DhtReady | NetworkEvent::DhtBootstrapped) => {}
                 _ => log::error!(?event, "Unexpected NetworkEvent"),
             };
         });
@@ -336,6 +359,138 @@ async fn two_networks_can_connect() {
     assert_eq!(peer1, net1.get_local_peer_id());
 }
 
+#[test(tokio::test)]
+async fn mesh_networks_are_fully_connected() {
+    let networks = testing::build_mesh(4).await;
+
+    for (index, network) in networks.iter().enumerate() {
+        assert_eq!(
+            network.get_local_peer_id(),
+            testing::keypair(index).public().to_peer_id()
+        );
+        assert_eq!(network.peer_count(), 3);
+        for other in &networks {
+            if other.get_local_peer_id() != network.get_local_peer_id() {
+                assert!(network.has_peer(other.get_local_peer_id()));
+            }
+        }
+    }
+}
+
+#[test(tokio::test)]
+async fn two_networks_can_connect_via_quic() {
+    let quic_address: Multiaddr = "/ip4/127.0.0.1/udp/0/quic-v1".parse().unwrap();
+    let quic_config = || {
+        let mut config = network_config(quic_address.clone());
+        config.memory_transport = false;
+        config.enable_quic = true;
+        config
+    };
+
+    let net1 = Network::new(quic_config()).await;
+    net1.listen_on(vec![quic_address.clone()]).await.unwrap();
+    let net2 = Network::new(quic_config()).await;
+
+    // Wait for the listener to report the port it was bound to.
+    let addr1 = loop {
+        if let Some(address) = net1.listen_addresses().await.unwrap().pop() {
+            break address;
+        }
+        sleep(Duration::from_millis(50)).await;
+    };
+
+    let mut events1 = net1.subscribe_events();
+    net2.dial_address(addr1).await.unwrap();
+
+    let event1 = helper::get_next_peer_event(&mut events1).await;
+    helper::assert_peer_joined(&event1, &net2.get_local_peer_id());
+}
+
+#[test(tokio::test)]
+async fn peer_connection_info_reports_direction() {
+    let (net1, net2) = create_connected_networks().await;
+
+    // Network 2 dialed network 1
+    let info1 = net1
+        .peer_connection_info(net2.get_local_peer_id())
+        .await
+        .unwrap()
+        .unwrap();
+    let info2 = net2
+        .peer_connection_info(net1.get_local_peer_id())
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(info1.direction, ConnectionDirection::Inbound);
+    assert_eq!(info2.direction, ConnectionDirection::Outbound);
+    assert!(info1
+        .protocols
+        .iter()
+        .any(|protocol| protocol.as_ref() == DISCOVERY_PROTOCOL));
+
+    assert!(net1
+        .peer_connection_info(PeerId::random())
+        .await
+        .unwrap()
+        .is_none());
+}
+
+#[test(tokio::test)]
+async fn network_info_reports_connected_peers() {
+    let (net1, net2) = create_connected_networks().await;
+
+    let info1 = net1.network_info().await.unwrap();
+    assert_eq!(info1.num_peers(), 1);
+    assert!(info1.connection_counters().num_established_incoming() >= 1);
+
+    let info2 = net2.network_info().await.unwrap();
+    assert_eq!(info2.num_peers(), 1);
+    assert!(info2.connection_counters().num_established_outgoing() >= 1);
+}
+
+#[test(tokio::test)]
+async fn bandwidth_is_tracked_per_peer() {
+    let (net1, net2) = create_connected_networks().await;
+
+    let stats = net1.bandwidth(&net2.get_local_peer_id()).unwrap();
+    assert!(stats.bytes_sent > 0);
+    assert!(stats.bytes_received > 0);
+
+    let total = net1.total_bandwidth();
+    assert!(total.bytes_sent >= stats.bytes_sent);
+    assert!(total.bytes_received >= stats.bytes_received);
+
+    assert!(net1.bandwidth(&PeerId::random()).is_none());
+}
+
+#[test(tokio::test)]
+async fn peer_protocols_lists_negotiated_protocols() {
+    let (net1, net2) = create_connected_networks().await;
+
+    let protocols = net1.peer_protocols(net2.get_local_peer_id()).await.unwrap();
+    assert!(protocols
+        .iter()
+        .any(|protocol| protocol == DISCOVERY_PROTOCOL));
+
+    assert!(net1
+        .peer_protocols(PeerId::random())
+        .await
+        .unwrap()
+        .is_empty());
+}
+
+#[test(tokio::test)]
+async fn peer_score_of_connected_peers() {
+    let (net1, net2) = create_connected_networks().await;
+
+    assert!(net1
+        .peer_score(net2.get_local_peer_id())
+        .await
+        .unwrap()
+        .is_some());
+    assert_eq!(net1.peer_score(PeerId::random()).await.unwrap(), None);
+}
+
 #[test(tokio::test(flavor = "multi_thread", worker_threads = 2))]
 async fn two_networks_can_connect_double_dial() {
     let (net1, net2) = create_double_connected_networks().await;
@@ -431,6 +586,111 @@ async fn dht_put_and_get() {
     assert_eq!(fetched_record, Some(put_record));
 }
 
+#[test(tokio::test)]
+async fn dht_get_providers_without_providers() {
+    let (net1, _net2) = create_connected_networks().await;
+
+    let providers = net1
+        .get_providers(RecordKey::new(b"unknown"), Duration::from_secs(30))
+        .await
+        .unwrap();
+
+    // The lookup terminates on its own, long before the timeout.
+    let providers = timeout(Duration::from_secs(20), providers.collect::<Vec<_>>())
+        .await
+        .expect("Provider lookup should have finished");
+    assert!(providers.is_empty());
+}
+
+#[test(tokio::test)]
+async fn dht_start_providing_and_get_providers() {
+    let (net1, net2) = create_connected_networks().await;
+    let key = RecordKey::new(b"epoch");
+
+    net1.start_providing(key.clone()).await.unwrap();
+
+    let providers = net2
+        .get_providers(key, Duration::from_secs(20))
+        .await
+        .unwrap()
+        .collect::<Vec<_>>()
+        .await;
+    assert!(providers.contains(&net1.get_local_peer_id()));
+}
+
+#[test(tokio::test)]
+async fn dht_routing_events() {
+    let mut rng = thread_rng();
+    let addr1 = multiaddr![Memory(rng.gen::<u64>())];
+    let addr2 = multiaddr![Memory(rng.gen::<u64>())];
+
+    let net1 = Network::new(network_config(addr1.clone())).await;
+    net1.listen_on(vec![addr1.clone()]).await.unwrap();
+
+    let net2 = Network::new(network_config(addr2.clone())).await;
+    net2.listen_on(vec![addr2.clone()]).await.unwrap();
+
+    let mut routing_events2 = net2.subscribe_dht_routing_events();
+    net2.dial_address(addr1).await.unwrap();
+
+    let event = timeout(Duration::from_secs(30), routing_events2.next())
+        .await
+        .expect("A routing table event should be emitted")
+        .unwrap()
+        .unwrap();
+    assert_eq!(event.peer_id, net1.get_local_peer_id());
+    assert_eq!(event.change, DhtRoutingChange::Inserted);
+
+    let mut events2 = net2.subscribe_events();
+    net2.disconnect_peer(net1.get_local_peer_id(), CloseReason::Other)
+        .await;
+    let event2 = helper::get_next_peer_event(&mut events2).await;
+    helper::assert_peer_left(&event2, &net1.get_local_peer_id());
+
+    let evicted = async {
+        while let Some(Ok(routing_event)) = routing_events2.next().await {
+            if routing_event.change == DhtRoutingChange::Evicted {
+                return routing_event;
+            }
+        }
+        panic!("No more routing table events");
+    };
+    let evicted = timeout(Duration::from_secs(30), evicted)
+        .await
+        .expect("The peer should be evicted from the routing table");
+    assert_eq!(evicted.peer_id, net1.get_local_peer_id());
+    assert_eq!(evicted.bucket, event.bucket);
+}
+
+#[test(tokio::test)]
+async fn dht_bootstrapped_event() {
+    let mut rng = thread_rng();
+    let addr1 = multiaddr![Memory(rng.gen::<u64>())];
+    let addr2 = multiaddr![Memory(rng.gen::<u64>())];
+
+    let net1 = Network::new(network_config(addr1.clone())).await;
+    net1.listen_on(vec![addr1.clone()]).await.unwrap();
+
+    let net2 = Network::new(network_config(addr2.clone())).await;
+    net2.listen_on(vec![addr2.clone()]).await.unwrap();
+
+    let mut events2 = net2.subscribe_events();
+    net2.dial_address(addr1).await.unwrap();
+
+    let bootstrapped = async {
+        while let Some(event) = events2.next().await {
+            if let Ok(NetworkEvent::DhtBootstrapped) = event {
+                return;
+            }
+        }
+        panic!("No more events");
+    };
+
+    timeout(Duration::from_secs(30), bootstrapped)
+        .await
+        .expect("DHT should be bootstrapped");
+}
+
 #[test(tokio::test)]
 async fn ban_peer() {
     let (net1, net2) = create_connected_networks().await;
@@ -459,6 +719,153 @@ async fn ban_peer() {
     assert_eq!(net2.get_peers(), &[]);
 }
 
+#[test(tokio::test)]
+async fn ban_and_unban_peer_with_expiry() {
+    let (net1, net2) = create_connected_networks().await;
+    let net1_peer_id = *net1.local_peer_id();
+
+    let mut events2 = net2.subscribe_events();
+    net2.ban_peer(net1_peer_id, None).await.unwrap();
+
+    let event2 = helper::get_next_peer_event(&mut events2).await;
+    helper::assert_peer_left(&event2, &net1_peer_id);
+    assert_eq!(
+        net2.banned_peers().await.unwrap(),
+        vec![(net1_peer_id, None)]
+    );
+
+    // Dialing the banned peer is refused.
+    assert!(net2.dial_peer(net1_peer_id).await.is_err());
+
+    assert!(net2.unban_peer(net1_peer_id).await.unwrap());
+    assert!(!net2.unban_peer(net1_peer_id).await.unwrap());
+    assert!(net2.banned_peers().await.unwrap().is_empty());
+
+    // Temporary bans expire on their own.
+    net2.ban_peer(net1_peer_id, Some(Duration::from_millis(100)))
+        .await
+        .unwrap();
+    assert_eq!(net2.banned_peers().await.unwrap().len(), 1);
+    sleep(Duration::from_millis(500)).await;
+    assert!(net2.banned_peers().await.unwrap().is_empty());
+}
+
+#[test(tokio::test)]
+async fn pause_and_resume_discovery() {
+    let (net1, net2) = create_connected_networks().await;
+
+    assert!(!net1.discovery_paused().await.unwrap());
+    net1.pause_discovery().await.unwrap();
+    assert!(net1.discovery_paused().await.unwrap());
+
+    // Pausing the discovery keeps the existing connections.
+    sleep(Duration::from_millis(100)).await;
+    assert!(net1.has_peer(*net2.local_peer_id()));
+
+    net1.resume_discovery().await.unwrap();
+    assert!(!net1.discovery_paused().await.unwrap());
+}
+
+#[test(tokio::test)]
+async fn peers_connect_through_relay_server() {
+    let mut rng = thread_rng();
+    let relay_addr = multiaddr![Memory(rng.gen::<u64>())];
+    let addr1 = multiaddr![Memory(rng.gen::<u64>())];
+    let addr2 = multiaddr![Memory(rng.gen::<u64>())];
+
+    let mut relay_config = network_config(relay_addr.clone());
+    relay_config.relay_server = Some(RelayLimits::default());
+    let relay = Network::new(relay_config).await;
+    relay.listen_on(vec![relay_addr.clone()]).await.unwrap();
+    let relay_peer = relay_addr.with(Protocol::P2p(relay.get_local_peer_id()));
+
+    // Network 2 reserves a slot on the relay.
+    let mut config2 = network_config(addr2);
+    config2.enable_relay_client = true;
+    config2.relay_peers = vec![relay_peer.clone()];
+    let net2 = Network::new(config2).await;
+    let net2_peer_id = net2.get_local_peer_id();
+    sleep(Duration::from_secs(1)).await;
+
+    let mut config1 = network_config(addr1);
+    config1.enable_relay_client = true;
+    config1.relay_peers = vec![relay_peer.clone()];
+    let net1 = Network::new(config1).await;
+    let mut events1 = net1.subscribe_events();
+
+    net1.dial_address(
+        relay_peer
+            .with(Protocol::P2pCircuit)
+            .with(Protocol::P2p(net2_peer_id)),
+    )
+    .await
+    .unwrap();
+
+    let net2_joined = async {
+        loop {
+            let event = helper::get_next_peer_event(&mut events1).await;
+            if matches!(event, NetworkEvent::PeerJoined(peer_id, _) if peer_id == net2_peer_id) {
+                break;
+            }
+        }
+    };
+    timeout(Duration::from_secs(30), net2_joined)
+        .await
+        .expect("Network 2 should join through the relay");
+}
+
+#[test(tokio::test)]
+async fn peer_filter_rejects_connections() {
+    let mut rng = thread_rng();
+    let addr1 = multiaddr![Memory(rng.gen::<u64>())];
+    let addr2 = multiaddr![Memory(rng.gen::<u64>())];
+
+    let net2 = Network::new(network_config(addr2.clone())).await;
+    net2.listen_on(vec![addr2.clone()]).await.unwrap();
+    let net2_peer_id = net2.get_local_peer_id();
+
+    let mut config1 = network_config(addr1.clone());
+    config1.peer_filter = Some(PeerFilter::DenyList([net2_peer_id].into()));
+    let net1 = Network::new(config1).await;
+    net1.listen_on(vec![addr1.clone()]).await.unwrap();
+
+    // Inbound connections from the denied peer are rejected
+    net2.dial_address(addr1).await.unwrap();
+    sleep(Duration::from_secs(1)).await;
+    assert_eq!(net1.get_peers(), &[]);
+    assert_eq!(net2.get_peers(), &[]);
+
+    // Outbound connections to the denied peer are rejected
+    assert!(matches!(
+        net1.dial_peer(net2_peer_id).await,
+        Err(NetworkError::PeerFiltered(peer_id)) if peer_id == net2_peer_id
+    ));
+}
+
+#[test(tokio::test)]
+async fn reconnect_hook_is_called() {
+    let (net1, net2) = create_connected_networks().await;
+    let net1_peer_id = net1.get_local_peer_id();
+
+    let (reconnect_tx, mut reconnect_rx) = tokio::sync::mpsc::unbounded_channel();
+    net2.on_peer_reconnect(Box::new(move |peer_id| {
+        reconnect_tx.send(peer_id).unwrap();
+    }));
+
+    let mut events2 = net2.subscribe_events();
+    net2.disconnect_peer(net1_peer_id, CloseReason::Other).await;
+    let event2 = helper::get_next_peer_event(&mut events2).await;
+    helper::assert_peer_left(&event2, &net1_peer_id);
+
+    net2.dial_peer(net1_peer_id).await.unwrap();
+
+    let reconnected_peer_id = timeout(Duration::from_secs(10), reconnect_rx.recv())
+        .await
+        .expect("Peer should reconnect")
+        .unwrap();
+    assert_eq!(reconnected_peer_id, net1_peer_id);
+}
+
 pub struct TestTopic;
 
 impl Topic for TestTopic {
@@ -514,4 +921,12 @@ async fn test_gossipsub() {
         net2.publish::<TestTopic>(msg.clone()).await.unwrap();
     }
     net1.network_info().await.unwrap();
+
+    // The swarm task keeps up, so publishing with explicit backpressure policies succeeds.
+    net2.try_publish::<TestTopic>(TestRecord { x: 100 })
+        .await
+        .unwrap();
+    net2.publish_with_timeout::<TestTopic>(TestRecord { x: 101 }, Duration::from_secs(1))
+        .await
+        .unwrap();
 }
@@ -114,10 +114,10 @@ impl TestNetwork {
         let addr2 = multiaddr![Memory(rng.gen::<u64>())];
 
         let net1 = Network::new(network_config(addr1.clone())).await;
-        net1.listen_on(vec![addr1.clone()]).await;
+        net1.listen_on(vec![addr1.clone()]).await.unwrap();
 
         let net2 = Network::new(network_config(addr2.clone())).await;
-        net2.listen_on(vec![addr2.clone()]).await;
+        net2.listen_on(vec![addr2.clone()]).await.unwrap();
 
         log::debug!(address = %addr1, peer_id = %net1.get_local_peer_id(), "Network 1");
         log::debug!(address = %addr2, peer_id = %net2.get_local_peer_id(), "Network 2");
@@ -155,16 +155,16 @@ impl TestNetwork {
         let addr4 = multiaddr![Memory(rng.gen::<u64>())];
 
         let net1 = Network::new(network_config(addr1.clone())).await;
-        net1.listen_on(vec![addr1.clone()]).await;
+        net1.listen_on(vec![addr1.clone()]).await.unwrap();
 
         let net2 = Network::new(network_config(addr2.clone())).await;
-        net2.listen_on(vec![addr2.clone()]).await;
+        net2.listen_on(vec![addr2.clone()]).await.unwrap();
 
         let net3 = Network::new(network_config(addr3.clone())).await;
-        net3.listen_on(vec![addr3.clone()]).await;
+        net3.listen_on(vec![addr3.clone()]).await.unwrap();
 
         let net4 = Network::new(network_config(addr4.clone())).await;
-        net4.listen_on(vec![addr4.clone()]).await;
+        net4.listen_on(vec![addr4.clone()]).await.unwrap();
 
         log::debug!(address = %addr1, peer_id = %net1.get_local_peer_id(), "Network 1");
         log::debug!(address = %addr2, peer_id = %net2.get_local_peer_id(), "Network 2");
@@ -237,6 +237,7 @@ fn network_config(address: Multiaddr) -> Config {
             house_keeping_interval: Duration::from_secs(60),
             keep_alive: true,
             only_secure_ws_connections: false,
+            handshake_timeout: Duration::from_secs(10),
         },
         kademlia: Default::default(),
         gossipsub,
@@ -246,8 +247,28 @@ fn network_config(address: Multiaddr) -> Config {
         desired_peer_count: 3,
         autonat_allow_non_global_ips: true,
         only_secure_ws_connections: false,
+        enable_quic: false,
+        enable_relay_client: false,
+        relay_peers: vec![],
+        relay_server: None,
         allow_loopback_addresses: true,
+        identify_agent_version: "test".to_string(),
+        identify_protocol_version: "/nimiq/0.0.1".to_string(),
         dht_quorum: NonZeroU8::new(1).unwrap(),
+        dht_record_ttl: None,
+        dht_publication_interval: None,
+        dht_provider_publication_interval: None,
+        protected_peers: Default::default(),
+        rate_limits: Default::default(),
+        listen_addresses: vec![],
+        connection_backoff_base: Duration::from_secs(1),
+        connection_backoff_max: Duration::from_secs(60),
+        max_concurrent_dials: 3,
+        dht_bootstrap_min_buckets: 1,
+        peer_filter: None,
+        peer_reconnect_window: Duration::from_secs(60),
+        gossip_dedup_cache_size: 1000,
+        gossip_dedup_cache_ttl: Duration::from_secs(60),
     }
 }
 
@@ -410,6 +431,39 @@ async fn test_valid_request_no_response() {
     };
 }
 
+// Test that a request times out after the given timeout if no response is provided.
+#[test(tokio::test)]
+async fn test_valid_request_no_response_custom_timeout() {
+    let (net1, net2) = TestNetwork::create_connected_networks().await;
+
+    let test_request = TestRequest { request: 42 };
+
+    let net1 = Arc::new(net1);
+
+    spawn({
+        let net1 = Arc::clone(&net1);
+        let test_request = test_request.clone();
+        async move { respond_requests::<TestRequest, TestRequest>(net1, None, test_request).await }
+    });
+
+    sleep(Duration::from_secs(1)).await;
+
+    let start = Instant::now();
+    let received_response = net2
+        .request_with_timeout::<TestRequest>(
+            test_request,
+            net1.get_local_peer_id(),
+            Duration::from_millis(500),
+        )
+        .await;
+
+    assert_eq!(
+        received_response,
+        Err(RequestError::OutboundRequest(OutboundRequestError::Timeout))
+    );
+    assert!(start.elapsed() < Duration::from_secs(5));
+}
+
 // Test that we can send a request and receive a timeout response if no response is
 // provided because the connection to a peer is closed in the middle of the request
 #[test(tokio::test)]
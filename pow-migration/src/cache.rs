@@ -0,0 +1,120 @@
+use std::future::Future;
+
+use nimiq_database::{
+    declare_table,
+    mdbx::MdbxDatabase,
+    traits::{Database, ReadTransaction, WriteTransaction},
+};
+use nimiq_serde::{Deserialize, Serialize};
+
+declare_table!(MigrationCacheTable, "MigrationCache", String => Vec<u8>);
+
+/// Cache for the data fetched from the PoW chain during the migration.
+///
+/// Fetching the registered agents and the accounts requires many RPC requests. Their results are
+/// stored in the database, keyed by the block range or block they were fetched for, such that a
+/// re-run of the migration (e.g. after an RPC failure) doesn't need to fetch them again.
+pub struct MigrationCache {
+    db: MdbxDatabase,
+    table: MigrationCacheTable,
+    force_refetch: bool,
+}
+
+impl MigrationCache {
+    /// Creates the cache in the given database. If `force_refetch` is set, cached values are
+    /// ignored and overwritten with freshly fetched ones.
+    pub fn new(db: MdbxDatabase, force_refetch: bool) -> Self {
+        let cache = MigrationCache {
+            db,
+            table: MigrationCacheTable,
+            force_refetch,
+        };
+        cache.db.create_regular_table(&cache.table);
+        cache
+    }
+
+    /// Returns the value cached for the given key or fetches it and caches it if it isn't
+    /// present. Values are only cached if fetching them succeeded.
+    pub async fn get_or_fetch<T, E, F, Fut>(&self, key: String, fetch: F) -> Result<T, E>
+    where
+        T: Serialize + Deserialize,
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<T, E>>,
+    {
+        if !self.force_refetch {
+            if let Some(value) = self.get(&key) {
+                log::info!(%key, "Using cached migration data");
+                return Ok(value);
+            }
+        }
+
+        let value = fetch().await?;
+
+        let mut txn = self.db.write_transaction();
+        txn.put(&self.table, &key, &value.serialize_to_vec());
+        txn.commit();
+
+        Ok(value)
+    }
+
+    fn get<T: Deserialize>(&self, key: &str) -> Option<T> {
+        let txn = self.db.read_transaction();
+        let bytes = txn.get(&self.table, &key.to_string())?;
+        match T::deserialize_from_vec(&bytes) {
+            Ok(value) => Some(value),
+            Err(error) => {
+                log::warn!(%key, %error, "Ignoring invalid cached migration data");
+                None
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::convert::Infallible;
+
+    use super::*;
+
+    async fn fetch(cache: &MigrationCache, value: u32) -> u32 {
+        cache
+            .get_or_fetch("key".to_string(), || async { Ok::<_, Infallible>(value) })
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn it_caches_fetched_values() {
+        let db = MdbxDatabase::new_volatile(Default::default()).unwrap();
+
+        let cache = MigrationCache::new(db.clone(), false);
+        assert_eq!(fetch(&cache, 1).await, 1);
+        // The cached value is returned instead of the fetched one.
+        assert_eq!(fetch(&cache, 2).await, 1);
+
+        // Failed fetches aren't cached.
+        let result = cache
+            .get_or_fetch("other".to_string(), || async { Err::<u32, _>("failed") })
+            .await;
+        assert_eq!(result, Err("failed"));
+        assert_eq!(cache.get::<u32>("other"), None);
+
+        // The cache persists in the database.
+        let cache = MigrationCache::new(db, false);
+        assert_eq!(fetch(&cache, 3).await, 1);
+    }
+
+    #[tokio::test]
+    async fn it_refetches_values_if_forced() {
+        let db = MdbxDatabase::new_volatile(Default::default()).unwrap();
+        assert_eq!(fetch(&MigrationCache::new(db.clone(), false), 1).await, 1);
+
+        let cache = MigrationCache::new(db.clone(), true);
+        assert_eq!(fetch(&cache, 2).await, 2);
+        assert_eq!(fetch(&cache, 3).await, 3);
+
+        // Refetched values overwrite the cached ones.
+        let cache = MigrationCache::new(db, false);
+        assert_eq!(fetch(&cache, 4).await, 3);
+    }
+}
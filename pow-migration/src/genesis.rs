@@ -1,29 +1,83 @@
-use std::{fs, path::PathBuf, str::FromStr, time::Instant};
+use std::{
+    collections::HashSet,
+    fs,
+    path::{Path, PathBuf},
+    str::FromStr,
+    time::Instant,
+};
 
 use nimiq_database::mdbx::MdbxDatabase;
 use nimiq_genesis_builder::config::GenesisConfig;
 use nimiq_hash::Blake2bHash;
 use nimiq_keys::{KeyPair, SecureGenerate};
-use nimiq_primitives::{coin::Coin, networks::NetworkId};
+use nimiq_primitives::{coin::Coin, networks::NetworkId, policy::Policy};
 use nimiq_rpc::Client;
 use nimiq_vrf::VrfSeed;
 use rand::{rngs::StdRng, SeedableRng};
 use time::OffsetDateTime;
 
 use crate::{
-    async_retryer, exit_with_error,
+    async_retryer,
+    cache::MigrationCache,
+    exit_with_error,
     history::get_history_root,
     state::{get_accounts, get_stakers, get_validators, POW_BLOCK_TIME},
-    types::{BlockWindows, GenesisError, PoSRegisteredAgents},
+    types::{BlockWindows, GenesisError, GenesisValidationError, PoSRegisteredAgents, SupplyCheck},
 };
 
-/// Gets the genesis config file
+/// Hash of the genesis block of the PoW mainnet.
+const POW_MAIN_GENESIS_HASH: &str =
+    "264aaf8a4f9828a76c550635da078eb466306a189fcc03710bee9f649c869d12";
+/// Hash of the genesis block of the PoW testnet.
+const POW_TEST_GENESIS_HASH: &str =
+    "1fc28119e35b1418713218192012c7eda9e1d6d142ce8138a313366bd6068300";
+
+/// Checks that the PoW client is connected to the PoW network that corresponds to the given
+/// target network, by comparing the hash of its genesis block to the expected one.
+pub async fn check_pow_network(
+    pow_client: &Client,
+    network_id: NetworkId,
+) -> Result<(), GenesisError> {
+    let expected = match network_id {
+        NetworkId::TestAlbatross => POW_TEST_GENESIS_HASH,
+        NetworkId::MainAlbatross => POW_MAIN_GENESIS_HASH,
+        _ => {
+            log::error!(%network_id, "Unsupported network ID as a target for the migration process");
+            return Err(GenesisError::InvalidNetworkId(network_id));
+        }
+    };
+    let expected = Blake2bHash::from_str(expected)?;
+
+    let pow_genesis = async_retryer(|| pow_client.get_block_by_number(1, false)).await?;
+    let actual = Blake2bHash::from_str(&pow_genesis.hash)?;
+
+    if actual != expected {
+        log::error!(
+            %network_id,
+            %expected,
+            %actual,
+            "The PoW client is connected to a different network than the migration targets"
+        );
+        return Err(GenesisError::NetworkMismatch {
+            network_id,
+            expected,
+            actual,
+        });
+    }
+    Ok(())
+}
+
+/// Gets the genesis config file.
+/// If a `supply_check` is given, a warning is logged if the genesis accounts don't add up to the
+/// expected supply.
 pub async fn get_pos_genesis(
     pow_client: &Client,
     pow_reg_window: &BlockWindows,
     network_id: NetworkId,
     env: MdbxDatabase,
+    cache: &MigrationCache,
     pos_registered_agents: Option<PoSRegisteredAgents>,
+    supply_check: Option<&SupplyCheck>,
 ) -> Result<GenesisConfig, GenesisError> {
     match network_id {
         NetworkId::TestAlbatross => {}
@@ -73,11 +127,7 @@ pub async fn get_pos_genesis(
     // The parent hash of the PoS genesis is the hash of cutting block
     let parent_hash = Blake2bHash::from_str(&final_block.hash)?;
 
-    // Build up the VRF seed using a random seed generator seeded with the final block hash
-    let mut parent_hash_bytes = [0u8; 32];
-    parent_hash_bytes.copy_from_slice(parent_hash.as_slice());
-    let mut rng = StdRng::from_seed(parent_hash_bytes);
-    let vrf_seed = VrfSeed::default().sign_next_with_rng(&KeyPair::generate(&mut rng), &mut rng);
+    let vrf_seed = derive_genesis_vrf_seed(&parent_hash);
 
     log::info!("Getting PoW account state");
 
@@ -86,19 +136,29 @@ pub async fn get_pos_genesis(
             (registered_agents.stakers, registered_agents.validators)
         } else {
             log::info!("Getting registered validators in the PoW chain");
-            let genesis_validators = get_validators(
-                pow_client,
-                pow_reg_window.registration_start..pow_reg_window.registration_end,
-            )
-            .await?;
+            let registration_window =
+                pow_reg_window.registration_start..pow_reg_window.registration_end;
+            let genesis_validators = cache
+                .get_or_fetch(
+                    format!(
+                        "validators_{}_{}",
+                        registration_window.start, registration_window.end
+                    ),
+                    || get_validators(pow_client, registration_window.clone()),
+                )
+                .await?;
 
             log::info!("Getting registered stakers in the PoW chain");
-            get_stakers(
-                pow_client,
-                &genesis_validators,
-                pow_reg_window.pre_stake_start..pow_reg_window.pre_stake_end,
-            )
-            .await?
+            let pre_stake_window = pow_reg_window.pre_stake_start..pow_reg_window.pre_stake_end;
+            cache
+                .get_or_fetch(
+                    format!(
+                        "stakers_{}_{}",
+                        pre_stake_window.start, pre_stake_window.end
+                    ),
+                    || get_stakers(pow_client, &genesis_validators, pre_stake_window.clone()),
+                )
+                .await?
         };
 
     // Calculate how much stake was burnt into registering validators and stakers
@@ -107,10 +167,30 @@ pub async fn get_pos_genesis(
         .iter()
         .fold(Coin::ZERO, |acc, validator| acc + validator.total_stake);
 
-    let genesis_accounts =
-        get_accounts(pow_client, &final_block, burnt_registration_balance).await?;
+    let (genesis_accounts, report) = cache
+        .get_or_fetch(
+            format!(
+                "accounts_{}_{}",
+                final_block.hash,
+                u64::from(burnt_registration_balance)
+            ),
+            || get_accounts(pow_client, &final_block, burnt_registration_balance),
+        )
+        .await?;
+
+    log::info!(
+        basic_balance = %report.basic_balance,
+        vesting_balance = %report.vesting_balance,
+        htlc_balance = %report.htlc_balance,
+        burnt_registration_balance = %report.burnt_registration_balance,
+        total = %report.total,
+        "Genesis accounts balance reconciliation"
+    );
+    if let Some(supply_check) = supply_check {
+        report.reconcile(supply_check.expected_supply, supply_check.tolerance);
+    }
 
-    Ok(GenesisConfig {
+    let genesis_config = GenesisConfig {
         network: network_id,
         vrf_seed: Some(vrf_seed),
         parent_election_hash: Some(parent_election_hash),
@@ -128,13 +208,299 @@ pub async fn get_pos_genesis(
         basic_accounts: genesis_accounts.basic_accounts,
         vesting_accounts: genesis_accounts.vesting_accounts,
         htlc_accounts: genesis_accounts.htlc_accounts,
-    })
+    };
+
+    validate_genesis(
+        &genesis_config,
+        burnt_registration_balance,
+        final_block.timestamp as u64,
+    )
+    .map_err(|errors| {
+        for error in &errors {
+            log::error!(%error, "Invalid genesis config");
+        }
+        GenesisError::Validation(errors)
+    })?;
+
+    Ok(genesis_config)
+}
+
+/// Derives the VRF seed of the PoS genesis block from the hash of its parent (the PoW cutting
+/// block). The derivation is deterministic, such that independent operators can reproduce and
+/// compare the seed byte by byte:
+/// 1. A `StdRng` (ChaCha12, as of `rand` 0.8) is seeded with the 32 bytes of the parent hash.
+/// 2. A Schnorr key pair is generated from that generator using `KeyPair::generate`.
+/// 3. The default VRF seed is signed with that key pair using `VrfSeed::sign_next_with_rng`,
+///    drawing any further randomness from the same generator.
+///
+/// The parent hash already commits to the block number of the cutting block, so no further
+/// input is needed.
+pub fn derive_genesis_vrf_seed(parent_hash: &Blake2bHash) -> VrfSeed {
+    let mut parent_hash_bytes = [0u8; 32];
+    parent_hash_bytes.copy_from_slice(parent_hash.as_slice());
+    let mut rng = StdRng::from_seed(parent_hash_bytes);
+    VrfSeed::default().sign_next_with_rng(&KeyPair::generate(&mut rng), &mut rng)
+}
+
+/// Checks the invariants of a genesis config generated by the migration without writing it:
+/// - The staked balance (validator deposits plus staker balances) equals the balance that was
+///   burnt in PoW to register validators and stakers, such that the total supply is conserved.
+/// - No validator address is present more than once.
+/// - All stakers delegate to a validator of the genesis config.
+/// - The genesis timestamp is strictly after the timestamp of the parent (cutting) block.
+///
+/// All violations found are returned.
+pub fn validate_genesis(
+    genesis_config: &GenesisConfig,
+    burnt_registration_balance: Coin,
+    parent_timestamp: u64,
+) -> Result<(), Vec<GenesisValidationError>> {
+    let mut errors = vec![];
+
+    let staked = genesis_config
+        .stakers
+        .iter()
+        .fold(Coin::ZERO, |acc, staker| {
+            acc + staker.balance + staker.inactive_balance
+        })
+        + Coin::from_u64_unchecked(
+            Policy::VALIDATOR_DEPOSIT * genesis_config.validators.len() as u64,
+        );
+    if staked != burnt_registration_balance {
+        errors.push(GenesisValidationError::SupplyMismatch {
+            staked,
+            burnt: burnt_registration_balance,
+        });
+    }
+
+    let mut validator_addresses = HashSet::new();
+    for validator in &genesis_config.validators {
+        if !validator_addresses.insert(&validator.validator_address) {
+            errors.push(GenesisValidationError::DuplicateValidator(
+                validator.validator_address.clone(),
+            ));
+        }
+    }
+
+    for staker in &genesis_config.stakers {
+        if !validator_addresses.contains(&staker.delegation) {
+            errors.push(GenesisValidationError::UnknownDelegation {
+                staker: staker.staker_address.clone(),
+                validator: staker.delegation.clone(),
+            });
+        }
+    }
+
+    match genesis_config.timestamp {
+        Some(timestamp) if timestamp.unix_timestamp() > parent_timestamp as i64 => {}
+        Some(timestamp) => errors.push(GenesisValidationError::InvalidTimestamp {
+            timestamp: timestamp.unix_timestamp(),
+            parent_timestamp,
+        }),
+        None => errors.push(GenesisValidationError::MissingTimestamp),
+    }
+
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors)
+    }
+}
+
+/// File format of the genesis config file
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GenesisFormat {
+    Toml,
+    Json,
 }
 
-/// Write the genesis config file to a TOML file
+impl GenesisFormat {
+    /// Selects the format from the extension of the given path. Paths without a `.json`
+    /// extension are written as TOML.
+    pub fn from_path(file_path: &Path) -> Self {
+        match file_path
+            .extension()
+            .and_then(|extension| extension.to_str())
+        {
+            Some(extension) if extension.eq_ignore_ascii_case("json") => GenesisFormat::Json,
+            _ => GenesisFormat::Toml,
+        }
+    }
+}
+
+/// Write the genesis config file to a TOML or JSON file, depending on the extension of the file
 pub fn write_pos_genesis(
     file_path: &PathBuf,
     genesis_config: GenesisConfig,
 ) -> Result<(), GenesisError> {
-    Ok(fs::write(file_path, toml::to_string(&genesis_config)?)?)
+    write_pos_genesis_with_format(
+        file_path,
+        genesis_config,
+        GenesisFormat::from_path(file_path),
+    )
+}
+
+/// Write the genesis config file in the given format
+pub fn write_pos_genesis_with_format(
+    file_path: &PathBuf,
+    genesis_config: GenesisConfig,
+    format: GenesisFormat,
+) -> Result<(), GenesisError> {
+    Ok(fs::write(
+        file_path,
+        serialize_genesis(&genesis_config, format)?,
+    )?)
+}
+
+fn serialize_genesis(
+    genesis_config: &GenesisConfig,
+    format: GenesisFormat,
+) -> Result<String, GenesisError> {
+    Ok(match format {
+        GenesisFormat::Toml => toml::to_string(genesis_config)?,
+        GenesisFormat::Json => serde_json::to_string_pretty(genesis_config)?,
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use nimiq_bls::KeyPair as BlsKeyPair;
+    use nimiq_genesis_builder::config::{GenesisStaker, GenesisValidator};
+    use nimiq_keys::{Address, KeyPair};
+
+    use super::*;
+
+    fn validator(address: &Address) -> GenesisValidator {
+        let mut rng = rand::thread_rng();
+        GenesisValidator {
+            validator_address: address.clone(),
+            signing_key: KeyPair::generate(&mut rng).public,
+            voting_key: BlsKeyPair::generate(&mut rng).public_key,
+            reward_address: address.clone(),
+            inactive_from: None,
+            jailed_from: None,
+            retired: false,
+        }
+    }
+
+    fn staker(address: &Address, delegation: &Address, balance: u64) -> GenesisStaker {
+        GenesisStaker {
+            staker_address: address.clone(),
+            balance: Coin::from_u64_unchecked(balance),
+            delegation: delegation.clone(),
+            inactive_balance: Coin::ZERO,
+            inactive_from: None,
+        }
+    }
+
+    fn genesis_config(
+        validators: Vec<GenesisValidator>,
+        stakers: Vec<GenesisStaker>,
+        timestamp: i64,
+    ) -> GenesisConfig {
+        GenesisConfig {
+            network: NetworkId::TestAlbatross,
+            timestamp: Some(OffsetDateTime::from_unix_timestamp(timestamp).unwrap()),
+            vrf_seed: None,
+            parent_election_hash: None,
+            parent_hash: None,
+            history_root: None,
+            block_number: 0,
+            validators,
+            stakers,
+            basic_accounts: vec![],
+            vesting_accounts: vec![],
+            htlc_accounts: vec![],
+        }
+    }
+
+    #[test]
+    fn derives_genesis_vrf_seed_deterministically() {
+        let parent_hash = Blake2bHash::from([1u8; 32]);
+        let other_parent_hash = Blake2bHash::from([2u8; 32]);
+
+        let vrf_seed = derive_genesis_vrf_seed(&parent_hash);
+        assert_eq!(vrf_seed, derive_genesis_vrf_seed(&parent_hash));
+        assert_ne!(vrf_seed, derive_genesis_vrf_seed(&other_parent_hash));
+
+        // The seed must be a valid successor of the default seed signed by the derived key.
+        let mut parent_hash_bytes = [0u8; 32];
+        parent_hash_bytes.copy_from_slice(parent_hash.as_slice());
+        let key_pair = KeyPair::generate(&mut StdRng::from_seed(parent_hash_bytes));
+        assert!(vrf_seed
+            .verify(&VrfSeed::default(), &key_pair.public)
+            .is_ok());
+    }
+
+    #[test]
+    fn selects_genesis_format_from_extension() {
+        assert_eq!(
+            GenesisFormat::from_path(Path::new("genesis.json")),
+            GenesisFormat::Json
+        );
+        assert_eq!(
+            GenesisFormat::from_path(Path::new("genesis.toml")),
+            GenesisFormat::Toml
+        );
+        assert_eq!(
+            GenesisFormat::from_path(Path::new("genesis")),
+            GenesisFormat::Toml
+        );
+    }
+
+    #[test]
+    fn genesis_json_round_trips() {
+        let validator_address = Address::from([1u8; 20]);
+        let staker_address = Address::from([2u8; 20]);
+        let mut config = genesis_config(
+            vec![validator(&validator_address)],
+            vec![staker(&staker_address, &validator_address, 1000)],
+            1000,
+        );
+        config.vrf_seed = Some(derive_genesis_vrf_seed(&Blake2bHash::from([1u8; 32])));
+        config.parent_hash = Some(Blake2bHash::from([2u8; 32]));
+
+        let json = serialize_genesis(&config, GenesisFormat::Json).unwrap();
+        let deserialized: GenesisConfig = serde_json::from_str(&json).unwrap();
+        assert_eq!(deserialized, config);
+    }
+
+    #[test]
+    fn can_validate_genesis() {
+        let validator_address = Address::from([1u8; 20]);
+        let staker_address = Address::from([2u8; 20]);
+        let unknown_address = Address::from([3u8; 20]);
+        let burnt = Coin::from_u64_unchecked(Policy::VALIDATOR_DEPOSIT + 1000);
+
+        let config = genesis_config(
+            vec![validator(&validator_address)],
+            vec![staker(&staker_address, &validator_address, 1000)],
+            1001,
+        );
+        assert_eq!(validate_genesis(&config, burnt, 1000), Ok(()));
+
+        let config = genesis_config(
+            vec![validator(&validator_address), validator(&validator_address)],
+            vec![staker(&staker_address, &unknown_address, 1000)],
+            1000,
+        );
+        assert_eq!(
+            validate_genesis(&config, burnt, 1000),
+            Err(vec![
+                GenesisValidationError::SupplyMismatch {
+                    staked: Coin::from_u64_unchecked(2 * Policy::VALIDATOR_DEPOSIT + 1000),
+                    burnt,
+                },
+                GenesisValidationError::DuplicateValidator(validator_address),
+                GenesisValidationError::UnknownDelegation {
+                    staker: staker_address,
+                    validator: unknown_address,
+                },
+                GenesisValidationError::InvalidTimestamp {
+                    timestamp: 1000,
+                    parent_timestamp: 1000,
+                },
+            ])
+        );
+    }
 }
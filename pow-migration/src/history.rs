@@ -1,4 +1,4 @@
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 use nimiq_blockchain::{interface::HistoryInterface, HistoryStore};
 use nimiq_database::{
@@ -76,10 +76,20 @@ fn from_pow_transaction(pow_transaction: &PoWTransaction) -> Result<Transaction,
     Ok(tx)
 }
 
+/// Minimum time between two invocations of the history migration progress callback.
+const PROGRESS_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Callback reporting the number of processed blocks and the total number of blocks to be
+/// processed while migrating the history up to a candidate block.
+pub type HistoryProgressCallback = Box<dyn FnMut(u32, u32) + Send>;
+
 /// Task that is responsible for migrating the PoW history into a PoS history up until an instructed block height.
 /// It migrates the history up to the `candidate_block` received in `rx_candidate_block` if the head of the PoW chain
 /// is greater than `candidate_block + block_confirmations` and if not waits for this to happen.
 /// Note that this waiting process is done per block such that the migration can be triggered per confirmed block.
+///
+/// If a `progress` callback is given, it is invoked at most once per `PROGRESS_INTERVAL` and once
+/// the migration up to a candidate block is finished.
 pub async fn migrate_history(
     mut rx_candidate_block: mpsc::Receiver<u32>,
     tx_migration_completed: watch::Sender<u32>,
@@ -87,6 +97,7 @@ pub async fn migrate_history(
     network_id: NetworkId,
     pow_client: Client,
     block_confirmations: u32,
+    mut progress: Option<HistoryProgressCallback>,
 ) {
     let mut history_store_height = get_history_store_height(env.clone(), network_id).await;
     let history_store = HistoryStore::new(env.clone(), network_id);
@@ -108,7 +119,17 @@ pub async fn migrate_history(
         // Get transactions of each block and add them to the PoS history store
         // Migrate up to the candidate_block (exclusive) as this block number eventually becomes
         // the block number for the genesis block on the PoS chain.
-        for block_height in (history_store_height + 1)..candidate_block {
+        let first_block_height = history_store_height + 1;
+        let num_blocks = candidate_block - first_block_height;
+        let mut last_progress = Instant::now();
+        for block_height in first_block_height..candidate_block {
+            if let Some(progress) = progress.as_mut() {
+                if last_progress.elapsed() >= PROGRESS_INTERVAL {
+                    progress(block_height - first_block_height, num_blocks);
+                    last_progress = Instant::now();
+                }
+            }
+
             // Check if we are closing in on the PoW head block.
             // If this is true, we may need to take some extra time in order to make sure
             // that blocks leading up to the head block are confirmed before we
@@ -190,6 +211,10 @@ pub async fn migrate_history(
             txn.commit();
         }
 
+        if let Some(progress) = progress.as_mut() {
+            progress(num_blocks, num_blocks);
+        }
+
         log::info!(
             candidate_block,
             "Finished migrating PoW history up to the candidate block"
@@ -1,3 +1,4 @@
+pub mod cache;
 pub mod genesis;
 pub mod history;
 pub mod monitor;
@@ -9,6 +10,7 @@ use std::{
     future::Future,
     path::PathBuf,
     process::{exit, Command, ExitStatus},
+    sync::OnceLock,
     time::Duration,
 };
 
@@ -19,18 +21,67 @@ use nimiq_keys::Address;
 use nimiq_primitives::networks::NetworkId;
 use nimiq_rpc::Client;
 use nimiq_serde::Serialize;
+use rand::Rng;
 use tokio::time::sleep;
 
 use crate::{
-    genesis::get_pos_genesis,
+    cache::MigrationCache,
+    genesis::{check_pow_network, get_pos_genesis},
     monitor::{
         check_validators_ready, generate_ready_tx, get_ready_txns, send_tx, ValidatorsReadiness,
     },
     state::{get_stakers, get_validators, setup_pow_rpc_server},
-    types::{BlockWindows, Error, PoSRegisteredAgents},
+    types::{BlockWindows, Error, PoSRegisteredAgents, SupplyCheck},
 };
 
-const RETRYER_MAX_ATTEMPTS: u8 = 5;
+/// Policy for retrying requests to the PoW RPC server.
+#[derive(Clone, Debug)]
+pub struct RetryPolicy {
+    /// Maximum number of attempts, including the first one.
+    pub max_attempts: u32,
+    /// Delay before the first retry.
+    pub base_delay: Duration,
+    /// Factor by which the delay is multiplied after each retry.
+    pub backoff: f64,
+    /// Maximum relative deviation that is randomly applied to each delay (e.g. 0.1 for ±10%).
+    pub jitter: f64,
+    /// Upper bound of each delay, after the backoff and jitter have been applied.
+    pub max_delay: Duration,
+}
+
+impl RetryPolicy {
+    /// Returns the delay before the retry following the given (1-based) failed attempt.
+    /// Delays that overflow are capped at the maximum delay, a non-finite jitter is ignored.
+    fn delay(&self, attempt: u32) -> Duration {
+        let exponent = i32::try_from(attempt.saturating_sub(1)).unwrap_or(i32::MAX);
+        let mut delay = self.base_delay.as_secs_f64() * self.backoff.powi(exponent);
+        if self.jitter > 0.0 && self.jitter.is_finite() {
+            delay *= 1.0 + rand::thread_rng().gen_range(-self.jitter..=self.jitter);
+        }
+        Duration::try_from_secs_f64(delay.max(0.0))
+            .map_or(self.max_delay, |delay| delay.min(self.max_delay))
+    }
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy {
+            max_attempts: 5,
+            base_delay: Duration::from_millis(300),
+            backoff: 1.0,
+            jitter: 0.0,
+            max_delay: Duration::from_secs(60),
+        }
+    }
+}
+
+static RETRY_POLICY: OnceLock<RetryPolicy> = OnceLock::new();
+
+/// Sets the retry policy used by `async_retryer`. This can only be done once, before the
+/// first request is retried. Returns the given policy if a policy was already set.
+pub fn set_retry_policy(policy: RetryPolicy) -> Result<(), RetryPolicy> {
+    RETRY_POLICY.set(policy)
+}
 
 static TESTNET_BLOCK_WINDOWS: &BlockWindows = &BlockWindows {
     // The testnet blocks are produced ~every minute.
@@ -82,6 +133,10 @@ pub fn get_block_windows(network_id: NetworkId) -> Result<&'static BlockWindows,
 
 /// Performs the PoS migration from PoW by parsing transactions and state of the PoW
 /// chain and returning a PoS genesis configuration.
+///
+/// The data fetched from the PoW chain is cached in `env`, such that it doesn't need to be
+/// fetched again on a re-run. If `force_refetch` is set, the cache is bypassed.
+/// If a `supply_check` is given, the genesis accounts are reconciled against the expected supply.
 pub async fn migrate(
     pow_client: &Client,
     block_windows: &BlockWindows,
@@ -89,16 +144,28 @@ pub async fn migrate(
     env: MdbxDatabase,
     validator_address: &Option<Address>,
     network_id: NetworkId,
+    force_refetch: bool,
+    supply_check: Option<SupplyCheck>,
 ) -> Result<Option<GenesisConfig>, Error> {
+    // Make sure the PoW client is on the network we are migrating
+    check_pow_network(pow_client, network_id).await?;
+
     // First set up the PoW client for accounts migration
     setup_pow_rpc_server(pow_client).await?;
 
+    let cache = MigrationCache::new(env.clone(), force_refetch);
+
     // Now we obtain the list of registered validators
-    let registered_validators = get_validators(
-        pow_client,
-        block_windows.registration_start..block_windows.registration_end,
-    )
-    .await?;
+    let registration_window = block_windows.registration_start..block_windows.registration_end;
+    let registered_validators = cache
+        .get_or_fetch(
+            format!(
+                "validators_{}_{}",
+                registration_window.start, registration_window.end
+            ),
+            || get_validators(pow_client, registration_window.clone()),
+        )
+        .await?;
 
     log::debug!("This is the list of registered validators:");
 
@@ -155,12 +222,16 @@ pub async fn migrate(
     }
 
     // Now we obtain the stake distribution
-    let (stakers, validators) = get_stakers(
-        pow_client,
-        &registered_validators,
-        block_windows.pre_stake_start..block_windows.pre_stake_end,
-    )
-    .await?;
+    let pre_stake_window = block_windows.pre_stake_start..block_windows.pre_stake_end;
+    let (stakers, validators) = cache
+        .get_or_fetch(
+            format!(
+                "stakers_{}_{}",
+                pre_stake_window.start, pre_stake_window.end
+            ),
+            || get_stakers(pow_client, &registered_validators, pre_stake_window.clone()),
+        )
+        .await?;
 
     log::debug!("This is the list of stakers:");
 
@@ -228,10 +299,12 @@ pub async fn migrate(
         block_windows,
         network_id,
         env.clone(),
+        &cache,
         Some(PoSRegisteredAgents {
             validators: validators.clone(),
             stakers: stakers.clone(),
         }),
+        supply_check.as_ref(),
     )
     .await?;
 
@@ -380,8 +453,20 @@ pub fn exit_with_error<E: Debug>(error: E, message: &'static str) -> ! {
     exit(1);
 }
 
-/// Retries an asynchronous operation until it succeeds or errors after the maximum number of attempts (`RETRYER_MAX_ATTEMPTS`) is reached.
+/// Retries an asynchronous operation according to the retry policy set by `set_retry_policy`
+/// (or the default policy). See `async_retryer_with_policy`.
 pub async fn async_retryer<F, Fut, T, E>(f: F) -> Result<T, E>
+where
+    F: Fn() -> Fut,
+    Fut: Future<Output = Result<T, E>>,
+    E: Debug,
+{
+    async_retryer_with_policy(RETRY_POLICY.get_or_init(RetryPolicy::default), f).await
+}
+
+/// Retries an asynchronous operation until it succeeds or the maximum number of attempts of the
+/// given policy is reached. In the latter case, the last error is returned.
+pub async fn async_retryer_with_policy<F, Fut, T, E>(policy: &RetryPolicy, f: F) -> Result<T, E>
 where
     F: Fn() -> Fut,
     Fut: Future<Output = Result<T, E>>,
@@ -393,20 +478,62 @@ where
         match f().await {
             Ok(val) => return Ok(val),
             Err(err) => {
-                if attempts >= RETRYER_MAX_ATTEMPTS {
+                if attempts >= policy.max_attempts {
                     log::error!(
+                        %attempts,
                         ?err,
                         "Failed to retrieve data from PoW RPC server after multiple attempts"
                     );
                     return Err(err);
                 }
+                let delay = policy.delay(attempts);
                 log::warn!(
                     %attempts,
+                    max_attempts = policy.max_attempts,
+                    ?delay,
                     ?err,
                     "Request to RPC server failed. Retrying request..."
                 );
-                sleep(Duration::from_millis(300)).await;
+                sleep(delay).await;
             }
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn it_applies_the_backoff() {
+        let policy = RetryPolicy {
+            base_delay: Duration::from_secs(1),
+            backoff: 2.0,
+            ..Default::default()
+        };
+        assert_eq!(policy.delay(1), Duration::from_secs(1));
+        assert_eq!(policy.delay(3), Duration::from_secs(4));
+    }
+
+    #[test]
+    fn it_caps_the_delay() {
+        let policy = RetryPolicy {
+            base_delay: Duration::from_secs(1),
+            backoff: 10.0,
+            max_delay: Duration::from_secs(30),
+            ..Default::default()
+        };
+        assert_eq!(policy.delay(2), Duration::from_secs(10));
+        assert_eq!(policy.delay(3), Duration::from_secs(30));
+        // The delay overflows a `Duration`.
+        assert_eq!(policy.delay(1000), Duration::from_secs(30));
+
+        let policy = RetryPolicy {
+            backoff: f64::INFINITY,
+            jitter: f64::INFINITY,
+            max_delay: Duration::from_secs(30),
+            ..Default::default()
+        };
+        assert_eq!(policy.delay(2), Duration::from_secs(30));
+    }
+}
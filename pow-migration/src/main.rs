@@ -10,10 +10,12 @@ use nimiq_pow_migration::{
     genesis::write_pos_genesis,
     get_block_windows,
     history::{get_history_store_height, migrate_history},
-    launch_pos_client, migrate,
+    launch_pos_client, migrate, set_retry_policy,
     state::{get_stakers, get_validators},
+    types::SupplyCheck,
+    RetryPolicy,
 };
-use nimiq_primitives::networks::NetworkId;
+use nimiq_primitives::{coin::Coin, networks::NetworkId};
 use nimiq_rpc::Client;
 use nimiq_utils::spawn;
 use tokio::{
@@ -39,6 +41,30 @@ struct Args {
     /// Optional PoW RPC server password
     #[arg(short, long)]
     password: Option<String>,
+    /// Ignore the data cached from previous runs and fetch it again from the PoW chain
+    #[arg(long)]
+    force_refetch: bool,
+    /// Maximum number of attempts for each request to the PoW RPC server
+    #[arg(long, default_value_t = 5)]
+    rpc_max_attempts: u32,
+    /// Delay before retrying a failed request to the PoW RPC server (e.g. "300ms", "2s")
+    #[arg(long, default_value = "300ms", value_parser = humantime::parse_duration)]
+    rpc_retry_delay: Duration,
+    /// Factor by which the retry delay is multiplied after each failed attempt
+    #[arg(long, default_value_t = 1.0, value_parser = parse_non_negative)]
+    rpc_retry_backoff: f64,
+    /// Maximum relative random deviation of each retry delay (e.g. 0.1 for ±10%)
+    #[arg(long, default_value_t = 0.0, value_parser = parse_non_negative)]
+    rpc_retry_jitter: f64,
+    /// Maximum delay before retrying a failed request to the PoW RPC server
+    #[arg(long, default_value = "1m", value_parser = humantime::parse_duration)]
+    rpc_max_retry_delay: Duration,
+    /// Known PoW supply (in Luna) at the cutting block to reconcile the genesis accounts against
+    #[arg(long)]
+    expected_supply: Option<u64>,
+    /// Maximum discrepancy (in Luna) between the expected supply and the genesis accounts
+    #[arg(long, default_value_t = 0)]
+    supply_tolerance: u64,
     /// Optional additional subcommands
     #[command(subcommand)]
     command: Option<Commands>,
@@ -56,6 +82,15 @@ enum Commands {
     ListValidators,
 }
 
+/// Parses a finite, non-negative factor.
+fn parse_non_negative(value: &str) -> Result<f64, String> {
+    let value: f64 = value.parse().map_err(|error| format!("{error}"))?;
+    if !value.is_finite() || value < 0.0 {
+        return Err("must be a finite, non-negative number".to_string());
+    }
+    Ok(value)
+}
+
 fn initialize_logging() {
     let filter = Targets::new()
         .with_default(LevelFilter::DEBUG)
@@ -85,6 +120,15 @@ async fn main() {
 
     let args = Args::parse();
 
+    set_retry_policy(RetryPolicy {
+        max_attempts: args.rpc_max_attempts,
+        base_delay: args.rpc_retry_delay,
+        backoff: args.rpc_retry_backoff,
+        jitter: args.rpc_retry_jitter,
+        max_delay: args.rpc_max_retry_delay,
+    })
+    .expect("Retry policy must not be set before");
+
     let current_exe_dir = std::env::current_exe()
         .map(|mut path| {
             path.pop();
@@ -130,6 +174,13 @@ async fn main() {
     let block_windows = get_block_windows(config.network_id)
         .unwrap_or_else(|error| exit_with_error(error, "Couldn't get block windows"));
 
+    let supply_check = args.expected_supply.map(|expected_supply| SupplyCheck {
+        expected_supply: Coin::try_from(expected_supply)
+            .unwrap_or_else(|error| exit_with_error(error, "Invalid expected supply")),
+        tolerance: Coin::try_from(args.supply_tolerance)
+            .unwrap_or_else(|error| exit_with_error(error, "Invalid supply tolerance")),
+    });
+
     // Check to see if the client already has consensus
     loop {
         let status = async_retryer(|| pow_client.consensus()).await.unwrap();
@@ -248,6 +299,9 @@ async fn main() {
             config.network_id,
             pow_client.clone(),
             block_windows.block_confirmations,
+            Some(Box::new(|processed_blocks, total_blocks| {
+                info!(processed_blocks, total_blocks, "Migrating the PoW history");
+            })),
         ));
 
         // Check that the `nimiq-client` exists
@@ -321,6 +375,8 @@ async fn main() {
                 env.clone(),
                 &validator_address,
                 config.network_id,
+                args.force_refetch,
+                supply_check.clone(),
             )
             .await
             .unwrap_or_else(|error| exit_with_error(error, "Could not migrate"));
@@ -23,7 +23,7 @@ use nimiq_transaction::account::htlc_contract::{AnyHash, AnyHash32, AnyHash64};
 
 use crate::{
     async_retryer,
-    types::{GenesisAccounts, GenesisValidator, StateError},
+    types::{GenesisAccounts, GenesisValidator, ReconciliationReport, StateError},
 };
 
 /// PoW target block time in seconds
@@ -123,17 +123,22 @@ pub async fn setup_pow_rpc_server(client: &Client) -> Result<(), StateError> {
 }
 
 /// Gets the set of the Genesis Accounts by taking a snapshot of the accounts in
-/// a specific block number defined by `cutting_block`.
+/// a specific block number defined by `cutting_block`, along with a breakdown of
+/// their balances.
 pub async fn get_accounts(
     pow_client: &Client,
     cutting_block: &Block,
     burnt_registration_balance: Coin,
-) -> Result<GenesisAccounts, StateError> {
+) -> Result<(GenesisAccounts, ReconciliationReport), StateError> {
     let mut genesis_accounts = GenesisAccounts {
         vesting_accounts: vec![],
         basic_accounts: vec![],
         htlc_accounts: vec![],
     };
+    let mut report = ReconciliationReport {
+        burnt_registration_balance,
+        ..Default::default()
+    };
     let mut start_prefix = "".to_string();
 
     // Check that the PoW client is already set up
@@ -166,22 +171,29 @@ pub async fn get_accounts(
                         // address balance).
                         pos_basic_account.balance -= burnt_registration_balance;
                     }
+                    report.basic_balance += pos_basic_account.balance;
                     genesis_accounts.basic_accounts.push(pos_basic_account);
                 }
                 nimiq_rpc::primitives::Account::Vesting(pow_account) => {
                     let pos_vesting_account =
                         pos_vesting_account_from_account(&pow_account, cutting_block)?;
+                    report.vesting_balance += pos_vesting_account.balance;
                     genesis_accounts.vesting_accounts.push(pos_vesting_account);
                 }
                 nimiq_rpc::primitives::Account::HTLC(pow_account) => {
                     let pos_htlc_account =
                         pos_htlc_account_from_account(&pow_account, cutting_block)?;
+                    report.htlc_balance += pos_htlc_account.balance;
                     genesis_accounts.htlc_accounts.push(pos_htlc_account);
                 }
             }
         }
     }
-    Ok(genesis_accounts)
+    report.total = report.basic_balance
+        + report.vesting_balance
+        + report.htlc_balance
+        + report.burnt_registration_balance;
+    Ok((genesis_accounts, report))
 }
 
 /// Gets the set of validators registered in the PoW chain by parsing the required
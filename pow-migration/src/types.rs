@@ -4,11 +4,13 @@ use hex::FromHexError;
 use nimiq_genesis_builder::config::{
     GenesisAccount, GenesisHTLC, GenesisStaker, GenesisVestingContract,
 };
+use nimiq_hash::Blake2bHash;
 use nimiq_keys::{Address, AddressParseError};
 use nimiq_primitives::{
     coin::{Coin, CoinConvertError},
     networks::NetworkId,
 };
+use serde_derive::{Deserialize, Serialize};
 use thiserror::Error;
 
 /// PoW block registration window
@@ -68,7 +70,7 @@ pub struct PoSRegisteredAgents {
 }
 
 /// Genesis accounts for the genesis state
-#[derive(Debug)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct GenesisAccounts {
     /// Basic accounts for the genesis state.
     pub basic_accounts: Vec<GenesisAccount>,
@@ -80,8 +82,57 @@ pub struct GenesisAccounts {
     pub htlc_accounts: Vec<GenesisHTLC>,
 }
 
-/// Genesis validators for the genesis state
+/// Supply that the genesis accounts are expected to add up to.
 #[derive(Clone, Debug)]
+pub struct SupplyCheck {
+    /// The known PoW supply at the cutting block.
+    pub expected_supply: Coin,
+    /// Maximum discrepancy between the expected supply and the genesis accounts that is accepted
+    /// without a warning.
+    pub tolerance: Coin,
+}
+
+/// Breakdown of the balances of the genesis accounts, used to reconcile them against the
+/// known PoW supply.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReconciliationReport {
+    /// Total balance of the basic accounts (excluding the burnt registration balance).
+    pub basic_balance: Coin,
+    /// Total balance of the vesting contracts.
+    pub vesting_balance: Coin,
+    /// Total balance of the HTLCs.
+    pub htlc_balance: Coin,
+    /// Balance that was burnt in PoW to register validators and stakers, which is staked in PoS.
+    pub burnt_registration_balance: Coin,
+    /// Sum of all the balances above.
+    pub total: Coin,
+}
+
+impl ReconciliationReport {
+    /// Compares the total balance to the expected supply. Returns false and logs a warning if
+    /// they differ by more than `tolerance`.
+    pub fn reconcile(&self, expected_supply: Coin, tolerance: Coin) -> bool {
+        let discrepancy = if self.total > expected_supply {
+            self.total - expected_supply
+        } else {
+            expected_supply - self.total
+        };
+        if discrepancy > tolerance {
+            log::warn!(
+                total = %self.total,
+                %expected_supply,
+                %discrepancy,
+                %tolerance,
+                "Genesis accounts don't match the expected supply"
+            );
+            return false;
+        }
+        true
+    }
+}
+
+/// Genesis validators for the genesis state
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct GenesisValidator {
     /// Inner genesis validator information
     pub validator: nimiq_genesis_builder::config::GenesisValidator,
@@ -134,6 +185,9 @@ pub enum GenesisError {
     /// Serialization error
     #[error("Serialization: {0}")]
     Serialization(#[from] toml::ser::Error),
+    /// JSON serialization error
+    #[error("JSON serialization: {0}")]
+    JsonSerialization(#[from] serde_json::Error),
     /// Invalid time
     #[error("Invalid timestamp")]
     Timestamp(#[from] time::error::ComponentRange),
@@ -143,6 +197,41 @@ pub enum GenesisError {
     /// Invalid Network ID
     #[error("Invalid network ID {0}")]
     InvalidNetworkId(NetworkId),
+    /// The PoW client is connected to a different network than the migration targets
+    #[error("PoW client is on the wrong network for {network_id}: genesis hash is {actual}, expected {expected}")]
+    NetworkMismatch {
+        network_id: NetworkId,
+        expected: Blake2bHash,
+        actual: Blake2bHash,
+    },
+    /// The generated genesis config violates some invariants
+    #[error("Invalid genesis config: {0:?}")]
+    Validation(Vec<GenesisValidationError>),
+}
+
+/// Invariants of a genesis config that can be violated
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum GenesisValidationError {
+    /// The staked balance doesn't match the balance burnt for registering validators and stakers
+    #[error("Staked balance {staked} doesn't match the burnt registration balance {burnt}")]
+    SupplyMismatch { staked: Coin, burnt: Coin },
+    /// A validator address is present more than once
+    #[error("Duplicate validator address: {0}")]
+    DuplicateValidator(Address),
+    /// A staker delegates to a validator that is not part of the genesis
+    #[error("Staker {staker} delegates to unknown validator {validator}")]
+    UnknownDelegation { staker: Address, validator: Address },
+    /// The genesis timestamp is missing
+    #[error("Missing genesis timestamp")]
+    MissingTimestamp,
+    /// The genesis timestamp is not after the timestamp of the parent block
+    #[error(
+        "Genesis timestamp {timestamp} is not after the parent block timestamp {parent_timestamp}"
+    )]
+    InvalidTimestamp {
+        timestamp: i64,
+        parent_timestamp: u64,
+    },
 }
 
 /// Error types that can be returned
@@ -39,6 +39,8 @@ mod pow_migration_test {
             env,
             &Some(address),
             network_id,
+            false,
+            None,
         )
         .await
     }
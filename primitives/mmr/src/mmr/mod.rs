@@ -480,6 +480,32 @@ impl<H: Merge + Clone + PartialEq, S: Store<H>> MerkleMountainRange<H, S> {
             }
         })
     }
+
+    /// Checks the whole tree in O(n) and returns the indices of all inner nodes that are missing
+    /// or whose hash doesn't match the hash of their children.
+    /// Note that the hashes of the leaves themselves can't be checked here.
+    pub fn find_inconsistent_nodes(&self) -> Vec<usize> {
+        (0..self.len())
+            .filter(|&index| {
+                let pos = Position::from(index);
+                let (Some(left_pos), Some(right_pos)) = (pos.left_child(), pos.right_child())
+                else {
+                    return self.store.get(index).is_none();
+                };
+
+                match (
+                    self.store.get(index),
+                    self.store.get(left_pos.index),
+                    self.store.get(right_pos.index),
+                ) {
+                    (Some(node), Some(left), Some(right)) => {
+                        left.merge(&right, pos.num_leaves() as u64) != node
+                    }
+                    _ => true,
+                }
+            })
+            .collect()
+    }
 }
 
 /// This is the main struct for the Peaks Merkle Mountain Range.
@@ -656,6 +682,24 @@ mod tests {
         }
     }
 
+    #[test]
+    fn it_finds_inconsistent_nodes() {
+        let nodes = vec![2, 3, 5, 7, 11, 13, 17, 19, 23, 29];
+
+        let mut mmr = MerkleMountainRange::<TestHash, _>::new(MemoryStore::new());
+        for v in nodes.iter() {
+            mmr.push(v).unwrap();
+        }
+        assert_eq!(mmr.find_inconsistent_nodes(), Vec::<usize>::new());
+
+        // Replace the first leaf, which invalidates its parent.
+        let mut store = MemoryStore::new();
+        store.inner = (0..mmr.len()).map(|i| mmr.get(i).unwrap()).collect();
+        store.inner[0] = 42usize.hash(1);
+        let mmr = MerkleMountainRange::<TestHash, _>::new(store);
+        assert_eq!(mmr.find_inconsistent_nodes(), vec![2]);
+    }
+
     #[test]
     fn it_correctly_constructs_ptrees() {
         let nodes = vec![2, 3, 5, 7, 11, 13, 17, 19, 23, 29];
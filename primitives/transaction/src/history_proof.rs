@@ -14,9 +14,18 @@ pub struct HistoryTreeProof {
 }
 
 impl HistoryTreeProof {
-    /// Verifies the Merkle proof. It will return None if the verification encounters an error.
+    /// Verifies the Merkle proof against the given history root. This doesn't require access to
+    /// the history store, so it can be used by light clients that only know the root.
+    /// It will return None if the verification encounters an error.
     pub fn verify(&self, expected_root: Blake2bHash) -> Option<bool> {
-        assert_eq!(self.history.len(), self.positions.len());
+        if self.history.len() != self.positions.len() {
+            log::error!(
+                num_history = self.history.len(),
+                num_positions = self.positions.len(),
+                "Number of historic transactions and positions differ"
+            );
+            return None;
+        }
         let zipped: Vec<_> = self
             .positions
             .iter()
@@ -0,0 +1,74 @@
+use nimiq_hash::Blake2bHash;
+use nimiq_keys::Address;
+use nimiq_mmr::{mmr::MerkleMountainRange, store::memory::MemoryStore};
+use nimiq_primitives::{coin::Coin, networks::NetworkId};
+use nimiq_test_log::test;
+use nimiq_transaction::{
+    historic_transaction::{HistoricTransaction, HistoricTransactionData},
+    history_proof::HistoryTreeProof,
+    ExecutedTransaction, Transaction,
+};
+
+fn historic_transaction(value: u64) -> HistoricTransaction {
+    HistoricTransaction {
+        network_id: NetworkId::UnitAlbatross,
+        block_number: 1,
+        block_time: 0,
+        data: HistoricTransactionData::Basic(ExecutedTransaction::Ok(Transaction::new_basic(
+            Address::from_user_friendly_address("NQ09 VF5Y 1PKV MRM4 5LE1 55KV P6R2 GXYJ XYQF")
+                .unwrap(),
+            Address::burn_address(),
+            Coin::from_u64_unchecked(value),
+            Coin::ZERO,
+            0,
+            NetworkId::UnitAlbatross,
+        ))),
+    }
+}
+
+/// Builds a history tree from scratch and returns its root together with a proof for the
+/// historic transactions at the given positions.
+fn prove(positions: Vec<usize>) -> (Blake2bHash, HistoryTreeProof) {
+    let hist_txs: Vec<_> = (0..7).map(historic_transaction).collect();
+
+    let mut tree = MerkleMountainRange::new(MemoryStore::new());
+    for hist_tx in &hist_txs {
+        tree.push(hist_tx).unwrap();
+    }
+
+    let proof = HistoryTreeProof {
+        proof: tree.prove(&positions, None).unwrap(),
+        history: positions.iter().map(|&i| hist_txs[i].clone()).collect(),
+        positions,
+    };
+    (tree.get_root().unwrap(), proof)
+}
+
+#[test]
+fn it_verifies_a_valid_proof() {
+    let (root, proof) = prove(vec![1, 4, 6]);
+    assert_eq!(proof.verify(root), Some(true));
+}
+
+#[test]
+fn it_rejects_a_tampered_proof() {
+    let (root, proof) = prove(vec![1, 4, 6]);
+
+    // A different root.
+    assert_eq!(proof.verify(Blake2bHash::default()), Some(false));
+
+    // A modified historic transaction.
+    let mut tampered = prove(vec![1, 4, 6]).1;
+    tampered.history[1] = historic_transaction(100);
+    assert_eq!(tampered.verify(root.clone()), Some(false));
+
+    // A historic transaction claimed at a different position.
+    let mut tampered = prove(vec![1, 4, 6]).1;
+    tampered.history.swap(0, 1);
+    assert_eq!(tampered.verify(root.clone()), Some(false));
+
+    // Positions that don't match the historic transactions.
+    let mut tampered = prove(vec![1, 4, 6]).1;
+    tampered.positions.pop();
+    assert_eq!(tampered.verify(root), None);
+}
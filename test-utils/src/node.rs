@@ -4,7 +4,13 @@ use nimiq_block::Block;
 use nimiq_blockchain::{Blockchain, BlockchainConfig};
 use nimiq_blockchain_proxy::BlockchainProxy;
 use nimiq_bls::cache::PublicKeyCache;
-use nimiq_consensus::{sync::syncer_proxy::SyncerProxy, Consensus};
+use nimiq_consensus::{
+    sync::{
+        history::{DiversityPolicy, DEFAULT_MAX_PENDING_CHUNKS},
+        syncer_proxy::SyncerProxy,
+    },
+    Consensus,
+};
 use nimiq_database::mdbx::MdbxDatabase;
 use nimiq_genesis_builder::GenesisInfo;
 use nimiq_network_interface::network::Network as NetworkInterface;
@@ -97,6 +103,8 @@ impl<N: NetworkInterface + TestNetwork> Node<N> {
                 TESTING_BLS_CACHE_MAX_CAPACITY,
             ))),
             network.subscribe_events(),
+            DEFAULT_MAX_PENDING_CHUNKS,
+            DiversityPolicy::Disabled,
         )
         .await;
         let consensus = Consensus::<N>::new(
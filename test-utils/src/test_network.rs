@@ -78,7 +78,7 @@ impl TestNetwork for Network {
             NonZeroU8::new(1).unwrap(),
         );
         let network = Arc::new(Network::new(config).await);
-        network.listen_on(vec![peer_address]).await;
+        network.listen_on(vec![peer_address]).await.unwrap();
         network
     }
 
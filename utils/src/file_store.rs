@@ -4,9 +4,14 @@ use std::{
     path::{Path, PathBuf},
 };
 
+#[cfg(feature = "otp")]
+use nimiq_hash::{argon2kdf::Argon2Error, Blake2bHash, Blake2bHasher, Hasher};
 use nimiq_serde::{Deserialize, DeserializeError, Serialize};
 use thiserror::Error;
 
+#[cfg(feature = "otp")]
+use crate::otp::{Locked, Unlocked, Verify};
+
 pub struct FileStore {
     path: PathBuf,
 }
@@ -41,15 +46,93 @@ impl FileStore {
         }
     }
 
+    /// Stores the item. The item is written to a temporary file first, which then replaces the
+    /// file, such that the file is never left partially written.
     pub fn store<T: Serialize>(&self, item: &T) -> Result<(), Error> {
         log::debug!(path = ?self.path.display(), "Writing to file");
 
-        let file = create_file_creating_parent_if_not_exists(&self.path)?;
+        let tmp_path = self.tmp_path();
+        let file = create_file_creating_parent_if_not_exists(&tmp_path)?;
         let mut buf_writer = BufWriter::new(file);
         Serialize::serialize(item, &mut buf_writer)?;
         buf_writer.flush()?;
+        buf_writer.get_ref().sync_all()?;
+        fs::rename(&tmp_path, &self.path)?;
         Ok(())
     }
+
+    fn tmp_path(&self) -> PathBuf {
+        let mut file_name = self.path.file_name().unwrap_or_default().to_owned();
+        file_name.push(".tmp");
+        self.path.with_file_name(file_name)
+    }
+
+    /// Loads from a file that was written with `store_encrypted`, decrypting its contents with
+    /// the given password.
+    #[cfg(feature = "otp")]
+    pub fn load_encrypted<T: Deserialize>(&self, password: &[u8]) -> Result<T, Error> {
+        let locked: Locked<EncryptedData> = self.load()?;
+        let unlocked = locked
+            .unlock(password)
+            .map_err(|_| Error::InvalidPassword)?;
+        Ok(T::deserialize_from_vec(
+            &Unlocked::unlocked_data(&unlocked).data,
+        )?)
+    }
+
+    /// Like `load_or_store`, but the file contents are encrypted with the given password.
+    #[cfg(feature = "otp")]
+    pub fn load_or_store_encrypted<T, F>(&self, password: &[u8], mut f: F) -> Result<T, Error>
+    where
+        T: Serialize + Deserialize,
+        F: FnMut() -> T,
+    {
+        match self.load_encrypted(password) {
+            Err(Error::Io(err)) if err.kind() == io::ErrorKind::NotFound => {
+                log::debug!(path = ?self.path.display(), "File does not exist, falling back to default");
+                let default = f();
+                self.store_encrypted(&default, password)?;
+                Ok(default)
+            }
+            Ok(result) => Ok(result),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Stores the item encrypted with the given password. The password is stretched with
+    /// Argon2id before it is used for the encryption.
+    #[cfg(feature = "otp")]
+    pub fn store_encrypted<T: Serialize>(&self, item: &T, password: &[u8]) -> Result<(), Error> {
+        let data = EncryptedData::new(item.serialize_to_vec());
+        let locked = Unlocked::lock(Unlocked::with_defaults(data, password)?);
+        self.store(&locked)
+    }
+}
+
+/// Serialized item stored by `FileStore::store_encrypted`. The checksum is used to detect a wrong
+/// password when decrypting it.
+#[cfg(feature = "otp")]
+#[derive(Default, Serialize, Deserialize)]
+struct EncryptedData {
+    checksum: Blake2bHash,
+    data: Vec<u8>,
+}
+
+#[cfg(feature = "otp")]
+impl EncryptedData {
+    fn new(data: Vec<u8>) -> Self {
+        EncryptedData {
+            checksum: Blake2bHasher::default().digest(&data),
+            data,
+        }
+    }
+}
+
+#[cfg(feature = "otp")]
+impl Verify for EncryptedData {
+    fn verify(&self) -> bool {
+        Blake2bHasher::default().digest(&self.data) == self.checksum
+    }
 }
 
 fn create_file_creating_parent_if_not_exists(path: &Path) -> io::Result<File> {
@@ -72,4 +155,12 @@ pub enum Error {
 
     #[error("IO error: {0}")]
     Io(#[from] io::Error),
+
+    #[cfg(feature = "otp")]
+    #[error("Key derivation error: {0}")]
+    KeyDerivation(#[from] Argon2Error),
+
+    #[cfg(feature = "otp")]
+    #[error("Invalid password")]
+    InvalidPassword,
 }
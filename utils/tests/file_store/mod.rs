@@ -0,0 +1,46 @@
+use nimiq_test_log::test;
+use nimiq_utils::file_store::FileStore;
+use tempfile::tempdir;
+
+#[test]
+fn it_stores_the_default_value_once() {
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("keys").join("key.dat");
+    let file_store = FileStore::new(&path);
+
+    let value: u64 = file_store.load_or_store(|| 12345u64).unwrap();
+    assert_eq!(value, 12345);
+
+    let value: u64 = file_store.load_or_store(|| 0u64).unwrap();
+    assert_eq!(value, 12345);
+
+    // Only the stored file remains, the temporary file was moved into place.
+    let files: Vec<_> = std::fs::read_dir(path.parent().unwrap())
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name())
+        .collect();
+    assert_eq!(files, vec!["key.dat"]);
+}
+
+#[cfg(feature = "otp")]
+#[test]
+fn it_stores_and_loads_encrypted_values() {
+    let dir = tempdir().unwrap();
+    let file_store = FileStore::new(dir.path().join("key.dat"));
+
+    let value: u64 = file_store
+        .load_or_store_encrypted(b"password", || 12345u64)
+        .unwrap();
+    assert_eq!(value, 12345);
+
+    // The stored value is loaded instead of storing a new one.
+    let value: u64 = file_store
+        .load_or_store_encrypted(b"password", || 0u64)
+        .unwrap();
+    assert_eq!(value, 12345);
+
+    assert!(matches!(
+        file_store.load_encrypted::<u64>(b"wrong password"),
+        Err(nimiq_utils::file_store::Error::InvalidPassword)
+    ));
+}
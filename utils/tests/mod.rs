@@ -1,5 +1,7 @@
 #[cfg(feature = "crc")]
 pub mod crc;
+#[cfg(feature = "key-store")]
+pub mod file_store;
 #[cfg(feature = "merkle")]
 pub mod merkle;
 #[cfg(feature = "otp")]
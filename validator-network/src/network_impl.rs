@@ -6,7 +6,9 @@ use log::warn;
 use nimiq_bls::{lazy::LazyPublicKey, CompressedPublicKey, KeyPair, SecretKey};
 use nimiq_network_interface::{
     network::{CloseReason, MsgAcceptance, Network, SubscribeEvents, Topic},
-    request::{InboundRequestError, Message, Request, RequestCommon, RequestError},
+    request::{
+        InboundRequestError, Message, Request, RequestCommon, RequestError, RequestPriority,
+    },
 };
 use nimiq_serde::{Deserialize, Serialize};
 use nimiq_utils::spawn;
@@ -278,6 +280,7 @@ impl<M: RequestCommon> RequestCommon for ValidatorMessage<M> {
     const TYPE_ID: u16 = 10_000 + M::TYPE_ID;
     type Response = M::Response;
     const MAX_REQUESTS: u32 = M::MAX_REQUESTS;
+    const PRIORITY: RequestPriority = M::PRIORITY;
 }
 
 // Proposal - gossip
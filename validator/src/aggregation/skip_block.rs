@@ -26,7 +26,7 @@ use nimiq_handel::{
     update::LevelUpdate,
 };
 use nimiq_hash::Blake2sHash;
-use nimiq_network_interface::request::{MessageMarker, RequestCommon};
+use nimiq_network_interface::request::{MessageMarker, RequestCommon, RequestPriority};
 use nimiq_primitives::{policy, slots_allocation::Validators, Message};
 use nimiq_validator_network::ValidatorNetwork;
 use parking_lot::RwLock;
@@ -145,6 +145,7 @@ impl RequestCommon for SkipBlockUpdate {
     const TYPE_ID: u16 = 123;
     const MAX_REQUESTS: u32 = 500;
     const TIME_WINDOW: Duration = Duration::from_millis(500);
+    const PRIORITY: RequestPriority = RequestPriority::High;
     type Response = ();
 }
 
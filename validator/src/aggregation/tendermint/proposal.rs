@@ -8,7 +8,7 @@ use nimiq_hash::{Blake2sHash, Blake2sHasher, Hash, Hasher, SerializeContent};
 use nimiq_keys::Ed25519Signature as SchnorrSignature;
 use nimiq_network_interface::{
     network::Network,
-    request::{Handle, RequestCommon, RequestMarker},
+    request::{Handle, RequestCommon, RequestMarker, RequestPriority},
 };
 use nimiq_primitives::TendermintStep;
 use nimiq_serde::Serialize;
@@ -160,6 +160,7 @@ impl RequestCommon for RequestProposal {
     const TYPE_ID: u16 = 199;
     type Response = Option<SignedProposal>;
     const MAX_REQUESTS: u32 = MAX_REQUEST_RESPONSE_PROPOSAL;
+    const PRIORITY: RequestPriority = RequestPriority::High;
 }
 
 impl<N: Network> Handle<N, Arc<RwLock<Option<MacroState>>>> for RequestProposal {
@@ -1,6 +1,6 @@
 use std::time::Duration;
 
-use nimiq_network_interface::request::{MessageMarker, RequestCommon};
+use nimiq_network_interface::request::{MessageMarker, RequestCommon, RequestPriority};
 use nimiq_tendermint::TaggedAggregationMessage;
 use serde::{Deserialize, Serialize};
 
@@ -17,5 +17,6 @@ impl RequestCommon for TendermintUpdate {
     const TYPE_ID: u16 = 124;
     const MAX_REQUESTS: u32 = 500;
     const TIME_WINDOW: Duration = Duration::from_millis(500);
+    const PRIORITY: RequestPriority = RequestPriority::High;
     type Response = ();
 }
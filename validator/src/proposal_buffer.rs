@@ -566,7 +566,11 @@ mod test {
     use nimiq_blockchain_proxy::BlockchainProxy;
     use nimiq_bls::cache::PublicKeyCache;
     use nimiq_consensus::{
-        sync::syncer_proxy::SyncerProxy, Consensus, ConsensusEvent, ConsensusProxy,
+        sync::{
+            history::{DiversityPolicy, DEFAULT_MAX_PENDING_CHUNKS},
+            syncer_proxy::SyncerProxy,
+        },
+        Consensus, ConsensusEvent, ConsensusProxy,
     };
     use nimiq_keys::{KeyPair as SchnorrKeyPair, PrivateKey as SchnorrPrivateKey};
     use nimiq_network_interface::network::Network as NetworkInterface;
@@ -608,6 +612,8 @@ mod test {
             Arc::clone(&net),
             Arc::new(Mutex::new(PublicKeyCache::new(10))),
             net.subscribe_events(),
+            DEFAULT_MAX_PENDING_CHUNKS,
+            DiversityPolicy::Disabled,
         )
         .await;
 
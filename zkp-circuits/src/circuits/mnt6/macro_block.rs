@@ -36,6 +36,11 @@ use crate::{
 /// Since the state is composed only of the block number and the public keys of the current validator
 /// list, updating the state is just incrementing the block number and substituting the previous
 /// public keys with the public keys of the new validator list.
+///
+/// The public key tree always contains one public key per slot (`Policy::SLOTS` keys in total),
+/// where a validator's key is repeated for each of its slots. The circuit is therefore independent
+/// of the number of validators: an epoch with fewer validators than slots needs no padding, since
+/// every slot is still assigned to one of the validators.
 #[derive(Clone)]
 pub struct MacroBlockCircuit {
     // Witnesses (private)
@@ -48,6 +53,9 @@ pub struct MacroBlockCircuit {
     prev_block: MacroBlock,
     final_block: MacroBlock,
 
+    // Constants
+    min_signers: u16,
+
     // Inputs (public)
     pub prev_header_hash: [u8; 32],
     pub final_header_hash: [u8; 32],
@@ -59,6 +67,11 @@ impl CircuitInput for MacroBlockCircuit {
 }
 
 impl MacroBlockCircuit {
+    /// Creates the circuit. The final block must be signed by at least `min_signers` slots,
+    /// usually `Policy::TWO_F_PLUS_ONE`.
+    ///
+    /// The threshold is a constant of the circuit, so the proving and verifying keys must have
+    /// been generated with the same threshold (see [`MacroBlockCircuit::rand`]).
     pub fn new(
         keys: VerifyingKeys,
         prev_pk_tree_proof: Proof<MNT6_753>,
@@ -68,6 +81,7 @@ impl MacroBlockCircuit {
         r_agg_pk_commitment: G2Projective,
         prev_block: MacroBlock,
         final_block: MacroBlock,
+        min_signers: u16,
     ) -> Self {
         let prev_header_hash = prev_block.hash_blake2s().0;
         let final_header_hash = final_block.hash_blake2s().0;
@@ -82,11 +96,14 @@ impl MacroBlockCircuit {
             r_agg_pk_commitment,
             prev_block,
             final_block,
+            min_signers,
             prev_header_hash,
             final_header_hash,
         }
     }
 
+    /// Creates a random circuit for the key generation, using `Policy::TWO_F_PLUS_ONE` as the
+    /// signer threshold.
     pub fn rand<R: Rng + ?Sized>(rng: &mut R) -> Self {
         let proof = Proof {
             a: G1Affine::rand(rng),
@@ -121,6 +138,7 @@ impl MacroBlockCircuit {
             r_agg_commitment,
             prev_block,
             final_block,
+            Policy::TWO_F_PLUS_ONE,
         )
     }
 }
@@ -249,7 +267,7 @@ impl ConstraintSynthesizer<MNT6Fq> for MacroBlockCircuit {
 
         // Verifying that the block is valid.
         final_block_var
-            .verify_signature(cs, &agg_pk_var)?
+            .verify_signature(cs, &agg_pk_var, self.min_signers)?
             .enforce_equal(&Boolean::constant(true))?;
 
         Ok(())
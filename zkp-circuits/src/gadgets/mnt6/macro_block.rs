@@ -63,9 +63,11 @@ impl MacroBlockGadget {
         cs: ConstraintSystemRef<MNT6Fq>,
         // This is the aggregated public key.
         agg_pk: &G2Var,
+        // This is the minimum number of slots that need to have signed the block.
+        min_signers: u16,
     ) -> Result<Boolean<MNT6Fq>, SynthesisError> {
         // Verify that there are enough signers.
-        let enough_signers = self.check_signers(cs.clone())?;
+        let enough_signers = self.check_signers(cs.clone(), min_signers)?;
 
         // Get the hash point for the signature.
         let hash = self.tendermint_hash(cs.clone())?;
@@ -156,13 +158,14 @@ impl MacroBlockGadget {
         Ok(g1_point)
     }
 
-    /// A function that checks if there are enough signers.
+    /// A function that checks if there are at least `min_signers` signers.
     pub fn check_signers(
         &self,
         cs: ConstraintSystemRef<MNT6Fq>,
+        min_signers: u16,
     ) -> Result<Boolean<MNT6Fq>, SynthesisError> {
         // Get the minimum number of signers.
-        let min_signers = FqVar::new_constant(cs, MNT6Fq::from(Policy::TWO_F_PLUS_ONE as u64))?;
+        let min_signers = FqVar::new_constant(cs, MNT6Fq::from(min_signers as u64))?;
 
         // Initialize the running sum.
         let mut num_signers = FqVar::zero();
@@ -538,12 +541,51 @@ mod tests {
 
         // Verify block.
         assert!(block_var
-            .verify_signature(cs, &agg_pk_var)
+            .verify_signature(cs, &agg_pk_var, Policy::TWO_F_PLUS_ONE)
             .unwrap()
             .value()
             .unwrap());
     }
 
+    #[test]
+    fn block_verify_min_signers() {
+        // Create random number generator.
+        let rng = &mut test_rng(true);
+
+        // Create macro block signed by all slots.
+        let mut block = MacroBlock::non_empty_default();
+        block.header.network = NetworkId::UnitAlbatross;
+        block.header.block_number = u32::rand(rng);
+        block.header.round = u32::rand(rng);
+
+        let (block, agg_pk) = TemporaryBlockProducer::finalize_macro_block(
+            ProposalMessage {
+                round: block.header.round,
+                valid_round: None,
+                proposal: block.header.clone(),
+            },
+            block.body.clone().unwrap(),
+            block.hash_blake2s(),
+        );
+
+        // Verify block with a threshold that is met and one that is not.
+        for (min_signers, expected) in [(Policy::SLOTS, true), (Policy::SLOTS + 1, false)] {
+            let cs = ConstraintSystem::<MNT6Fq>::new_ref();
+            let mut block_var =
+                MacroBlockGadget::new_witness(cs.clone(), || Ok(block.clone())).unwrap();
+            let agg_pk_var = G2Var::new_witness(cs.clone(), || Ok(agg_pk.0.public_key)).unwrap();
+
+            assert_eq!(
+                block_var
+                    .verify_signature(cs, &agg_pk_var, min_signers)
+                    .unwrap()
+                    .value()
+                    .unwrap(),
+                expected
+            );
+        }
+    }
+
     #[test]
     fn block_verify_wrong_block_number() {
         // Initialize the constraint system.
@@ -580,7 +622,7 @@ mod tests {
 
         // Verify block.
         assert!(!block_var
-            .verify_signature(cs, &agg_pk_var)
+            .verify_signature(cs, &agg_pk_var, Policy::TWO_F_PLUS_ONE)
             .unwrap()
             .value()
             .unwrap());
@@ -622,7 +664,7 @@ mod tests {
 
         // Verify block.
         assert!(!block_var
-            .verify_signature(cs, &agg_pk_var)
+            .verify_signature(cs, &agg_pk_var, Policy::TWO_F_PLUS_ONE)
             .unwrap()
             .value()
             .unwrap());
@@ -664,7 +706,7 @@ mod tests {
 
         // Verify block.
         assert!(!block_var
-            .verify_signature(cs, &agg_pk_var)
+            .verify_signature(cs, &agg_pk_var, Policy::TWO_F_PLUS_ONE)
             .unwrap()
             .value()
             .unwrap());
@@ -712,7 +754,7 @@ mod tests {
 
         // Verify block.
         assert!(!block_var
-            .verify_signature(cs, &agg_pk_var)
+            .verify_signature(cs, &agg_pk_var, Policy::TWO_F_PLUS_ONE)
             .unwrap()
             .value()
             .unwrap());
@@ -754,7 +796,7 @@ mod tests {
 
         // Verify block.
         assert!(!block_var
-            .verify_signature(cs, &agg_pk_var)
+            .verify_signature(cs, &agg_pk_var, Policy::TWO_F_PLUS_ONE)
             .unwrap()
             .value()
             .unwrap());
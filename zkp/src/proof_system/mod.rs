@@ -1,6 +1,7 @@
 use ark_groth16::Proof;
 use ark_mnt6_753::MNT6_753;
 
+pub mod proof;
 #[cfg(feature = "zkp-prover")]
 pub mod prove;
 pub mod verify;
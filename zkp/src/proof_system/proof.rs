@@ -0,0 +1,48 @@
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize, SerializationError};
+use thiserror::Error;
+
+use crate::NanoProof;
+
+/// The current version of the proof encoding.
+pub const PROOF_VERSION: u8 = 1;
+
+/// The size of a serialized proof, including the version prefix. A proof consists of two
+/// compressed G1 points (95 bytes each) and one compressed G2 point (285 bytes) on MNT6-753.
+pub const PROOF_SIZE: usize = 1 + 95 + 285 + 95;
+
+#[derive(Debug, Error)]
+pub enum ProofError {
+    #[error("unsupported proof version: {0}")]
+    UnsupportedVersion(u8),
+    #[error("invalid proof length: {0}")]
+    InvalidLength(usize),
+    #[error("serialization error: {0}")]
+    Serialization(#[from] SerializationError),
+}
+
+/// Serializes a proof for transport.
+///
+/// Version 1 of the encoding is the version byte followed by the compressed points `a`, `b` and
+/// `c` of the proof, as encoded by `CanonicalSerialize`.
+pub fn serialize_proof(proof: &NanoProof) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(PROOF_SIZE);
+    bytes.push(PROOF_VERSION);
+    // Serializing into a vector can't fail.
+    proof.serialize_compressed(&mut bytes).unwrap();
+    bytes
+}
+
+/// Deserializes a proof that was serialized with [`serialize_proof`]. The points of the proof
+/// are checked to be valid, so this can be used on untrusted input.
+pub fn deserialize_proof(bytes: &[u8]) -> Result<NanoProof, ProofError> {
+    match bytes.first() {
+        Some(&PROOF_VERSION) => {}
+        Some(&version) => return Err(ProofError::UnsupportedVersion(version)),
+        None => return Err(ProofError::InvalidLength(0)),
+    }
+    if bytes.len() != PROOF_SIZE {
+        return Err(ProofError::InvalidLength(bytes.len()));
+    }
+
+    Ok(NanoProof::deserialize_compressed(&bytes[1..])?)
+}
@@ -730,6 +730,7 @@ fn prove_macro_block<R: CryptoRng + Rng>(
         agg_pk_chunks[1],
         prev_block,
         final_block,
+        Policy::TWO_F_PLUS_ONE,
     );
     let prev_header_hash = circuit.prev_header_hash;
     let final_header_hash = circuit.final_header_hash;
@@ -1,8 +1,12 @@
+use std::{fs::File, path::Path};
+
 use ark_crypto_primitives::snark::SNARK;
 use ark_ff::ToConstraintField;
-use ark_groth16::{Groth16, Proof};
-use ark_mnt6_753::MNT6_753;
+use ark_groth16::{Groth16, PreparedVerifyingKey, Proof, VerifyingKey};
+use ark_mnt6_753::{Fr as MNT6Fr, MNT6_753};
+use ark_serialize::CanonicalDeserialize;
 use nimiq_hash::Blake2sHash;
+use nimiq_zkp_circuits::metadata::VerifyingKeyMetadata;
 use nimiq_zkp_primitives::{NanoZKPError, VerifyingData};
 
 /// This function verifies a proof for the Merger Wrapper circuit, which implicitly is a proof for
@@ -18,11 +22,11 @@ pub fn verify(
     verifying_data: &VerifyingData,
 ) -> Result<bool, NanoZKPError> {
     // Prepare the inputs.
-    let mut inputs = vec![];
-
-    inputs.append(&mut genesis_header_hash.0.to_field_elements().unwrap());
-    inputs.append(&mut final_header_hash.0.to_field_elements().unwrap());
-    inputs.append(&mut verifying_data.keys_commitment.to_field_elements().unwrap());
+    let inputs = public_inputs(
+        &genesis_header_hash,
+        &final_header_hash,
+        &verifying_data.keys_commitment,
+    );
 
     // Verify proof.
     let result = Groth16::<MNT6_753>::verify(&verifying_data.merger_wrapper_vk, &inputs, &proof)?;
@@ -30,3 +34,82 @@ pub fn verify(
     // Return result.
     Ok(result)
 }
+
+/// Computes the public inputs of a proof for the Merger Wrapper circuit.
+///
+/// The header hashes are the Blake2s hashes of the macro block headers (see
+/// `MacroBlock::hash_blake2s`) and the keys commitment is the commitment to the verifying keys of
+/// all circuits (see `VerifyingData::keys_commitment`).
+///
+/// The inputs are the concatenation of the field elements of, in this order, the genesis header
+/// hash, the final header hash and the keys commitment. Each of these byte strings is split into
+/// chunks of 94 bytes (the last chunk may be shorter) and each chunk is interpreted as a
+/// little-endian integer, which is the field element. Hence, each header hash results in one
+/// field element and the keys commitment in three (of 94, 94 and 2 bytes), i.e. five in total.
+pub fn public_inputs(
+    genesis_header_hash: &Blake2sHash,
+    final_header_hash: &Blake2sHash,
+    keys_commitment: &[u8; 95 * 2],
+) -> Vec<MNT6Fr> {
+    let mut inputs = vec![];
+
+    inputs.append(&mut genesis_header_hash.0.to_field_elements().unwrap());
+    inputs.append(&mut final_header_hash.0.to_field_elements().unwrap());
+    inputs.append(&mut keys_commitment.to_field_elements().unwrap());
+
+    inputs
+}
+
+/// A verifier for the Merger Wrapper circuit that prepares the verifying key only once, such that
+/// it can be used to verify many proofs. It is cheap to clone and can be shared across threads.
+#[derive(Clone)]
+pub struct PreparedVerifier {
+    prepared_vk: PreparedVerifyingKey<MNT6_753>,
+    keys_commitment: [u8; 95 * 2],
+}
+
+impl PreparedVerifier {
+    pub fn new(verifying_data: &VerifyingData) -> Self {
+        Self {
+            prepared_vk: Groth16::<MNT6_753>::process_vk(&verifying_data.merger_wrapper_vk)
+                .expect("Preparing the verifying key can't fail"),
+            keys_commitment: verifying_data.keys_commitment,
+        }
+    }
+
+    /// Loads the verifying key and its metadata from a directory as created by the ZKP setup,
+    /// i.e. from `verifying_keys/merger_wrapper.bin` and `meta_data.json`.
+    pub fn from_path(path: &Path) -> Result<Self, NanoZKPError> {
+        let mut file = File::open(path.join("verifying_keys").join("merger_wrapper.bin"))?;
+        let merger_wrapper_vk = VerifyingKey::deserialize_uncompressed_unchecked(&mut file)?;
+
+        let file = File::open(path.join("meta_data.json"))?;
+        let metadata: VerifyingKeyMetadata =
+            serde_json::from_reader(file).map_err(|_| NanoZKPError::InvalidMetadata)?;
+
+        Ok(Self::new(&VerifyingData {
+            merger_wrapper_vk,
+            keys_commitment: *metadata.vks_commitment(),
+        }))
+    }
+
+    /// Verifies a proof for the Merger Wrapper circuit, see [`verify`].
+    pub fn verify(
+        &self,
+        genesis_header_hash: &Blake2sHash,
+        final_header_hash: &Blake2sHash,
+        proof: &Proof<MNT6_753>,
+    ) -> Result<bool, NanoZKPError> {
+        let inputs = public_inputs(
+            genesis_header_hash,
+            final_header_hash,
+            &self.keys_commitment,
+        );
+
+        Ok(Groth16::<MNT6_753>::verify_with_processed_vk(
+            &self.prepared_vk,
+            &inputs,
+            proof,
+        )?)
+    }
+}
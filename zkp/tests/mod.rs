@@ -1,2 +1,4 @@
+mod proof;
 #[cfg(feature = "zkp-prover")]
 mod prover;
+mod verify;
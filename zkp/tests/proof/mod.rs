@@ -0,0 +1,45 @@
+use ark_ff::UniformRand;
+use ark_mnt6_753::{G1Affine, G2Affine};
+use nimiq_test_log::test;
+use nimiq_zkp::{
+    proof::{deserialize_proof, serialize_proof, ProofError, PROOF_SIZE},
+    NanoProof,
+};
+
+fn random_proof() -> NanoProof {
+    let mut rng = rand::thread_rng();
+    NanoProof {
+        a: G1Affine::rand(&mut rng),
+        b: G2Affine::rand(&mut rng),
+        c: G1Affine::rand(&mut rng),
+    }
+}
+
+#[test]
+fn proof_serialization_round_trips() {
+    let proof = random_proof();
+
+    let bytes = serialize_proof(&proof);
+    assert_eq!(bytes.len(), PROOF_SIZE);
+    assert_eq!(deserialize_proof(&bytes).unwrap(), proof);
+}
+
+#[test]
+fn proof_deserialization_rejects_invalid_input() {
+    let mut bytes = serialize_proof(&random_proof());
+
+    assert!(matches!(
+        deserialize_proof(&bytes[..PROOF_SIZE - 1]),
+        Err(ProofError::InvalidLength(_))
+    ));
+    assert!(matches!(
+        deserialize_proof(&[]),
+        Err(ProofError::InvalidLength(0))
+    ));
+
+    bytes[0] = 2;
+    assert!(matches!(
+        deserialize_proof(&bytes),
+        Err(ProofError::UnsupportedVersion(2))
+    ));
+}
@@ -0,0 +1,36 @@
+use ark_ff::{Field, One};
+use ark_mnt6_753::Fr as MNT6Fr;
+use nimiq_hash::Blake2sHash;
+use nimiq_test_log::test;
+use nimiq_zkp::verify::public_inputs;
+
+#[test]
+fn public_inputs_match_the_specification() {
+    let mut genesis_header_hash = [0u8; 32];
+    genesis_header_hash[0] = 1;
+    let mut final_header_hash = [0u8; 32];
+    final_header_hash[1] = 1;
+    let mut keys_commitment = [0u8; 95 * 2];
+    keys_commitment[0] = 2;
+    keys_commitment[93] = 1;
+    keys_commitment[94] = 3;
+    keys_commitment[189] = 1;
+
+    let inputs = public_inputs(
+        &Blake2sHash(genesis_header_hash),
+        &Blake2sHash(final_header_hash),
+        &keys_commitment,
+    );
+
+    let two_pow_8 = MNT6Fr::from(256u64);
+    assert_eq!(
+        inputs,
+        vec![
+            MNT6Fr::one(),
+            two_pow_8,
+            two_pow_8.pow([93]) + MNT6Fr::from(2u64),
+            MNT6Fr::from(3u64),
+            two_pow_8,
+        ]
+    );
+}